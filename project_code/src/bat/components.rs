@@ -1,5 +1,4 @@
 use bevy::prelude::*;
-use crate::shop::components::Item;
 
 //constants
 pub const BAT_ANIMATION_TIME: f32 = 0.2;
@@ -18,18 +17,8 @@ pub const BAT_AGRO_RANGE: f32 = 700.;
 pub struct Bat {
     pub rotation_speed: f32,
     pub current_hp: f32,
-    pub max_hp: f32,
 }
 
 #[derive(Component)]
 pub struct BatProjectile;
 
-#[derive(Component)]
-pub struct Loot;
-
-#[derive(Bundle)]
-pub struct LootBundle {
-    pub item: Item,
-    pub sprite_bundle: SpriteBundle,
-    pub marker: Loot,
-}
@@ -1,10 +1,11 @@
 use bevy::prelude::*;
-use bevy::render::texture;
 
 use crate::bat::components::*;
-use crate::data::gameworld_data::*;
+use crate::dungeon_sync::components::DungeonEnemy;
+use crate::dungeon_sync::systems::report_dungeon_kill;
 use crate::enemies::*;
 use crate::hitbox_system::*;
+use crate::network::components::{Server, UDP};
 use crate::player::components::*;
 use crate::shop::systems::generate_loot_item;
 
@@ -72,46 +73,18 @@ pub fn animate_bat(
     }
 }
 
-/*  SPAWN_BAT FUNCTION  */
-/// Spawns a bat entity in the gameworld
-pub fn spawn_bat(
-    mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    mut texture_atlases: ResMut<Assets<TextureAtlasLayout>>,
-) {
-    let transform = Transform::from_xyz(0., -(WIN_H / 2.) + ((TILE_SIZE as f32) * 1.5), 900.)
-        .with_scale(Vec3::splat(2.0));
-
-    spawn_enemy(
-        &mut commands,
-        EnemyT::Bat,
-        transform,
-        &asset_server,
-        &mut texture_atlases,
-    );
-    //spawning bat 1 and setting bat information
-
-    let transform = Transform::from_xyz(200., -(WIN_H / 2.) + ((TILE_SIZE as f32) * 1.5), 900.)
-        .with_scale(Vec3::splat(2.0));
-
-    spawn_enemy(
-        &mut commands,
-        EnemyT::Bat,
-        transform,
-        &asset_server,
-        &mut texture_atlases,
-    );
-}
-
 /*   BAT_DAMAGED FUNCTION   */
 /// Current functionality: Detects when a player is within player attack range (this will later be replaced with
 // player weapon/attack collision) and then takes 1 damage (dies)
 pub fn bat_damaged(
     mut commands: Commands,
     mut bat_query: Query<(&mut Bat, Entity, &mut Hurtbox, &Transform), With<Bat>>,
+    dungeon_enemies: Query<&DungeonEnemy>,
     mut player_query: Query<&mut Player>,
+    mut udp: ResMut<UDP>,
+    server: Res<Server>,
 ) {
-    for (mut bat, entity, mut hurtbox, transform) in bat_query.iter_mut() {
+    for (mut bat, entity, mut hurtbox, _transform) in bat_query.iter_mut() {
         if !hurtbox.colliding.is {
             continue;
         }
@@ -121,11 +94,16 @@ pub fn bat_damaged(
 
         if bat.current_hp <= 0. {
             println!("Bat was attacked by player, it is dead :(");
-            let loot = generate_loot_item(EnemyT::Bat);
-            if loot.price > 0 {
-                println!("Bat dropped: {}", loot.name);
-                if let Ok(mut player) = player_query.get_single_mut() {
-                    player.inventory.add_item(loot);
+            if let Ok(enemy) = dungeon_enemies.get(entity) {
+                //the loot comes once the server says nobody beat us to it
+                report_dungeon_kill(&mut udp, &server, enemy);
+            } else {
+                let loot = generate_loot_item(EnemyT::Bat);
+                if loot.price > 0 {
+                    println!("Bat dropped: {}", loot.name);
+                    if let Ok(mut player) = player_query.get_single_mut() {
+                        player.inventory.add_item(loot);
+                    }
                 }
             }
             commands.entity(entity).despawn();
@@ -151,6 +129,7 @@ pub fn despawn_all_bats(mut commands: Commands, query: Query<Entity, With<Bat>>)
 /// Things not added:
 /// * Attack cooldown timer
 /// * Projectile shooting
+///
 /// Things currently added:
 /// * Distance-to-player checking
 /// * Attack cooldown timer
@@ -292,11 +271,8 @@ pub fn move_bat(
 
 /*   DESPAWN_ALL_BAT_PROJ   */
 /// Despawns all the bat's projectiles
-pub fn despawn_all_bat_proj(
-    mut commands: Commands,
-    mut proj_query: Query<(Entity, &mut Lifetime)>,
-) {
-    for (entity, mut lifetime) in proj_query.iter_mut() {
+pub fn despawn_all_bat_proj(mut commands: Commands, proj_query: Query<Entity, With<Lifetime>>) {
+    for entity in proj_query.iter() {
         commands.entity(entity).despawn();
     }
 }
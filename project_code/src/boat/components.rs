@@ -1,11 +1,17 @@
 use bevy::prelude::*;
+use protocol::simulation::{BoatInput, BoatState};
+use std::collections::VecDeque;
 
 use crate::components::BoundingBox;
 
-pub const CANNONBALL_SPEED: f32 = 800.;
-pub const CANNONBALL_LIFETIME: f32 = 6.;
-pub const MAX_ACCEL: f32 = 800.;
-pub const BOAT_MAX_HP: f32 = 5.;
+pub use protocol::combat::{BOAT_MAX_HP, CANNONBALL_LIFETIME, CANNONBALL_SPEED, CANNON_COOLDOWN};
+pub const BOAT_HURTBOX_SIZE: Vec2 = Vec2::new(50., 50.);
+
+//prediction constants
+pub const INPUT_HISTORY: usize = 120; //ticks of unacked input kept for replaying
+pub const INPUT_REDUNDANCY: usize = 3; //inputs resent with every tick
+pub const SNAP_DISTANCE: f32 = 128.; //corrections bigger than this aren't smoothed
+pub const ERROR_DECAY: f32 = 10.; //how fast small corrections are smoothed out
 
 /// Struct to represent the boat entity that players will be represented as
 /// in the ocean world
@@ -26,13 +32,36 @@ pub struct Boat {
 #[derive(Component)]
 pub struct Cannonball;
 
-/// Struct to maintain last boat position for out of transition spawning
-pub struct BoatLastPosition {
-    pub last_pos: Vec2,
-}
-
 /// Cannonball velocity struct
 #[derive(Component)]
 pub struct CannonballVelocity {
     pub v: Vec3,
 }
+
+/// Client side prediction for the host's boat. The boat is simulated locally
+/// with the same step function the server uses, and every input the server
+/// hasn't confirmed yet is kept so it can be replayed on top of a snapshot
+#[derive(Component)]
+pub struct BoatPrediction {
+    /// Sequence number of the first input applied to this boat. Snapshots that
+    /// haven't caught up to it are still about a boat from before a respawn
+    pub first_seq: Option<u32>,
+    pub history: VecDeque<BoatInput>,
+    pub state: BoatState,
+    pub prev_state: BoatState,
+    /// Offset between where the boat is drawn and where it is simulated, shrinks
+    /// every frame so corrections from the server don't snap the boat
+    pub error: Vec3,
+}
+
+impl BoatPrediction {
+    pub fn new(state: BoatState) -> BoatPrediction {
+        BoatPrediction {
+            first_seq: None,
+            history: VecDeque::new(),
+            state,
+            prev_state: state,
+            error: Vec3::ZERO,
+        }
+    }
+}
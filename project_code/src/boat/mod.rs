@@ -17,10 +17,17 @@ impl Plugin for BoatPlugin {
             OnEnter(GameworldState::Ocean),
             spawn_boat.after(despawn_player),
         )
+        .add_systems(
+            FixedUpdate,
+            predict_boat
+                .run_if(in_state(GameworldState::Ocean))
+                .run_if(in_state(GameState::Running)),
+        )
         .add_systems(
             Update,
             (
-                move_boat,
+                reconcile_boat,
+                move_boat.after(reconcile_boat),
                 boat_attack.after(move_boat),
                 move_cannonball,
                 cannonball_lifetime_check,
@@ -1,101 +1,158 @@
-use std::thread;
 
 use crate::boat::components::*;
 use crate::components::BoundingBox;
-use crate::components::*;
 use crate::controls::*;
 use crate::enemies::*;
 
-use crate::data::gameworld_data::*;
 use crate::player::components::AttackCooldown;
 use crate::wind::components::Wind;
-use crate::{controls::*, create_env, HostPlayer, Player, Server, UDP};
+use crate::network::components::ServerEvent;
+use crate::network::systems::send;
+use crate::{HostPlayer, Server, UDP};
+use protocol::combat::Weapon;
+use protocol::messages::{ClientMessage, ServerMessage};
+use protocol::reliable::Channel;
+use protocol::simulation::{step_boat, BoatInput, BoatState};
 use crate::{hitbox_system::*, Lifetime};
 use bevy::prelude::*;
 
-/*   MOVE_BOAT FUNCTION   */
-/// Moves and updates the boats position
-pub fn move_boat(
-    time: Res<Time>,
+/*   PREDICT_BOAT FUNCTION   */
+/// Runs once per simulation tick. Turns the player's controls into an input,
+/// applies it to the boat straight away and sends it to the server, which runs
+/// the same input through the same step function
+pub fn predict_boat(
     keyboard_input: Res<ButtonInput<KeyCode>>,
     mouse_input: Res<ButtonInput<MouseButton>>,
     wind: Res<Wind>,
-    mut query: Query<(&mut Boat, &mut Transform)>,
+    mut query: Query<&mut BoatPrediction>,
+    mut udp: ResMut<UDP>,
+    server: Res<Server>,
+    mut last_seq: Local<u32>,
+) {
+    //only the host's boat is predicted
+    for mut prediction in query.iter_mut() {
+        //sequence numbers keep counting across respawns so the server never
+        //mistakes a new input for an old one
+        *last_seq += 1;
+        prediction.first_seq.get_or_insert(*last_seq);
+
+        let input = BoatInput {
+            seq: *last_seq,
+            turn: get_player_input(PlayerControl::Left, &keyboard_input, &mouse_input)
+                - get_player_input(PlayerControl::Right, &keyboard_input, &mouse_input),
+            thrust: get_player_input(PlayerControl::Up, &keyboard_input, &mouse_input),
+        };
+
+        prediction.prev_state = prediction.state;
+        step_boat(&mut prediction.state, &input, wind.direction, server.tick_seconds);
+
+        prediction.history.push_back(input);
+        while prediction.history.len() > INPUT_HISTORY {
+            prediction.history.pop_front();
+        }
+
+        //the last few inputs go out every tick so a lost packet doesn't lose one
+        let skip = prediction.history.len().saturating_sub(INPUT_REDUNDANCY);
+        let inputs = prediction.history.iter().skip(skip).copied().collect();
+
+        send(
+            &mut udp,
+            &server,
+            &ClientMessage::BoatInputs(inputs),
+            Channel::Unreliable,
+        );
+    }
+}
+
+/*   RECONCILE_BOAT FUNCTION   */
+/// Takes the server's word for where the boat was after the last input it
+/// processed, then replays every input it hasn't processed yet on top of that
+pub fn reconcile_boat(
+    mut events: EventReader<ServerEvent>,
+    mut query: Query<(&Boat, &mut BoatPrediction)>,
     host: Res<HostPlayer>,
-    udp: Res<UDP>,
     server: Res<Server>,
 ) {
-    for (mut boat, mut transform) in query.iter_mut() {
-        if boat.id != host.player.id {
+    for ServerEvent(message) in events.read() {
+        let ServerMessage::Snapshot(snapshot) = message else {
             continue;
+        };
+
+        let Some(authoritative) = snapshot.boats.iter().find(|boat| boat.id == host.player.id)
+        else {
+            continue;
+        };
+
+        for (boat, mut prediction) in query.iter_mut() {
+            if boat.id != host.player.id {
+                continue;
+            }
+
+            //the server is still working on an old boat of ours
+            match prediction.first_seq {
+                Some(first_seq) if authoritative.last_input >= first_seq => {}
+                _ => continue,
+            }
+
+            prediction
+                .history
+                .retain(|input| input.seq > authoritative.last_input);
+
+            let predicted = prediction.state;
+            let mut state = authoritative.state;
+            for input in prediction.history.iter() {
+                step_boat(&mut state, input, snapshot.wind, server.tick_seconds);
+            }
+
+            //nothing to correct when the prediction was right
+            let correction = predicted.pos - state.pos;
+            if correction == Vec3::ZERO {
+                continue;
+            }
+
+            prediction.state = state;
+            prediction.prev_state.pos -= correction;
+
+            //small corrections get smoothed out by move_boat, big ones snap
+            prediction.error += correction;
+            if prediction.error.length() > SNAP_DISTANCE {
+                prediction.error = Vec3::ZERO;
+            }
         }
+    }
+}
+
+/*   MOVE_BOAT FUNCTION   */
+/// Moves and updates the boats position. The boat is drawn between its last two
+/// simulation ticks so it moves smoothly at any frame rate
+pub fn move_boat(
+    time: Res<Time>,
+    fixed_time: Res<Time<Fixed>>,
+    mut query: Query<(&mut Boat, &mut BoatPrediction, &mut Transform)>,
+    host: Res<HostPlayer>,
+) {
+    let alpha = fixed_time.overstep_fraction();
 
-        // getting boat and wind direction
-        let boat_direction = transform.rotation * Vec3::Y;
-        let wind_direction = wind.direction;
-
-        // calculate the cosine similarity
-        let dot = boat_direction.truncate().dot(wind_direction);
-        let mag_w = wind_direction.length();
-        let mag_b = boat_direction.length();
-        let cs = dot / (mag_b * mag_w);
-
-        //initializing rotation and movement variables
-        let mut rotation_factor = 0.0;
-        let mut movement_factor = 0.0;
-
-        //getting rotation factor by checking left and right input and subtracting from one another
-        //e.g if left pressed and right no : 1 - 0 = 1
-        //will accout for both left and right being pressed in one check
-        //e.g 1 - 1 = 0
-        rotation_factor += get_player_input(PlayerControl::Left, &keyboard_input, &mouse_input)
-            - get_player_input(PlayerControl::Right, &keyboard_input, &mouse_input);
-
-        //checking if player is pressing up
-        movement_factor = get_player_input(PlayerControl::Up, &keyboard_input, &mouse_input);
-
-        //increasing acceleration if needed
-        if boat.acceleration <= MAX_ACCEL && movement_factor == 1. {
-            boat.acceleration += 3.;
-        } else if boat.acceleration > 0. {
-            boat.acceleration -= 7.;
-        } else if boat.acceleration < 0. {
-            boat.acceleration = 0.;
+    for (mut boat, mut prediction, mut transform) in query.iter_mut() {
+        if boat.id != host.player.id {
+            continue;
         }
 
-        //transforming the players rotation
-        transform.rotate_z(rotation_factor * boat.rotation_speed * time.delta_seconds());
+        //shrinking whatever correction is left over
+        let decay = (-ERROR_DECAY * time.delta_seconds()).exp();
+        prediction.error *= decay;
 
-        //getting movement information
-        let movement_dir = transform.rotation * Vec3::Y;
-        let movement_dis = movement_factor * (boat.movement_speed * time.delta_seconds() * cs)
-            + (0.5 * boat.acceleration * time.delta_seconds());
-        let translation_delta = movement_dir * movement_dis;
+        let prev = prediction.prev_state;
+        let curr = prediction.state;
 
         //moving the boat
-        transform.translation += translation_delta;
+        transform.translation = prev.pos.lerp(curr.pos, alpha) + prediction.error;
+        transform.rotation = prev.rot.slerp(curr.rot, alpha);
+        boat.acceleration = curr.acceleration;
 
-        let extents = Vec3::from((BOUNDS / 2.0, 0.0));
-        transform.translation = transform.translation.min(extents).max(-extents);
         // let pos = (((ship.aabb.aabb.min + ship.aabb.aabb.max) / 2.0) + translation_delta.truncate());
         // ship.aabb.update_position(pos);
         boat.aabb.update_position(transform.translation.truncate());
-
-        let boat = Player {
-            id: boat.id,
-            addr: host.player.addr.clone(),
-            pos: transform.translation,
-            rot: transform.rotation,
-            boat: true,
-            used: true,
-        };
-
-        udp.socket
-            .send_to(
-                create_env("player_update".to_string(), boat).as_bytes(),
-                server.addr.clone(),
-            )
-            .expect("Failed to send [update] packet");
     }
 }
 
@@ -106,15 +163,25 @@ pub fn spawn_boat(
     asset_server: Res<AssetServer>,
     mut texture_atlases: ResMut<Assets<TextureAtlasLayout>>,
     host: Res<HostPlayer>,
-    player_entities: Res<PlayerEntities>,
+    mut udp: ResMut<UDP>,
+    server: Res<Server>,
 ) {
+    //the server puts the boat in the water and simulates it from here on
+    let state = BoatState::spawn();
+    send(
+        &mut udp,
+        &server,
+        &ClientMessage::BoatSpawned,
+        Channel::Reliable,
+    );
+
     //getting boat sprite info
     let boat_sheet_handle = asset_server.load("s_basic_ship.png");
     let boat_layout = TextureAtlasLayout::from_grid(UVec2::splat(100), 2, 2, None, None);
     let boat_layout_handle = texture_atlases.add(boat_layout);
 
     //getting hurtbox information
-    let hurtbox_size = Vec2::new(50., 50.);
+    let hurtbox_size = BOAT_HURTBOX_SIZE;
     let hurtbox_offset = Vec2::new(0., 0.);
 
     //spawning boat
@@ -122,7 +189,8 @@ pub fn spawn_boat(
         SpriteBundle {
             texture: boat_sheet_handle,
             transform: Transform {
-                translation: Vec3::new(0., 0., 900.),
+                translation: state.pos,
+                rotation: state.rot,
                 ..default()
             },
             ..default()
@@ -133,14 +201,15 @@ pub fn spawn_boat(
         },
         Boat {
             id: host.player.id,
-            movement_speed: 150.,
-            rotation_speed: f32::to_radians(100.0),
+            movement_speed: state.movement_speed,
+            rotation_speed: state.rotation_speed,
             acceleration: 0.,
             aabb: BoundingBox::new(Vec2::splat(0.), Vec2::splat(16.)),
-            health: 5.,
-            max_health: 5.,
+            health: BOAT_MAX_HP,
+            max_health: BOAT_MAX_HP,
             cannon_damage: 1.,
         },
+        BoatPrediction::new(state),
         AttackCooldown {
             remaining: Timer::from_seconds(1.5, TimerMode::Once),
         },
@@ -175,7 +244,7 @@ pub fn boat_attack(
 
         /***   ATTACK   ***/
         if get_player_input(PlayerControl::Attack, &keyboard_input, &mouse_input) == 1. {
-            cooldown.remaining = Timer::from_seconds(1.5, TimerMode::Once);
+            cooldown.remaining = Timer::from_seconds(CANNON_COOLDOWN, TimerMode::Once);
 
             //getting cannonball sprite
             let cannonball_handler = asset_server.load("s_cannonball.png");
@@ -212,12 +281,12 @@ pub fn boat_attack(
                 },
                 Hitbox {
                     size: hitbox_size,
-                    offset: offset,
+                    offset,
                     lifetime: Some(Timer::from_seconds(CANNONBALL_LIFETIME, TimerMode::Once)),
                     entity: BOAT,
                     projectile: true,
                     enemy: false,
-                    dmg: 1.,
+                    dmg: Weapon::Cannon.damage(),
                 },
             ));
         }
@@ -273,9 +342,11 @@ pub fn despawn_cannonballs(mut commands: Commands, query: Query<Entity, With<Can
 }
 
 pub fn check_boat_health(
-    mut boat_query: Query<(&mut Boat, Entity, &mut Hurtbox, &mut Transform), With<Boat>>,
+    mut boat_query: Query<(&mut Boat, &mut Hurtbox, &mut BoatPrediction), With<Boat>>,
+    mut udp: ResMut<UDP>,
+    server: Res<Server>,
 ) {
-    for (mut boat, entity, mut hurtbox, mut transform) in boat_query.iter_mut() {
+    for (mut boat, mut hurtbox, mut prediction) in boat_query.iter_mut() {
         if !hurtbox.colliding.is {
             continue;
         }
@@ -285,9 +356,7 @@ pub fn check_boat_health(
 
         if boat.health <= 0. {
             println!("Boat died... yikes!");
-            transform.translation = Vec3::new(0., 0., 900.);
-            boat.health = boat.max_health;
-            //transform.translation = boat.spawn_position;
+            sink_boat(&mut boat, &mut prediction, &mut udp, &server);
             println!("Boat respawned!");
         } else {
             println!("Ouch! Boat was hit... HP: {}", boat.health);
@@ -296,3 +365,15 @@ pub fn check_boat_health(
         hurtbox.colliding.is = false;
     }
 }
+
+/*   SINK_BOAT FUNCTION   */
+/// Puts a boat that ran out of health back in the water at the spawn, here and
+/// on the server. move_boat draws the boat where the prediction says, so that's
+/// what has to start over
+pub fn sink_boat(boat: &mut Boat, prediction: &mut BoatPrediction, udp: &mut UDP, server: &Server) {
+    boat.health = boat.max_health;
+    *prediction = BoatPrediction::new(BoatState::spawn());
+
+    send(udp, server, &ClientMessage::BoatSank, Channel::Reliable);
+    send(udp, server, &ClientMessage::BoatSpawned, Channel::Reliable);
+}
@@ -6,7 +6,6 @@ use crate::GameworldState;
 use bevy::app::Plugin;
 use bevy::prelude::*;
 use crate::boss::systems::*;
-use crate::boss::components::*;
 
 pub struct BossPlugin;
 
@@ -0,0 +1,95 @@
+use bevy::prelude::*;
+use std::collections::VecDeque;
+
+/// Chat lines kept for scrolling back through, older ones are dropped
+pub const CHAT_HISTORY: usize = 100;
+
+/// Chat lines shown at once
+pub const CHAT_VISIBLE_LINES: usize = 8;
+
+//chat colors
+pub const CHAT_TEXT_COLOR: Color = Color::srgb(0.95, 0.9, 0.85);
+pub const CHAT_NAME_COLOR: Color = Color::srgb(0.95, 0.75, 0.3);
+pub const CHAT_OWN_NAME_COLOR: Color = Color::srgb(0.4, 0.85, 0.4);
+pub const CHAT_NOTICE_COLOR: Color = Color::srgb(0.9, 0.3, 0.3);
+
+/// One line of the chat overlay
+pub struct ChatEntry {
+    /// Id of the player who said it, None for notices from the game itself
+    pub from: Option<i32>,
+    pub name: String,
+    pub text: String,
+}
+
+/// Everything said in the room so far, newest last
+#[derive(Resource)]
+pub struct ChatLog {
+    pub lines: VecDeque<ChatEntry>,
+    /// How many lines up from the newest the overlay is scrolled
+    pub scroll: usize,
+    /// Whether the overlay is shown, toggled with Tab
+    pub shown: bool,
+}
+
+impl ChatLog {
+    pub fn init() -> ChatLog {
+        ChatLog {
+            lines: VecDeque::new(),
+            scroll: 0,
+            shown: true,
+        }
+    }
+
+    /// Adds a line, dropping the oldest once there are CHAT_HISTORY of them
+    pub fn push(&mut self, entry: ChatEntry) {
+        self.lines.push_back(entry);
+        if self.lines.len() > CHAT_HISTORY {
+            self.lines.pop_front();
+        }
+
+        //keep looking at the same lines when scrolled back
+        if self.scroll > 0 {
+            self.scroll = (self.scroll + 1).min(self.max_scroll());
+        }
+    }
+
+    /// A line from the game rather than a player
+    pub fn notice(&mut self, text: String) {
+        self.push(ChatEntry {
+            from: None,
+            name: String::new(),
+            text,
+        });
+    }
+
+    /// Furthest the overlay can be scrolled back
+    pub fn max_scroll(&self) -> usize {
+        self.lines.len().saturating_sub(CHAT_VISIBLE_LINES)
+    }
+
+    /// The lines the overlay shows, oldest first
+    pub fn visible(&self) -> impl Iterator<Item = &ChatEntry> {
+        let end = self.lines.len() - self.scroll;
+        self.lines
+            .range(end.saturating_sub(CHAT_VISIBLE_LINES)..end)
+    }
+}
+
+/// What's being typed into the chat, if the chat box is open
+#[derive(Resource, Default)]
+pub struct ChatInput {
+    pub typing: bool,
+    pub text: String,
+}
+
+/// Root node of the chat overlay
+#[derive(Component)]
+pub struct ChatUI;
+
+/// Text showing the chat lines
+#[derive(Component)]
+pub struct ChatLinesText;
+
+/// Text showing what's being typed
+#[derive(Component)]
+pub struct ChatInputText;
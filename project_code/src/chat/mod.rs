@@ -0,0 +1,42 @@
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+
+pub mod components;
+pub mod systems;
+
+use components::{ChatInput, ChatLog};
+use systems::*;
+
+use crate::components::GameworldState;
+
+/// Text chat with the rest of the room, shown over the game in the bottom left.
+/// Enter opens the chat box and sends, Escape closes it, Tab hides the chat
+/// and Page Up / Page Down scroll back through it
+pub struct ChatPlugin;
+
+impl Plugin for ChatPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ChatLog::init())
+            .init_resource::<ChatInput>()
+            .add_systems(Startup, setup_chat)
+            //before anything else reads the keyboard, so typing doesn't move the player
+            .add_systems(
+                PreUpdate,
+                type_chat
+                    .after(InputSystem)
+                    .run_if(not(in_state(GameworldState::MainMenu))),
+            )
+            .add_systems(
+                Update,
+                (
+                    receive_chat,
+                    note_crew_whereabouts,
+                    update_chat_lines
+                        .after(receive_chat)
+                        .after(note_crew_whereabouts),
+                    update_chat_input,
+                    show_chat,
+                ),
+            );
+    }
+}
@@ -0,0 +1,274 @@
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::input::ButtonState;
+use bevy::prelude::*;
+use protocol::components::Area;
+use protocol::messages::{ClientMessage, ServerMessage, MAX_CHAT_LENGTH};
+use protocol::reliable::Channel;
+use std::collections::HashMap;
+
+use super::components::*;
+use crate::components::GameworldState;
+use crate::network::components::{ConnectionState, HostPlayer, ServerEvent, ServerLink};
+use crate::network::systems::send;
+
+/*   SETUP_CHAT FUNCTION   */
+/// Spawns the chat overlay, it stays hidden until there's a game to show it over
+pub fn setup_chat(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(10.0),
+                    bottom: Val::Px(10.0),
+                    width: Val::Px(520.0),
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(6.0)),
+                    ..default()
+                },
+                background_color: Color::srgba(0.1, 0.1, 0.1, 0.6).into(),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            ChatUI,
+        ))
+        .with_children(|parent| {
+            parent.spawn((TextBundle::default(), ChatLinesText));
+            parent.spawn((TextBundle::default(), ChatInputText));
+        });
+}
+
+/*   TYPE_CHAT FUNCTION   */
+/// Enter opens the chat box and sends what's in it, Escape closes it. While
+/// it's open it takes the whole keyboard, so typing doesn't steer the player.
+/// Tab shows or hides the chat and Page Up / Page Down scroll it
+pub fn type_chat(
+    mut keyboard_events: EventReader<KeyboardInput>,
+    mut keyboard_input: ResMut<ButtonInput<KeyCode>>,
+    mut input: ResMut<ChatInput>,
+    mut log: ResMut<ChatLog>,
+    mut link: ServerLink,
+    state: Res<State<ConnectionState>>,
+) {
+    let was_typing = input.typing;
+
+    for event in keyboard_events.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+
+        match &event.logical_key {
+            Key::PageUp => {
+                log.scroll = (log.scroll + CHAT_VISIBLE_LINES - 1).min(log.max_scroll());
+            }
+            Key::PageDown => {
+                log.scroll = log.scroll.saturating_sub(CHAT_VISIBLE_LINES - 1);
+            }
+            Key::Enter if !input.typing => input.typing = true,
+            Key::Tab if !input.typing => log.shown = !log.shown,
+            _ if !input.typing => {}
+            Key::Character(text) => {
+                for c in text.chars().filter(|c| !c.is_control()) {
+                    if input.text.chars().count() < MAX_CHAT_LENGTH {
+                        input.text.push(c);
+                    }
+                }
+            }
+            Key::Space if input.text.chars().count() < MAX_CHAT_LENGTH => input.text.push(' '),
+            Key::Backspace => {
+                input.text.pop();
+            }
+            Key::Enter => {
+                let text = std::mem::take(&mut input.text);
+                input.typing = false;
+                send_chat(&mut link, state.get(), &mut log, text.trim());
+            }
+            Key::Escape => {
+                input.text.clear();
+                input.typing = false;
+            }
+            _ => {}
+        }
+    }
+
+    //the key that closed the chat box doesn't get through either
+    if was_typing || input.typing {
+        keyboard_input.reset_all();
+    }
+}
+
+fn send_chat(link: &mut ServerLink, state: &ConnectionState, log: &mut ChatLog, text: &str) {
+    if text.is_empty() {
+        return;
+    }
+
+    if !matches!(state, ConnectionState::Syncing | ConnectionState::Connected) {
+        log.notice("Not connected to the server, nobody heard that".to_string());
+        return;
+    }
+
+    //shown once the server passes it back, so everyone sees the same order
+    send(
+        &mut link.udp,
+        &link.server,
+        &ClientMessage::Chat(text.to_string()),
+        Channel::Reliable,
+    );
+    log.scroll = 0;
+}
+
+/*   RECEIVE_CHAT FUNCTION   */
+/// Adds the chat messages the server passes on, and its own announcements, to the log
+pub fn receive_chat(mut events: EventReader<ServerEvent>, mut log: ResMut<ChatLog>) {
+    for ServerEvent(message) in events.read() {
+        match message {
+            ServerMessage::Chat(line) => log.push(ChatEntry {
+                from: Some(line.from),
+                name: line.name.clone(),
+                text: line.text.clone(),
+            }),
+            ServerMessage::ChatThrottled { retry_in } => log.notice(format!(
+                "You're chatting too fast, wait {:.0}s",
+                retry_in.ceil()
+            )),
+            ServerMessage::Announcement(text) => log.notice(format!("[Server] {}", text)),
+            _ => {}
+        }
+    }
+}
+
+/*   NOTE_CREW_WHEREABOUTS FUNCTION   */
+/// Adds a notice to the log whenever someone else in the room sets sail, lands
+/// or goes down into a dungeon, from the areas in the roster
+pub fn note_crew_whereabouts(
+    mut events: EventReader<ServerEvent>,
+    mut log: ResMut<ChatLog>,
+    host: Res<HostPlayer>,
+    mut last_seen: Local<HashMap<i32, Area>>,
+) {
+    for ServerEvent(message) in events.read() {
+        let ServerMessage::Roster(roster) = message else {
+            continue;
+        };
+
+        for player in roster.players.iter() {
+            let before = last_seen.insert(player.id, player.area);
+
+            //everyone leaving the menus at once when the session starts isn't news
+            let moved = before.is_some_and(|before| {
+                before != player.area && before != Area::MainMenu && player.area != Area::MainMenu
+            });
+            if moved && player.id != host.player.id {
+                log.notice(format!("{} is now {}", player.name, player.area));
+            }
+        }
+
+        //ids are handed out again once a slot is free
+        last_seen.retain(|id, _| roster.players.iter().any(|player| player.id == *id));
+    }
+}
+
+/*   UPDATE_CHAT_LINES FUNCTION   */
+/// Shows the part of the log the chat is scrolled to, our own name in a
+/// different color
+pub fn update_chat_lines(
+    log: Res<ChatLog>,
+    host: Res<HostPlayer>,
+    asset_server: Res<AssetServer>,
+    mut text_query: Query<&mut Text, With<ChatLinesText>>,
+) {
+    if !log.is_changed() {
+        return;
+    }
+
+    let font = asset_server.load("pixel_pirate.ttf");
+    let style = |color: Color| TextStyle {
+        font: font.clone(),
+        font_size: 18.0,
+        color,
+    };
+
+    let mut sections = Vec::new();
+    for entry in log.visible() {
+        match entry.from {
+            Some(id) => {
+                let color = if id == host.player.id {
+                    CHAT_OWN_NAME_COLOR
+                } else {
+                    CHAT_NAME_COLOR
+                };
+                sections.push(TextSection::new(format!("{}: ", entry.name), style(color)));
+                sections.push(TextSection::new(
+                    format!("{}\n", entry.text),
+                    style(CHAT_TEXT_COLOR),
+                ));
+            }
+            None => sections.push(TextSection::new(
+                format!("{}\n", entry.text),
+                style(CHAT_NOTICE_COLOR),
+            )),
+        }
+    }
+    if log.scroll > 0 {
+        sections.push(TextSection::new(
+            format!("({} newer, Page Down)\n", log.scroll),
+            style(CHAT_NOTICE_COLOR),
+        ));
+    }
+
+    for mut text in text_query.iter_mut() {
+        text.sections = sections.clone();
+    }
+}
+
+/*   UPDATE_CHAT_INPUT FUNCTION   */
+/// Shows what's being typed into the chat box
+pub fn update_chat_input(
+    input: Res<ChatInput>,
+    asset_server: Res<AssetServer>,
+    mut text_query: Query<&mut Text, With<ChatInputText>>,
+) {
+    if !input.is_changed() {
+        return;
+    }
+
+    let value = if input.typing {
+        format!("> {}_", input.text)
+    } else {
+        String::new()
+    };
+
+    for mut text in text_query.iter_mut() {
+        *text = Text::from_section(
+            value.clone(),
+            TextStyle {
+                font: asset_server.load("pixel_pirate.ttf"),
+                font_size: 18.0,
+                color: CHAT_TEXT_COLOR,
+            },
+        );
+    }
+}
+
+/*   SHOW_CHAT FUNCTION   */
+/// Shows the chat over the game while it's toggled on or being typed into
+pub fn show_chat(
+    log: Res<ChatLog>,
+    input: Res<ChatInput>,
+    gameworld_state: Res<State<GameworldState>>,
+    mut root_query: Query<&mut Visibility, With<ChatUI>>,
+) {
+    let shown = *gameworld_state.get() != GameworldState::MainMenu && (log.shown || input.typing);
+
+    for mut visibility in root_query.iter_mut() {
+        let wanted = if shown {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        //only touch it when it changes so the UI isn't redone every frame
+        if *visibility != wanted {
+            *visibility = wanted;
+        }
+    }
+}
@@ -1,23 +1,10 @@
 use bevy::{math::bounding::Aabb2d, prelude::*};
 use crate::level::components::IslandType;
-use crate::shop::components::*;
+use protocol::ocean::OceanParams;
 
 #[derive(Component)]
 pub struct Background;
 
-#[derive(Resource)]
-pub struct StateTransitionCooldown {
-    pub timer: Timer,
-}
-
-impl Default for StateTransitionCooldown {
-    fn default() -> Self {
-        Self {
-            timer: Timer::from_seconds(1.0, TimerMode::Once),
-        }
-    }
-}
-
 #[derive(Component)]
 pub struct TransitionImmunity {
     pub timer: Timer,
@@ -70,49 +57,25 @@ impl BoundingBox {
 }
 
 #[derive(Resource)]
-pub struct SpawnLocations {
-    pub player: Vec3,
-    pub door: Vec3,
-}
-
-impl Default for SpawnLocations {
-    fn default() -> Self {
-        Self {
-            player: Vec3::ZERO,
-            door: Vec3::ZERO,
-        }
-    }
-}
-
-// GLOBAL PLAYER ENTITIES
-#[derive(Resource)]
-pub struct PlayerEntities {
-    pub players: Vec<Entity>,
-    pub next_id: u8,
-    pub current_entity: Option<Entity>,
-    pub saved_inventory: Option<Inventory>,  // Add this
+pub struct CurrentIslandType {
+    pub island_type: IslandType,
+    /// Ocean zone of the island, None for the starting island which isn't in one
+    pub zone: Option<u32>,
 }
 
-impl Default for PlayerEntities {
-    fn default() -> Self {
-        Self {
-            players: Vec::with_capacity(4),
-            next_id: 1,
-            current_entity: None,
-            saved_inventory: None,
-        }
+impl CurrentIslandType {
+    /// Zone of the island, the starting island's being the one after every
+    /// ocean zone, see OceanParams::start_island
+    pub fn island(&self, ocean: &OceanParams) -> u32 {
+        self.zone.unwrap_or_else(|| ocean.start_island())
     }
 }
 
-#[derive(Resource)]
-pub struct CurrentIslandType {
-    pub island_type: IslandType,
-}
-
 impl Default for CurrentIslandType {
     fn default() -> Self {
         Self {
-            island_type: IslandType::Level1
+            island_type: IslandType::Level1,
+            zone: None,
         }
     }
 }
@@ -0,0 +1,209 @@
+use bevy::log::Level;
+use bevy::prelude::*;
+use clap::Parser;
+use protocol::messages::{RoomChoice, MAX_NAME_LENGTH};
+use serde::Deserialize;
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::path::PathBuf;
+use std::{fmt, fs, io};
+
+/// Config file read when --config isn't given, skipped if it doesn't exist
+pub const DEFAULT_CONFIG_PATH: &str = "client.toml";
+
+/// Command line flags. Anything given here wins over the config file
+#[derive(Parser)]
+#[command(name = "project_code", about = "Sea of Fortune")]
+pub struct Cli {
+    /// TOML config file [default: client.toml, if there is one]
+    #[arg(short, long)]
+    pub config: Option<PathBuf>,
+    /// Server to join, as host:port
+    #[arg(short, long)]
+    pub server: Option<String>,
+    /// Name shown to the other players
+    #[arg(short, long)]
+    pub name: Option<String>,
+    /// Local address to send from, port 0 picks a free one
+    #[arg(long)]
+    pub bind: Option<SocketAddr>,
+    /// Join this room right away instead of waiting in the main menu
+    #[arg(short, long, conflicts_with = "create_room")]
+    pub room: Option<String>,
+    /// Open a new room right away instead of waiting in the main menu
+    #[arg(long)]
+    pub create_room: bool,
+    /// Ocean seed of the new room [default: picked by the server]
+    #[arg(long, requires = "create_room")]
+    pub seed: Option<u64>,
+    /// Seed of every random number the game picks, to play a session back
+    /// [default: random, printed at startup]
+    #[arg(long)]
+    pub rng_seed: Option<u64>,
+    /// Print the rooms open on the server and exit
+    #[arg(long)]
+    pub list_rooms: bool,
+    /// error, warn, info, debug or trace
+    #[arg(long)]
+    pub log_level: Option<String>,
+}
+
+/// Client settings, from the config file and the command line. Keys missing
+/// from the file keep their defaults. Server, name and room are only what the
+/// main menu starts out with. Tick rate and room size come from the server
+/// when joining
+#[derive(Resource, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClientConfig {
+    pub server: String,
+    pub bind: SocketAddr,
+    pub name: String,
+    /// Code of the room to join, any room with space when left out
+    pub room: Option<String>,
+    pub create_room: bool,
+    /// Ocean seed asked for when creating a room
+    pub seed: Option<u64>,
+    /// Seed of every random number the game picks, a random one when left out
+    pub rng_seed: Option<u64>,
+    pub log_level: String,
+}
+
+impl Default for ClientConfig {
+    fn default() -> ClientConfig {
+        ClientConfig {
+            server: "127.0.0.1:5000".to_string(),
+            bind: "0.0.0.0:0".parse().unwrap(),
+            name: "Pirate".to_string(),
+            room: None,
+            create_room: false,
+            seed: None,
+            rng_seed: None,
+            log_level: "info".to_string(),
+        }
+    }
+}
+
+impl ClientConfig {
+    /// Reads the config file, if there is one, and applies the command line on top
+    pub fn load(cli: Cli) -> Result<ClientConfig, ConfigError> {
+        let mut config = match cli.config {
+            Some(path) => ClientConfig::read(path)?,
+            None if fs::metadata(DEFAULT_CONFIG_PATH).is_ok() => {
+                ClientConfig::read(PathBuf::from(DEFAULT_CONFIG_PATH))?
+            }
+            None => ClientConfig::default(),
+        };
+
+        if let Some(server) = cli.server {
+            config.server = server;
+        }
+        if let Some(bind) = cli.bind {
+            config.bind = bind;
+        }
+        if let Some(name) = cli.name {
+            config.name = name;
+        }
+        if cli.room.is_some() {
+            config.room = cli.room;
+        }
+        if cli.create_room {
+            config.create_room = true;
+        }
+        if cli.seed.is_some() {
+            config.seed = cli.seed;
+        }
+        if cli.rng_seed.is_some() {
+            config.rng_seed = cli.rng_seed;
+        }
+        if let Some(log_level) = cli.log_level {
+            config.log_level = log_level;
+        }
+
+        if config.name.chars().count() > MAX_NAME_LENGTH {
+            return Err(ConfigError::Invalid(format!(
+                "name can be at most {} characters, got {:?}",
+                MAX_NAME_LENGTH, config.name
+            )));
+        }
+        if config.create_room && config.room.is_some() {
+            return Err(ConfigError::Invalid(
+                "room and create_room can't both be set".to_string(),
+            ));
+        }
+        if config.seed.is_some() && !config.create_room {
+            return Err(ConfigError::Invalid(
+                "seed only applies to rooms opened with create_room".to_string(),
+            ));
+        }
+
+        if config.log_level.parse::<Level>().is_err() {
+            return Err(ConfigError::Invalid(format!(
+                "log_level must be error, warn, info, debug or trace, got {:?}",
+                config.log_level
+            )));
+        }
+        Ok(config)
+    }
+
+    fn read(path: PathBuf) -> Result<ClientConfig, ConfigError> {
+        let text = fs::read_to_string(&path).map_err(|e| ConfigError::Read(path.clone(), e))?;
+        toml::from_str(&text).map_err(|e| ConfigError::Parse(path, e))
+    }
+
+    /// Looks up the server's address, host names are allowed
+    pub fn server_addr(&self) -> Result<SocketAddr, ConfigError> {
+        self.resolve(&self.server)
+    }
+
+    /// Looks up a host:port typed into the main menu
+    pub fn resolve(&self, server: &str) -> Result<SocketAddr, ConfigError> {
+        server
+            .to_socket_addrs()
+            .map_err(|e| ConfigError::Resolve(server.to_string(), e))?
+            //prefer an address the bound socket can actually reach
+            .find(|addr| addr.is_ipv4() == self.bind.is_ipv4())
+            .ok_or_else(|| {
+                ConfigError::Invalid(format!(
+                    "{} has no address reachable from {}",
+                    server, self.bind
+                ))
+            })
+    }
+
+    /// Whether to join straight away instead of waiting for the menu
+    pub fn join_on_start(&self) -> bool {
+        self.create_room || self.room.is_some()
+    }
+
+    /// The room to ask for in new_player when joining from the command line
+    pub fn room_choice(&self) -> RoomChoice {
+        match &self.room {
+            _ if self.create_room => RoomChoice::Create { seed: self.seed },
+            Some(code) => RoomChoice::Join { code: code.clone() },
+            None => RoomChoice::Any,
+        }
+    }
+
+    /// Level for bevy's LogPlugin, already checked by load()
+    pub fn level(&self) -> Level {
+        self.log_level.parse().unwrap_or(Level::INFO)
+    }
+}
+
+/// Why the config couldn't be loaded
+pub enum ConfigError {
+    Read(PathBuf, io::Error),
+    Parse(PathBuf, toml::de::Error),
+    Resolve(String, io::Error),
+    Invalid(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::Read(path, e) => write!(f, "Could not read {}: {}", path.display(), e),
+            ConfigError::Parse(path, e) => write!(f, "Could not parse {}: {}", path.display(), e),
+            ConfigError::Resolve(server, e) => write!(f, "Could not look up {}: {}", server, e),
+            ConfigError::Invalid(reason) => write!(f, "Invalid config: {}", reason),
+        }
+    }
+}
@@ -7,12 +7,7 @@ pub enum PlayerControl {
     Down,       //S
     Left,       //A
     Right,      //D
-    Weapon1,    //1
-    Weapon2,    //2
-    Weapon3,    //3
-    Weapon4,    //4
     Attack,     //Left Mouse Button
-    Secondary,  //Right Mouse Button
 }
 
 /// Struct to represent current mouse position
@@ -24,6 +19,7 @@ impl PlayerControl {
 
     /// Checks if the expected control was pressed,
     /// * returns true if pressed
+    ///
     /// else
     /// * returns false
     pub fn pressed(&self, keyboard_input: &Res<ButtonInput<KeyCode>>, mouse_input: &Res<ButtonInput<MouseButton>>) -> bool {
@@ -40,16 +36,9 @@ impl PlayerControl {
             PlayerControl::Right => {
                 keyboard_input.pressed(KeyCode::KeyD)
             }
-            PlayerControl::Weapon1 => keyboard_input.pressed(KeyCode::Digit1),
-            PlayerControl::Weapon2 => keyboard_input.pressed(KeyCode::Digit2),
-            PlayerControl::Weapon3 => keyboard_input.pressed(KeyCode::Digit3),
-            PlayerControl::Weapon4 => keyboard_input.pressed(KeyCode::Digit4),
             PlayerControl::Attack => {
                 mouse_input.pressed(MouseButton::Left)
             }
-            PlayerControl::Secondary => {
-                mouse_input.pressed(MouseButton::Right)
-            }
         }
     }
 }
@@ -57,6 +46,7 @@ impl PlayerControl {
 /// Checks if a certain input was inputted by the controller. If
 /// input was pressed:
 /// * returns 1.0
+///
 /// else,
 /// * returns 0.0
 /// 
@@ -0,0 +1,10 @@
+use bevy::prelude::*;
+
+/// An enemy of the dungeon the server sent us into. It's only despawned for
+/// good, and only drops loot, once the server says who killed it
+#[derive(Component)]
+pub struct DungeonEnemy {
+    /// Zone of the island the dungeon is under
+    pub island: u32,
+    pub id: i32,
+}
@@ -0,0 +1,28 @@
+pub(crate) mod components;
+pub(crate) mod systems;
+
+use bevy::prelude::*;
+use systems::*;
+
+use crate::components::GameworldState;
+use crate::network::systems::report_area;
+
+/// Dungeons are owned by the server, so everyone who goes into an island's
+/// dungeon finds the same layout and enemies, and each enemy dies once for all
+/// of them
+pub struct DungeonSyncPlugin;
+
+impl Plugin for DungeonSyncPlugin {
+    /// Builds the dungeon sync plugin
+    fn build(&self, app: &mut App) {
+        //the server only lets us in once it knows we're down there
+        app.add_systems(
+            Update,
+            request_dungeon.after(report_area).run_if(
+                state_changed::<GameworldState>.and_then(in_state(GameworldState::Dungeon)),
+            ),
+        )
+        //loot is handed out even if we've left the dungeon in the meantime
+        .add_systems(Update, apply_dungeon_kills);
+    }
+}
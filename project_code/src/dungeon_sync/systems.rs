@@ -0,0 +1,122 @@
+use bevy::prelude::*;
+use protocol::dungeon::DungeonInstance;
+use protocol::messages::{ClientMessage, ServerMessage};
+use protocol::ocean::OceanParams;
+use protocol::reliable::Channel;
+
+use crate::components::CurrentIslandType;
+use crate::dungeon_sync::components::*;
+use crate::enemies::*;
+use crate::network::components::{HostPlayer, Server, ServerEvent, UDP};
+use crate::network::systems::send;
+use crate::player::components::Player;
+use crate::shop::systems::generate_loot_item;
+
+/*   REQUEST_DUNGEON FUNCTION   */
+/// Asks the server for the dungeon under the island we're on. It's built once
+/// the answer comes back, see generate_dungeon
+pub fn request_dungeon(
+    mut udp: ResMut<UDP>,
+    server: Res<Server>,
+    ocean: Res<OceanParams>,
+    current_island_type: Res<CurrentIslandType>,
+) {
+    let island = current_island_type.island(&ocean);
+
+    send(
+        &mut udp,
+        &server,
+        &ClientMessage::EnterDungeon { island },
+        Channel::Reliable,
+    );
+}
+
+/*   SPAWN_DUNGEON_ENEMIES FUNCTION   */
+/// Spawns the enemies still alive in a dungeon the server sent us into
+pub fn spawn_dungeon_enemies(
+    commands: &mut Commands,
+    instance: &DungeonInstance,
+    asset_server: &Res<AssetServer>,
+    texture_atlases: &mut ResMut<Assets<TextureAtlasLayout>>,
+) {
+    for enemy in instance.enemies.iter() {
+        let Some(enemy_t) = dungeon_enemy_type(enemy.etype) else {
+            warn!(
+                "Undefined enemy type for spawn_dungeon_enemies(): {}",
+                enemy.etype
+            );
+            continue;
+        };
+
+        let transform =
+            Transform::from_translation(enemy.position.extend(900.)).with_scale(Vec3::splat(2.0));
+        let entity = spawn_enemy(commands, enemy_t, transform, asset_server, texture_atlases);
+        commands.entity(entity).insert(DungeonEnemy {
+            island: instance.island,
+            id: enemy.id,
+        });
+    }
+}
+
+/*   REPORT_DUNGEON_KILL FUNCTION   */
+/// Tells the server we killed an enemy in a dungeon. Its loot drops once the
+/// server says nobody beat us to it, see apply_dungeon_kills
+pub fn report_dungeon_kill(udp: &mut UDP, server: &Server, enemy: &DungeonEnemy) {
+    send(
+        udp,
+        server,
+        &ClientMessage::DungeonEnemyKilled {
+            island: enemy.island,
+            id: enemy.id,
+        },
+        Channel::Reliable,
+    );
+}
+
+/*   APPLY_DUNGEON_KILLS FUNCTION   */
+/// Despawns the dungeon enemies other players killed, and gives the host the
+/// loot of the ones the server says they killed
+pub fn apply_dungeon_kills(
+    mut commands: Commands,
+    mut events: EventReader<ServerEvent>,
+    host: Res<HostPlayer>,
+    query: Query<(Entity, &DungeonEnemy)>,
+    mut player_query: Query<&mut Player>,
+) {
+    for ServerEvent(message) in events.read() {
+        let ServerMessage::DungeonEnemyDied(kill) = message else {
+            continue;
+        };
+
+        for (entity, enemy) in query.iter() {
+            if enemy.island == kill.island && enemy.id == kill.enemy.id {
+                commands.entity(entity).despawn();
+            }
+        }
+
+        if kill.by != host.player.id {
+            continue;
+        }
+        let Some(enemy_t) = dungeon_enemy_type(kill.enemy.etype) else {
+            continue;
+        };
+
+        let loot = generate_loot_item(enemy_t);
+        if loot.price > 0 {
+            info!("Dungeon enemy [{}] dropped: {}", kill.enemy.id, loot.name);
+            if let Ok(mut player) = player_query.get_single_mut() {
+                player.inventory.add_item(loot);
+            }
+        }
+    }
+}
+
+/// The enemy spawned for an enemy code the server put in a dungeon
+fn dungeon_enemy_type(etype: i32) -> Option<EnemyT> {
+    match etype {
+        SKELETON => Some(EnemyT::RSkeleton),
+        BAT => Some(EnemyT::Bat),
+        ROCK => Some(EnemyT::Rock),
+        _ => None,
+    }
+}
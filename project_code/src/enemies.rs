@@ -14,19 +14,11 @@ use crate::storm::components::*;
 use crate::whirlpool::components::*;
 use crate::Enemy;
 
-//ENTITIES
-pub const PLAYER: i32 = 0;
-pub const BOAT: i32 = 1;
-pub const BAT: i32 = 2;
-pub const KRAKEN: i32 = 3;
-pub const GHOSTSHIP: i32 = 4;
-pub const ROCK: i32 = 5;
-pub const SKELETON: i32 = 6;
-pub const SKEL2: i32 = 7;
-pub const WHIRLPOOL: i32 = 8;
-pub const BOSS: i32 = 9;
-pub const STORM: i32 = 10;
-pub const PSKELETON: i32 = 10;
+//ENTITIES (codes are shared with the server)
+pub use protocol::gameworld_data::{
+    BAT, BOAT, BOSS, GHOSTSHIP, KRAKEN, PLAYER, PSKELETON, ROCK, SKELETON, STORM,
+    WHIRLPOOL,
+};
 
 #[derive(Component)]
 pub struct EnemyTag;
@@ -51,64 +43,59 @@ pub fn spawn_enemy(
     transform: Transform,
     asset_server: &Res<AssetServer>,
     texture_atlases: &mut ResMut<Assets<TextureAtlasLayout>>,
-) {
-    match enemy {
+) -> Entity {
+    let spawned = match enemy {
         EnemyT::Storm(id) => {
             // Spawn the parent entity
-            commands
-                .spawn((
-                    SpatialBundle {
-                        transform,
+            let mut storm = commands.spawn((
+                SpatialBundle {
+                    transform,
+                    ..default()
+                },
+                Storm,
+                Hurtbox {
+                    size: Vec2::new(1200.0, 900.0),
+                    offset: Vec2::splat(0.),
+                    colliding: Collision::default(),
+                    entity: STORM,
+                    iframe: Timer::from_seconds(0.75, TimerMode::Once),
+                    enemy: true,
+                },
+                Enemy {
+                    id,
+                    etype: STORM,
+                    pos: transform.translation,
+                    animation_index: 0,
+                    hp: 1.,
+                    alive: true,
+                    target_id: -1,
+                },
+                EnemyTag,
+            ));
+            storm.with_children(|parent| {
+                // Spawn the transparent background
+                parent.spawn(SpriteBundle {
+                    sprite: Sprite {
+                        color: Color::srgba(0.5, 0.5, 0.5, 0.3),
+                        custom_size: Some(Vec2::new(1200.0, 900.0)),
                         ..default()
                     },
-                    Storm {
-                        damage_timer: Timer::from_seconds(
-                            STORM_DAMAGE_INTERVAL,
-                            TimerMode::Repeating,
-                        ),
-                    },
-                    Hurtbox {
-                        size: Vec2::new(1200.0, 900.0),
-                        offset: Vec2::splat(0.),
-                        colliding: Collision::default(),
-                        entity: STORM,
-                        iframe: Timer::from_seconds(0.75, TimerMode::Once),
-                        enemy: true,
-                    },
-                    Enemy {
-                        id,
-                        etype: STORM,
-                        pos: transform.translation,
-                        animation_index: 0,
-                        hp: 1.,
-                        alive: true,
-                        target_id: -1,
-                    },
-                    EnemyTag,
-                ))
-                .with_children(|parent| {
-                    // Spawn the transparent background
-                    parent.spawn(SpriteBundle {
-                        sprite: Sprite {
-                            color: Color::rgba(0.5, 0.5, 0.5, 0.3),
-                            custom_size: Some(Vec2::new(1200.0, 900.0)),
-                            ..default()
-                        },
-                        transform: Transform::from_xyz(0.0, 0.0, 0.0),
-                        ..default()
-                    });
+                    transform: Transform::from_xyz(0.0, 0.0, 0.0),
+                    ..default()
+                });
 
-                    // Spawn the storm image on top
-                    parent.spawn(SpriteBundle {
-                        texture: asset_server.load("s_storm.png"), // Make sure to add your storm image to assets
-                        transform: Transform::from_xyz(0.0, 0.0, 1.0), // Slightly higher z-index
-                        sprite: Sprite {
-                            custom_size: Some(Vec2::new(1200.0, 900.0)), // Same size as background
-                            ..default()
-                        },
+                // Spawn the storm image on top
+                parent.spawn(SpriteBundle {
+                    texture: asset_server.load("s_storm.png"), // Make sure to add your storm image to assets
+                    transform: Transform::from_xyz(0.0, 0.0, 1.0), // Slightly higher z-index
+                    sprite: Sprite {
+                        custom_size: Some(Vec2::new(1200.0, 900.0)), // Same size as background
                         ..default()
-                    });
+                    },
+                    ..default()
                 });
+            });
+            storm
         }
         EnemyT::Whirlpool(id) => {
             let whirlpool_texture_asset: Handle<Image> = asset_server.load("s_whirlpool.png");
@@ -119,11 +106,7 @@ pub fn spawn_enemy(
                     transform,
                     ..default()
                 },
-                Whirlpool {
-                    rotation_speed: f32::to_radians(90.0),
-                    current_hp: WHIRLPOOL_HP,
-                    max_hp: WHIRLPOOL_HP,
-                },
+                Whirlpool,
                 Lifetime(WHIRLPOOL_LIFETIME),
                 Hurtbox {
                     size: Vec2::new(400., 290.),
@@ -143,7 +126,7 @@ pub fn spawn_enemy(
                     target_id: -1,
                 },
                 EnemyTag,
-            ));
+            ))
         }
         EnemyT::Bat => {
             let bat_layout =
@@ -159,7 +142,6 @@ pub fn spawn_enemy(
                     //Setting default stats
                     rotation_speed: f32::to_radians(90.0),
                     current_hp: BAT_MAX_HP,
-                    max_hp: BAT_MAX_HP,
                 },
                 TextureAtlas {
                     layout: texture_atlases.add(bat_layout.clone()),
@@ -182,7 +164,7 @@ pub fn spawn_enemy(
                     iframe: Timer::from_seconds(0.75, TimerMode::Once),
                     enemy: true,
                 },
-            ));
+            ))
         }
         EnemyT::Kraken(id) => {
             let kraken_texture_handle = asset_server.load("s_kraken.png");
@@ -193,12 +175,7 @@ pub fn spawn_enemy(
                     transform,
                     ..default()
                 },
-                Kraken {
-                    //Setting default stats
-                    rotation_speed: f32::to_radians(90.0),
-                    current_hp: KRAKEN_MAX_HP,
-                    max_hp: KRAKEN_MAX_HP,
-                },
+                Kraken,
                 AttackCooldown {
                     remaining: Timer::from_seconds(1.5, TimerMode::Once),
                 },
@@ -220,7 +197,7 @@ pub fn spawn_enemy(
                     target_id: -1,
                 },
                 EnemyTag,
-            ));
+            ))
         }
         EnemyT::GhostShip(id) => {
             let ghostship_texture_handle = asset_server.load("s_ghost_ship.png");
@@ -232,9 +209,7 @@ pub fn spawn_enemy(
                     ..default()
                 },
                 GhostShip {
-                    //Setting default stats
                     rotation_speed: f32::to_radians(90.0),
-                    current_hp: GHOSTSHIP_MAX_HP,
                 },
                 AttackCooldown {
                     remaining: Timer::from_seconds(1.5, TimerMode::Once),
@@ -257,7 +232,7 @@ pub fn spawn_enemy(
                     target_id: -1,
                 },
                 EnemyTag,
-            ));
+            ))
         }
         EnemyT::RSkeleton => {
             let skeleton_layout = TextureAtlasLayout::from_grid(
@@ -280,7 +255,6 @@ pub fn spawn_enemy(
                 Skeleton {
                     rotation_speed: 0.0,
                     current_hp: SKELETON_MAX_HP,
-                    max_hp: SKELETON_MAX_HP,
                 },
                 TextureAtlas {
                     layout: texture_atlases.add(skeleton_layout.clone()),
@@ -303,7 +277,7 @@ pub fn spawn_enemy(
                     iframe: Timer::from_seconds(0.75, TimerMode::Once),
                     enemy: true,
                 },
-            ));
+            ))
         }
         EnemyT::Rock => {
             let rock_layout =
@@ -346,7 +320,7 @@ pub fn spawn_enemy(
                     enemy: true,
                     dmg: 1.,
                 },
-            ));
+            ))
         }
         EnemyT::PoisonSkeleton => {
             let pskeleton_layout = TextureAtlasLayout::from_grid(
@@ -370,7 +344,6 @@ pub fn spawn_enemy(
                 Skeleton {
                     rotation_speed: 0.0,
                     current_hp: PSKELETON_MAX_HP,
-                    max_hp: PSKELETON_MAX_HP,
                 },
                 TextureAtlas {
                     layout: texture_atlases.add(pskeleton_layout.clone()),
@@ -393,7 +366,8 @@ pub fn spawn_enemy(
                     iframe: Timer::from_seconds(0.75, TimerMode::Once),
                     enemy: true,
                 },
-            ));
+            ))
         }
-    }
+    };
+    spawned.id()
 }
@@ -0,0 +1,13 @@
+//how quickly an enemy proxy catches up to the position the server last sent
+pub const ENEMY_SMOOTHING: f32 = 10.;
+
+//ocean enemies that are owned by the server
+pub const SYNCED_ENEMIES: [i32; 4] = [
+    crate::enemies::KRAKEN,
+    crate::enemies::GHOSTSHIP,
+    crate::enemies::WHIRLPOOL,
+    crate::enemies::STORM,
+];
+
+//synced enemies the player can attack, whirlpools and storms are just hazards
+pub const DAMAGEABLE_ENEMIES: [i32; 2] = [crate::enemies::KRAKEN, crate::enemies::GHOSTSHIP];
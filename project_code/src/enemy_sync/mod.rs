@@ -0,0 +1,29 @@
+pub(crate) mod components;
+mod systems;
+
+use bevy::prelude::*;
+use protocol::delta::DeltaDecoder;
+use systems::*;
+
+use crate::components::GameworldState;
+use crate::GameState;
+
+pub struct EnemySyncPlugin;
+
+impl Plugin for EnemySyncPlugin {
+    /// Builds the enemy sync plugin
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DeltaDecoder>()
+            .add_systems(OnExit(GameworldState::Ocean), despawn_ocean_enemies)
+            .add_systems(
+                Update,
+                (
+                    apply_enemy_updates,
+                    smooth_enemy_movement.after(apply_enemy_updates),
+                    report_enemy_damage,
+                )
+                    .run_if(in_state(GameworldState::Ocean))
+                    .run_if(in_state(GameState::Running)),
+            );
+    }
+}
@@ -0,0 +1,212 @@
+use bevy::prelude::*;
+use protocol::combat::Weapon;
+use protocol::components::Damage;
+use protocol::delta::DeltaDecoder;
+use protocol::messages::{ClientMessage, ServerMessage};
+use protocol::reliable::Channel;
+
+use crate::enemies::*;
+use crate::enemy_sync::components::*;
+use crate::ghost_ship::systems::spawn_ghostship_projectile;
+use crate::hitbox_system::Hurtbox;
+use crate::kraken::systems::spawn_kraken_projectile;
+use crate::network::components::{Enemy, Server, ServerEvent, ServerLink, UDP};
+use crate::network::systems::send;
+
+/*   APPLY_ENEMY_UPDATES FUNCTION   */
+/// Spawns, moves and despawns the ocean enemies to match the server, and spawns
+/// the projectiles they fire
+pub fn apply_enemy_updates(
+    mut commands: Commands,
+    mut events: EventReader<ServerEvent>,
+    mut query: Query<(Entity, &mut Enemy), With<EnemyTag>>,
+    asset_server: Res<AssetServer>,
+    mut texture_atlases: ResMut<Assets<TextureAtlasLayout>>,
+    mut decoder: ResMut<DeltaDecoder>,
+    mut link: ServerLink,
+) {
+    for ServerEvent(message) in events.read() {
+        match message {
+            ServerMessage::UpdateEnemies(delta) => {
+                //one that came too late or builds on an update we never got is
+                //skipped, the next one makes up for it
+                let Some(enemies) = decoder.decode(delta) else {
+                    continue;
+                };
+                send(
+                    &mut link.udp,
+                    &link.server,
+                    &ClientMessage::AckEnemies(delta.seq),
+                    Channel::Unreliable,
+                );
+
+                sync_enemies(
+                    &mut commands,
+                    &mut query,
+                    &enemies,
+                    &asset_server,
+                    &mut texture_atlases,
+                );
+
+                //update_enemies is everything in view, anything missing from it
+                //died or went out of sight
+                for (entity, enemy) in query.iter() {
+                    if SYNCED_ENEMIES.contains(&enemy.etype)
+                        && !enemies.iter().any(|e| e.id == enemy.id)
+                    {
+                        commands.entity(entity).despawn_recursive();
+                    }
+                }
+            }
+            ServerMessage::NewEnemies(enemies) => sync_enemies(
+                &mut commands,
+                &mut query,
+                &enemies.list,
+                &asset_server,
+                &mut texture_atlases,
+            ),
+            ServerMessage::DeadEnemies(enemies) => {
+                for (entity, enemy) in query.iter() {
+                    if enemies.list.iter().any(|e| e.id == enemy.id) {
+                        debug!("Enemy [{}] died", enemy.id);
+                        commands.entity(entity).despawn_recursive();
+                    }
+                }
+            }
+            ServerMessage::UpdateProjectiles(projectiles) => {
+                for projectile in projectiles.list.iter() {
+                    match projectile.etype {
+                        KRAKEN => spawn_kraken_projectile(&mut commands, &asset_server, projectile),
+                        GHOSTSHIP => {
+                            spawn_ghostship_projectile(&mut commands, &asset_server, projectile)
+                        }
+                        _ => warn!(
+                            "Undefined projectile type for apply_enemy_updates(): {}",
+                            projectile.etype
+                        ),
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/*   SYNC_ENEMIES FUNCTION   */
+/// Moves the proxies of the enemies the server sent, and spawns the ones we
+/// don't have yet
+fn sync_enemies(
+    commands: &mut Commands,
+    query: &mut Query<(Entity, &mut Enemy), With<EnemyTag>>,
+    enemies: &[Enemy],
+    asset_server: &Res<AssetServer>,
+    texture_atlases: &mut ResMut<Assets<TextureAtlasLayout>>,
+) {
+    for server_enemy in enemies.iter() {
+        match query
+            .iter_mut()
+            .find(|(_, enemy)| enemy.id == server_enemy.id)
+        {
+            //already spawned, smooth_enemy_movement moves it over
+            Some((_, mut enemy)) => {
+                enemy.pos = server_enemy.pos;
+                enemy.hp = server_enemy.hp;
+            }
+            None => spawn_synced_enemy(commands, server_enemy, asset_server, texture_atlases),
+        }
+    }
+}
+
+/*   SPAWN_SYNCED_ENEMY FUNCTION   */
+/// Spawns the local proxy for an enemy the server told us about
+fn spawn_synced_enemy(
+    commands: &mut Commands,
+    enemy: &Enemy,
+    asset_server: &Res<AssetServer>,
+    texture_atlases: &mut ResMut<Assets<TextureAtlasLayout>>,
+) {
+    let (enemy_t, transform) = match enemy.etype {
+        KRAKEN => (
+            EnemyT::Kraken(enemy.id),
+            Transform::from_translation(enemy.pos).with_scale(Vec3::splat(2.0)),
+        ),
+        GHOSTSHIP => (
+            EnemyT::GhostShip(enemy.id),
+            Transform::from_translation(enemy.pos).with_scale(Vec3::splat(2.0)),
+        ),
+        WHIRLPOOL => (
+            EnemyT::Whirlpool(enemy.id),
+            Transform::from_translation(enemy.pos),
+        ),
+        STORM => (
+            EnemyT::Storm(enemy.id),
+            Transform::from_translation(enemy.pos),
+        ),
+        _ => {
+            warn!(
+                "Undefined enemy type for spawn_synced_enemy(): {}",
+                enemy.etype
+            );
+            return;
+        }
+    };
+
+    spawn_enemy(commands, enemy_t, transform, asset_server, texture_atlases);
+}
+
+/*   SMOOTH_ENEMY_MOVEMENT FUNCTION   */
+/// Eases each enemy proxy toward the last position the server sent for it
+pub fn smooth_enemy_movement(
+    time: Res<Time>,
+    mut query: Query<(&Enemy, &mut Transform), With<EnemyTag>>,
+) {
+    let t = (ENEMY_SMOOTHING * time.delta_seconds()).min(1.);
+
+    for (enemy, mut transform) in query.iter_mut() {
+        transform.translation = transform.translation.lerp(enemy.pos, t);
+    }
+}
+
+/*   REPORT_ENEMY_DAMAGE FUNCTION   */
+/// Tells the server whenever the host hits a kraken or ghost ship. The enemy is
+/// only removed once the server says it died
+pub fn report_enemy_damage(
+    mut udp: ResMut<UDP>,
+    server: Res<Server>,
+    mut query: Query<(&Enemy, &mut Hurtbox), With<EnemyTag>>,
+) {
+    for (enemy, mut hurtbox) in query.iter_mut() {
+        if !hurtbox.colliding.is || !DAMAGEABLE_ENEMIES.contains(&enemy.etype) {
+            continue;
+        }
+
+        send(
+            &mut udp,
+            &server,
+            &ClientMessage::EnemyDamaged(Damage {
+                target_id: enemy.id,
+                dmg: hurtbox.colliding.dmg,
+                //only boats sail the ocean, so only cannonballs reach these
+                weapon: Weapon::Cannon,
+            }),
+            Channel::Reliable,
+        );
+
+        hurtbox.colliding.dmg = 0.;
+        hurtbox.colliding.is = false;
+    }
+}
+
+/*   DESPAWN_OCEAN_ENEMIES FUNCTION   */
+/// Clears every ocean enemy proxy when leaving the ocean. The server sends them
+/// again with new_enemies when we come back
+pub fn despawn_ocean_enemies(
+    mut commands: Commands,
+    query: Query<(Entity, &Enemy), With<EnemyTag>>,
+) {
+    for (entity, enemy) in query.iter() {
+        if SYNCED_ENEMIES.contains(&enemy.etype) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
@@ -1,21 +1,13 @@
 use bevy::prelude::*;
 
-//constants
-pub const GHOSTSHIP_PROJECTILE_LIFETIME: f32 = 5.;
-pub const GHOSTSHIP_PROJECTILE_SPEED: f32 = 350.;
-
-//Bat base stats
+//Ghost ship base stats
 pub const GHOSTSHIP_MAX_HP: f32 = 2.;
 pub const GHOSTSHIP_ATTACK_DIST: f32 = 800.;
-pub const GHOSTSHIP_MOVEMENT_SPEED: f32 = 215.;
-pub const GHOSTSHIP_AGRO_STOP: f32 = 300.;
-pub const GHOSTSHIP_AGRO_RANGE: f32 = 1000.;
 
-/// Struct to represent the bat entity
+/// Struct to represent the ghost ship entity, which the server moves and fires
 #[derive(Component)]
 pub struct GhostShip {
     pub rotation_speed: f32,
-    pub current_hp: f32,
 }
 
 #[derive(Component)]
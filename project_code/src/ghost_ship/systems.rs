@@ -1,24 +1,11 @@
 use bevy::prelude::*;
 
 use crate::boat::components::Boat;
-use crate::data::gameworld_data::*;
 use crate::ghost_ship::components::*;
 use crate::hitbox_system::*;
 use crate::player::components::*;
 use crate::{enemies::*, HostPlayer};
-use rand::Rng;
-
-#[derive(Resource, Default)]
-pub struct GhostSpawnTimer {
-    pub timer: Timer,
-}
-
-pub fn setup_ghost_timer(mut commands: Commands) {
-    let initial_duration = rand::thread_rng().gen_range(30.0..70.0);
-    commands.insert_resource(GhostSpawnTimer {
-        timer: Timer::from_seconds(initial_duration, TimerMode::Once),
-    });
-}
+use protocol::components::Projectile;
 
 /*   ROTATE_KRAKEN FUNCTION   */
 /// This should be changed to a function called "track_player", which will
@@ -72,69 +59,6 @@ pub fn rotate_ghostship(
     }
 }
 
-/*  SPAWN_KRAKEN FUNCTION  */
-/// Spawns a kraken entity in the gameworld
-pub fn spawn_ghostship(
-    mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    mut texture_atlases: ResMut<Assets<TextureAtlasLayout>>,
-    time: Res<Time>,
-    mut spawn_timer: ResMut<GhostSpawnTimer>,
-) {
-    spawn_timer.timer.tick(time.delta());
-
-    if spawn_timer.timer.just_finished() {
-        // Generate random coordinates within world bounds
-        let spawn_x = rand::thread_rng().gen_range(-OCEAN_LEVEL_W / 2.0..OCEAN_LEVEL_W / 2.0);
-        let spawn_y = rand::thread_rng().gen_range(-OCEAN_LEVEL_H / 2.0..OCEAN_LEVEL_H / 2.0);
-
-        let spawn_pos = Vec3::new(spawn_x, spawn_y, 900.0);
-
-        let transform = Transform::from_translation(spawn_pos).with_scale(Vec3::splat(2.0));
-
-        spawn_enemy(
-            &mut commands,
-            EnemyT::GhostShip(0),
-            transform,
-            &asset_server,
-            &mut texture_atlases,
-        );
-
-        // Set new random duration for next spawn
-        let new_duration = rand::thread_rng().gen_range(30.0..60.0);
-        spawn_timer
-            .timer
-            .set_duration(std::time::Duration::from_secs_f32(new_duration));
-        spawn_timer.timer.reset();
-    }
-}
-
-/*   KRAKEN_DAMAGED FUNCTION   */
-/// Current functionality: Detects when a player is within player attack range (this will later be replaced with
-// player weapon/attack collision) and then takes 1 damage (dies)
-pub fn ghostship_damaged(
-    mut commands: Commands,
-    mut ghostship_query: Query<(&mut GhostShip, Entity, &mut Hurtbox)>,
-) {
-    for (mut ghostship, entity, mut hurtbox) in ghostship_query.iter_mut() {
-        if !hurtbox.colliding.is {
-            continue;
-        }
-
-        ghostship.current_hp -= hurtbox.colliding.dmg;
-        hurtbox.colliding.dmg = 0.;
-
-        if ghostship.current_hp <= 0. {
-            println!("Ghostship was attacked by player, it is dead :(");
-            commands.entity(entity).despawn();
-        } else {
-            println!("Ghostship was attacked by player");
-        }
-
-        hurtbox.colliding.is = false;
-    }
-}
-
 /*   DESPAWN_ALL_KRAKEN FUNCTION   */
 /// Despawns a kraken entity
 /// DEBUG: Despwans all kraken entities
@@ -144,89 +68,42 @@ pub fn despawn_all_ghostships(mut commands: Commands, query: Query<Entity, With<
     }
 }
 
-/*   KRAKEN_ATTACK FUNCTION   */
-/// Detects when player is within kraken attack range and attacks.
-/// Things not added:
-/// * Attack cooldown timer
-/// * Projectile shooting
-/// Things currently added:
-/// * Distance-to-player checking
-/// * Attack cooldown timer
-/// * Projectile shooting
-pub fn ghostship_attack(
-    mut commands: Commands,
-    time: Res<Time>,
-    mut ghostship_query: Query<(&Transform, &mut AttackCooldown), With<GhostShip>>,
-    player_query: Query<(&Transform, &Boat), With<Boat>>,
-    asset_server: Res<AssetServer>,
-    host: Res<HostPlayer>,
+/*   SPAWN_GHOSTSHIP_PROJECTILE FUNCTION   */
+/// Spawns a cannonball the server says a ghost ship fired
+pub fn spawn_ghostship_projectile(
+    commands: &mut Commands,
+    asset_server: &Res<AssetServer>,
+    projectile: &Projectile,
 ) {
-    for (ghostship_transform, mut cooldown) in ghostship_query.iter_mut() {
-        // Attacks only when cooldown is over
-        cooldown.remaining.tick(time.delta());
-        if !cooldown.remaining.just_finished() {
-            continue;
-        }
-
-        cooldown.remaining = Timer::from_seconds(2.5, TimerMode::Once);
-
-        //Gets positions (Vec3) of the entities
-        let ghostship_translation = ghostship_transform.translation;
-
-        for (ptransform, boat) in player_query.iter() {
-            if boat.id != host.player.id {
-                continue;
-            }
-            let player_translation = ptransform.translation;
-
-            //Gets positions (Vec2) of the entities
-            let player_position = player_translation.xy();
-            let ghostship_position = ghostship_translation.xy();
-
-            //Gets distance
-            let distance_to_player = ghostship_position.distance(player_position);
-
-            if distance_to_player > GHOSTSHIP_ATTACK_DIST {
-                continue;
-            }
-
-            //Gets direction projectile will be going
-            let original_direction = (player_translation - ghostship_translation).normalize();
-            let angle = original_direction.x.atan2(original_direction.y);
-            let angle_direction = Vec3::new(angle.sin(), angle.cos(), 0.0).normalize();
-
-            let projectile_start_position = ghostship_translation + angle_direction * 10.0;
-
-            //Sets the projectile texture
-            let ghostship_projectile_handle = asset_server.load("s_cannonball.png");
-
-            //Creates Projectile
-            commands.spawn((
-            SpriteBundle {
-                texture: ghostship_projectile_handle,
-                transform: Transform {
-                    translation: projectile_start_position,
-                    scale: Vec3::splat(2.0),
-                    ..default()
-                },
+    //Sets the projectile texture
+    let ghostship_projectile_handle = asset_server.load("s_cannonball.png");
+
+    //Creates Projectile
+    commands.spawn((
+        SpriteBundle {
+            texture: ghostship_projectile_handle,
+            transform: Transform {
+                translation: projectile.translation,
+                scale: Vec3::splat(2.0),
                 ..default()
             },
-            GhostShipProjectile,
-            Lifetime(GHOSTSHIP_PROJECTILE_LIFETIME),
-            Velocity {
-                v: angle_direction.truncate() * GHOSTSHIP_PROJECTILE_SPEED, /* (direction * speed of projectile) */
-            },
-            Hitbox {
-                size: Vec2::splat(60.),
-                offset: Vec2::splat(0.),
-                lifetime: Some(Timer::from_seconds(5., TimerMode::Once)),
-                entity: GHOSTSHIP,
-                projectile: true,
-                enemy: true,
-                dmg: 1.,
-            },));
-        }
-    }
+            ..default()
+        },
+        GhostShipProjectile,
+        Lifetime(projectile.lifetime),
+        Velocity {
+            v: projectile.velocity.v, /* (direction * speed of projectile) */
+        },
+        Hitbox {
+            size: Vec2::splat(60.),
+            offset: Vec2::splat(0.),
+            lifetime: Some(Timer::from_seconds(projectile.lifetime, TimerMode::Once)),
+            entity: GHOSTSHIP,
+            projectile: true,
+            enemy: true,
+            dmg: 1.,
+        },
+    ));
 }
 
 /*   MOVE_KRAKEN_PROJECTILE FUNCTION   */
@@ -263,55 +140,13 @@ pub fn ghostship_proj_lifetime_check(
     }
 }
 
-/*   MOVE_KRAKEN FUNCTION   */
-/// Moves the kraken as long as a player is within agro range
-pub fn move_ghostship(
-    time: Res<Time>,
-    mut ghostship_query: Query<&mut Transform, With<GhostShip>>,
-    player_query: Query<(&Transform, &Boat), (With<Boat>, Without<GhostShip>)>,
-    host: Res<HostPlayer>,
-) {
-    for mut transform in ghostship_query.iter_mut() {
-        //Gets positions (Vec3) of the entities
-        let ghostship_translation = transform.translation;
-
-        for (ptransform, boat) in player_query.iter() {
-            if boat.id != host.player.id {
-                continue;
-            }
-            let player_translation = ptransform.translation;
-
-            //Gets positions (Vec2) of the entities
-            let player_position = player_translation.xy();
-            let ghostship_position = ghostship_translation.xy();
-
-            //Gets distance
-            let distance_to_player = ghostship_position.distance(player_position);
-
-            //Check
-            if distance_to_player > GHOSTSHIP_AGRO_RANGE
-                || distance_to_player <= GHOSTSHIP_AGRO_STOP
-            {
-                continue;
-            }
-
-            //Gets direction projectile will be going
-            let direction = (player_translation - ghostship_translation).normalize();
-            let velocity = direction * GHOSTSHIP_MOVEMENT_SPEED;
-
-            //Moves kraken
-            transform.translation += velocity * time.delta_seconds();
-        }
-    }
-}
-
 /*   DESPAWN_ALL_KRAKEN_PROJ   */
 /// Despawns all the kraken's projectiles
 pub fn despawn_all_ghostship_proj(
     mut commands: Commands,
-    mut proj_query: Query<(Entity, &mut Lifetime), With<GhostShipProjectile>>,
+    proj_query: Query<Entity, (With<Lifetime>, With<GhostShipProjectile>)>,
 ) {
-    for (entity, mut lifetime) in proj_query.iter_mut() {
+    for entity in proj_query.iter() {
         commands.entity(entity).despawn();
     }
 }
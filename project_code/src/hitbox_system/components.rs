@@ -1,4 +1,3 @@
-use bevy::math::bounding::Aabb2d;
 use bevy::prelude::*;
 
 // Hitbox component: Represents an area that can cause interactions
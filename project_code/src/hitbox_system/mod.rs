@@ -1,5 +1,3 @@
-use crate::boat::systems::move_boat;
-use crate::player::systems::move_player;
 use bevy::prelude::*;
 pub(crate) mod components;
 mod systems;
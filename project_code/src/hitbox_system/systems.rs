@@ -1,7 +1,5 @@
 use crate::hitbox_system::components::*;
-use bevy::math::bounding::Aabb2d;
 use bevy::prelude::*;
-use bevy::sprite::MaterialMesh2dBundle;
 
 // System to check collisions between hitboxes and hurtboxes
 pub fn check_hitbox_hurtbox_collisions(
@@ -65,8 +63,9 @@ pub fn update_hitbox_lifetimes(
         }
     }
 }
-// Color::srgba(1., 0., 0., 0.5)
-// System to draw debug visualizations for hitboxes and hurtboxes
+// System to draw debug visualizations for hitboxes and hurtboxes, switched on by
+// hand in HitboxPlugin
+#[allow(dead_code)]
 pub fn draw_debug_boxes(
     mut gizmos: Gizmos,
     hitbox_query: Query<(&Transform, &Hitbox)>,
@@ -83,34 +82,6 @@ pub fn draw_debug_boxes(
     }
 }
 
-// Helper function to draw an AABB with the given color
-fn draw_aabb(gizmos: &mut Gizmos, aabb: Aabb2d, transform: &Transform, color: Color) {
-    let position = transform.translation.truncate();
-    let scale = transform.scale.truncate();
-    let min = aabb.min * scale + position;
-    let max = aabb.max * scale + position;
-
-    gizmos.rect_2d(
-        (min + max) / 2.0, // center point
-        0.0,               // rotation (in radians)
-        max - min,         // size
-        color,
-    );
-}
-
-// Helper function to transform an AABB by a given transform
-fn transform_aabb(aabb: Aabb2d, transform: &Transform) -> Aabb2d {
-    let position = transform.translation.truncate();
-    let scale = transform.scale.truncate();
-
-    Aabb2d::new(aabb.min * scale + position, aabb.max * scale + position)
-}
-
-// Helper function to check if two AABBs overlap
-fn aabbs_overlap(a: Aabb2d, b: Aabb2d) -> bool {
-    a.min.x < b.max.x && a.max.x > b.min.x && a.min.y < b.max.y && a.max.y > b.min.y
-}
-
 pub fn create_hitbox(
     commands: &mut Commands,
     entity: Entity,
@@ -151,10 +122,3 @@ pub fn create_hurtbox(
         enemy,
     });
 }
-
-pub fn get_aabb(size: Vec2, offset: Vec2) -> Aabb2d {
-    let half_size = size / 2.0;
-    let aabb = Aabb2d::new(offset - half_size, offset + half_size);
-
-    aabb
-}
@@ -3,7 +3,7 @@ use bevy::prelude::*;
 pub mod components;
 pub mod systems;
 
-use components::{PlayerHUD, ShipHPText, ShipHUD, ShipStats};
+use components::{PlayerHUD, ShipHUD};
 use systems::*;
 
 use crate::{components::GameworldState, level::systems::despawn_with};
@@ -3,7 +3,7 @@ use bevy::prelude::*;
 use crate::{
     boat::components::Boat,
     player::components::Player,
-    wind::components::{CountdownTimer, Wind},
+    wind::components::Wind,
 };
 
 use super::components::*;
@@ -53,15 +53,8 @@ pub fn init_player_hud(mut commands: Commands, asset_server: Res<AssetServer>, _
 }
 
 pub fn init_ship_hud(mut commands: Commands, asset_server: Res<AssetServer>, _wind: Res<Wind>) {
-    commands.insert_resource(ShipStats {
-        hp: 100.0,
-        gold: 50,
-        wind_dir: _wind.direction,
-    });
-
     let font_handle: Handle<Font> = asset_server.load("pixel_pirate.ttf");
     let arrow_handle: Handle<Image> = asset_server.load("s_arrow.png");
-    commands.insert_resource(ArrowTS(arrow_handle.clone()));
 
     let dot = Vec2::new(0., 1.).dot(_wind.direction);
     let mag_w = _wind.direction.length();
@@ -131,7 +124,7 @@ pub fn init_ship_hud(mut commands: Commands, asset_server: Res<AssetServer>, _wi
                         .with_children(|arrow_parent| {
                             arrow_parent.spawn((
                                 TextBundle::from_section(
-                                    format!("Wind:"),
+                                    "Wind:".to_string(),
                                     TextStyle {
                                         font: font_handle.clone(),
                                         font_size: 32.0,
@@ -157,7 +150,7 @@ pub fn init_ship_hud(mut commands: Commands, asset_server: Res<AssetServer>, _wi
 }
 
 pub fn update_player_hud(
-    player_query: Query<(&Player)>,
+    player_query: Query<&Player>,
     mut text_query: Query<(&mut Text, Option<&PlayerHPText>, Option<&GoldText>)>,
 ) {
     if let Ok(player) = player_query.get_single() {
@@ -175,7 +168,6 @@ pub fn update_ship_hud(
     player_query: Query<&Player>,
     ship_query: Query<&Boat>,
     _wind: Res<Wind>,
-    mut query: Query<&mut CountdownTimer>,
     mut text_query: Query<(&mut Text, Option<&ShipHPText>, Option<&GoldText>)>,
     mut arrow_q: Query<&mut Transform, With<Arrow>>,
 ) {
@@ -190,17 +182,16 @@ pub fn update_ship_hud(
                 }
 
                 if let Ok(mut arrow) = arrow_q.get_single_mut() {
-                    if let Ok(timer) = query.get_single() {
-                        if timer.timer.just_finished() {
-                            let dot = Vec2::new(0., 1.).dot(_wind.direction);
-                            let mag_w = _wind.direction.length();
-                            let mag_b = Vec2::new(0., 1.).length();
-                            let cs = dot / (mag_b * mag_w);
-                            let angle = cs.acos();
+                    //the server changes the wind, see sync_wind
+                    if _wind.is_changed() {
+                        let dot = Vec2::new(0., 1.).dot(_wind.direction);
+                        let mag_w = _wind.direction.length();
+                        let mag_b = Vec2::new(0., 1.).length();
+                        let cs = dot / (mag_b * mag_w);
+                        let angle = cs.acos();
 
-                            arrow.rotation = Quat::from_rotation_z(0.0);
-                            arrow.rotate_z(angle);
-                        }
+                        arrow.rotation = Quat::from_rotation_z(0.0);
+                        arrow.rotate_z(angle);
                     }
                 }
             }
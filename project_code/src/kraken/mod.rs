@@ -1,7 +1,7 @@
 use bevy::prelude::*;
 
 pub(crate) mod components;
-mod systems;
+pub(crate) mod systems;
 
 use crate::GameState;
 use crate::GameworldState;
@@ -11,24 +11,12 @@ pub struct KrakenPlugin;
 
 impl Plugin for KrakenPlugin {
     fn build(&self, app: &mut App) {
+        //krakens are spawned, moved and fired by the server, see enemy_sync
         app
-            // Initialize the spawn timer resource
-            .init_resource::<KrakenSpawnTimer>()
-            
-            // Setup systems
-            .add_systems(OnEnter(GameworldState::Ocean), setup_kraken_timer)
-            
             // Main game systems
             .add_systems(
                 Update,
-                (
-                    spawn_kraken,
-                    kraken_attack,
-                    kraken_damaged,
-                    move_kraken_projectile,
-                    kraken_proj_lifetime_check,
-                    move_kraken,
-                )
+                (move_kraken_projectile, kraken_proj_lifetime_check)
                     .run_if(in_state(GameworldState::Ocean))
                     .run_if(in_state(GameState::Running)),
             )
@@ -4,7 +4,10 @@ use systems::*;
 
 use crate::components::{Background, GameworldState};
 use crate::player::components::Sword;
-use crate::{create_env, HostPlayer, Server, UDP};
+use crate::network::systems::send;
+use crate::{HostPlayer, Server, UDP};
+use protocol::messages::ClientMessage;
+use protocol::reliable::Channel;
 
 pub mod components;
 pub mod systems;
@@ -42,11 +45,11 @@ impl Plugin for LevelPlugin {
     }
 }
 
-pub fn got_here_late_packet(udp: Res<UDP>, host: Res<HostPlayer>, server: Res<Server>) {
-    udp.socket
-        .send_to(
-            create_env("got_here_late".to_string(), host.player.clone()).as_bytes(),
-            server.addr.clone(),
-        )
-        .expect("Failed to send [got_here_late] packet");
+pub fn got_here_late_packet(mut udp: ResMut<UDP>, host: Res<HostPlayer>, server: Res<Server>) {
+    send(
+        &mut udp,
+        &server,
+        &ClientMessage::GotHereLate(host.player.clone()),
+        Channel::Reliable,
+    );
 }
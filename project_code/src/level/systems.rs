@@ -4,6 +4,8 @@ use crate::level::components::*;
 use bevy::prelude::*;
 
 use crate::data::gameworld_data::*;
+use protocol::ocean::{generate_ocean, OceanParams};
+use protocol::rng::{GameRng, RngStream};
 
 use rand::Rng;
 
@@ -52,60 +54,34 @@ pub fn setup_ocean(
     ocean_tile_sheet: Res<OceanTileSheet>,
     game_world_state: Res<State<GameworldState>>,
     island_tile_sheet: Res<IslandTileSheet>,
+    ocean_params: Res<OceanParams>,
 ) {
-    let mut rng = rand::thread_rng();
-    let mut tile_index;
-
     if *game_world_state.get() == GameworldState::Ocean {
         // current state --> ocean
 
-        //
-
-        let mut w = 0;
-        let mut h = 0;
-        let mut t = Vec3::new(
-            -OCEAN_W_CENTER + TILE_SIZE as f32 / 2.,
-            -OCEAN_H_CENTER + TILE_SIZE as f32 / 2.,
-            -1.0,
-        );
+        // rebuild the ocean from the seed the server sent, so every player
+        // sails the same water and finds the islands in the same places
+        let layout = generate_ocean(&ocean_params);
 
         // spawn background tiles
-        while (h as f32) * (TILE_SIZE as f32) < OCEAN_LEVEL_H {
-            while (w as f32) * (TILE_SIZE as f32) < OCEAN_LEVEL_W {
-                // weigh it so that its mostly dark blue just for aesthetic reasons
-                let rand = rng.gen_range(0..=10);
-                if rand < 9 {
-                    tile_index = 0
-                } else {
-                    tile_index = 1
-                }
-
-                commands
-                    .spawn((
-                        SpriteBundle {
-                            texture: ocean_tile_sheet.0.clone(),
-                            transform: Transform {
-                                translation: t,
-                                ..default()
-                            },
+        for tile in layout.tiles.iter() {
+            commands
+                .spawn((
+                    SpriteBundle {
+                        texture: ocean_tile_sheet.0.clone(),
+                        transform: Transform {
+                            translation: tile.translation.truncate().extend(-1.0),
                             ..default()
                         },
-                        TextureAtlas {
-                            layout: ocean_tile_sheet.1.clone(),
-                            index: tile_index,
-                        },
-                        OceanTile,
-                    ))
-                    .insert(OceanTile);
-
-                w += 1;
-                t += Vec3::new((TILE_SIZE * 2) as f32, 0., 0.);
-            }
-
-            w = 0;
-            t += Vec3::new(0., (TILE_SIZE * 2) as f32, 0.);
-            t.x = -OCEAN_W_CENTER + (TILE_SIZE * 2) as f32 / 2.0;
-            h += 1;
+                        ..default()
+                    },
+                    TextureAtlas {
+                        layout: ocean_tile_sheet.1.clone(),
+                        index: tile.tile_index,
+                    },
+                    OceanTile,
+                ))
+                .insert(OceanTile);
         }
 
         //spawn 4 islands
@@ -115,126 +91,94 @@ pub fn setup_ocean(
            we need to have collision detection on each island
             - if the ship collides we transition to the island
         */
+        for island in layout.islands.iter() {
+            let island_type = match island.zone {
+                1 => IslandType::Level2,
+                2 => IslandType::Level3,
+                3 => IslandType::Boss,
+                _ => IslandType::Boss,
+            };
 
-        let zone_size = Vec2::new(OCEAN_LEVEL_W / 4.0, OCEAN_LEVEL_H / 4.0);
-        let mut zone_count = 0.0;
-        let mut island_type = IslandType::Level1;
-
-        // loop through each zone
-        while zone_count < 4.0 {
-            match zone_count {
-                1.0 => island_type = IslandType::Level2,
-                2.0 => island_type = IslandType::Level3,
-                3.0 => island_type = IslandType::Boss,
-                _ => island_type = IslandType::Boss,
-            }
-
-            let rand_x = rng.gen_range(-OCEAN_W_CENTER + 64.0..OCEAN_W_CENTER - 64.0);
-
-            // get random y within range
-            let rand_y = rng.gen_range(
-                -OCEAN_H_CENTER + (zone_count * zone_size.y)
-                    ..(-OCEAN_H_CENTER + ((zone_count * zone_size.y) + zone_size.y)) - 128.0,
-            );
+            let position = island.position;
 
-            let rand_position = Vec2::new(rand_x, rand_y);
-
-            println!("spawning island at {}, {}", rand_x, rand_y);
+            println!("spawning island at {}, {}", position.x, position.y);
 
             commands.spawn((
                 SpriteBundle {
                     texture: island_tile_sheet.0.clone(),
-                    transform: Transform::from_xyz(rand_x, rand_y, 10.0),
+                    transform: Transform::from_xyz(position.x, position.y, 10.0),
                     ..default()
                 },
                 Island {
-                    aabb: BoundingBox::new(rand_position, Vec2::splat(64.0)),
+                    aabb: BoundingBox::new(position, Vec2::splat(64.0)),
                     island_type,
+                    zone: island.zone,
                 },
             ));
-
-            zone_count += 1.0;
         }
     }
 }
 
 pub fn setup_island(
     mut commands: Commands,
-    game_world_state: Res<State<GameworldState>>, // get the current gameworld state
+    mut rng: ResMut<GameRng>,
     sand_tile_sheet: Res<SandTileSheet>,
     island_query: Query<&Island, With<Island>>,
     dungeon_tile_sheet: Res<DungeonSheet>,
     ocean_door: Res<OceanDoorHandle>,
     current_island_type: Res<CurrentIslandType>,
 ) {
-    if *game_world_state.get() == GameworldState::Island {
-        let mut rng = rand::thread_rng();
-        let mut tile_index;
-
-        let mut w = 0;
-        let mut h = 0;
-        let mut t = Vec3::new(
-            -SAND_W_CENTER + TILE_SIZE as f32 / 2.,
-            -SAND_H_CENTER + TILE_SIZE as f32 / 2.,
-            -1.0,
-        );
+    let rng = rng.stream(RngStream::Island);
+    let mut tile_index;
 
-        while (h as f32) * (TILE_SIZE as f32) < SAND_LEVEL_H {
-            while (w as f32) * (TILE_SIZE as f32) < SAND_LEVEL_W {
-                let rand = rng.gen_range(0..=10);
-                if rand < 4 {
-                    tile_index = 0
-                } else if rand >= 4 && rand <= 7 {
-                    tile_index = 1
-                } else {
-                    tile_index = 2
-                }
+    let mut w = 0;
+    let mut h = 0;
+    let mut t = Vec3::new(
+        -SAND_W_CENTER + TILE_SIZE as f32 / 2.,
+        -SAND_H_CENTER + TILE_SIZE as f32 / 2.,
+        -1.0,
+    );
+
+    while (h as f32) * (TILE_SIZE as f32) < SAND_LEVEL_H {
+        while (w as f32) * (TILE_SIZE as f32) < SAND_LEVEL_W {
+            let rand = rng.gen_range(0..=10);
+            if rand < 4 {
+                tile_index = 0
+            } else if (4..=7).contains(&rand) {
+                tile_index = 1
+            } else {
+                tile_index = 2
+            }
 
-                commands
-                    .spawn((
-                        SpriteBundle {
-                            texture: sand_tile_sheet.0.clone(),
-                            transform: Transform {
-                                translation: t,
-                                ..default()
-                            },
+            commands
+                .spawn((
+                    SpriteBundle {
+                        texture: sand_tile_sheet.0.clone(),
+                        transform: Transform {
+                            translation: t,
                             ..default()
                         },
-                        TextureAtlas {
-                            layout: sand_tile_sheet.1.clone(),
-                            index: tile_index,
-                        },
-                        SandTile,
-                    ))
-                    .insert(SandTile);
-
-                w += 1;
-                t += Vec3::new((TILE_SIZE * 2) as f32, 0., 0.);
-            }
-
-            w = 0;
-            t += Vec3::new(0., (TILE_SIZE * 2) as f32, 0.);
-            t.x = -SAND_W_CENTER + (TILE_SIZE * 2) as f32 / 2.0;
-            h += 1;
-        }
-
-        if current_island_type.island_type == IslandType::Start {
-            commands.spawn((
-                SpriteBundle {
-                    texture: ocean_door.0.clone(),
-                    transform: Transform {
-                        translation: Vec3::new(-400., 0., 10.0),
                         ..default()
                     },
-                    ..default()
-                },
-                OceanDoor {
-                    aabb: BoundingBox::new(Vec3::new(-400., 0., 10.).truncate(), Vec2::splat(64.0)),
-                },
-            ));
-            return;
+                    TextureAtlas {
+                        layout: sand_tile_sheet.1.clone(),
+                        index: tile_index,
+                    },
+                    SandTile,
+                ))
+                .insert(SandTile);
+
+            w += 1;
+            t += Vec3::new((TILE_SIZE * 2) as f32, 0., 0.);
         }
 
+        w = 0;
+        t += Vec3::new(0., (TILE_SIZE * 2) as f32, 0.);
+        t.x = -SAND_W_CENTER + (TILE_SIZE * 2) as f32 / 2.0;
+        h += 1;
+    }
+
+    if current_island_type.island_type == IslandType::Start {
         commands.spawn((
             SpriteBundle {
                 texture: ocean_door.0.clone(),
@@ -248,71 +192,68 @@ pub fn setup_island(
                 aabb: BoundingBox::new(Vec3::new(-400., 0., 10.).truncate(), Vec2::splat(64.0)),
             },
         ));
+        return;
+    }
 
-        // get the current island type
-        let mut curr_dungeon: Handle<Image> = dungeon_tile_sheet.0.clone();
-        let mut curr_dungeon_type = IslandType::Level1;
-        for island in island_query.iter() {
-            match island.island_type {
-                IslandType::Level1 => {
-                    curr_dungeon = dungeon_tile_sheet.0.clone();
-                    curr_dungeon_type = IslandType::Level1;
-                }
-                IslandType::Level2 => {
-                    curr_dungeon = dungeon_tile_sheet.1.clone();
-                    curr_dungeon_type = IslandType::Level2;
-                }
-                IslandType::Level3 => {
-                    curr_dungeon = dungeon_tile_sheet.2.clone();
-                    curr_dungeon_type = IslandType::Level3;
-                }
-                IslandType::Boss => {
-                    curr_dungeon = dungeon_tile_sheet.3.clone();
-                    curr_dungeon_type = IslandType::Boss;
-                }
-                _ => {
-                    curr_dungeon = dungeon_tile_sheet.0.clone();
-                    curr_dungeon_type = IslandType::Level1;
-                }
+    commands.spawn((
+        SpriteBundle {
+            texture: ocean_door.0.clone(),
+            transform: Transform {
+                translation: Vec3::new(-400., 0., 10.0),
+                ..default()
+            },
+            ..default()
+        },
+        OceanDoor {
+            aabb: BoundingBox::new(Vec3::new(-400., 0., 10.).truncate(), Vec2::splat(64.0)),
+        },
+    ));
+
+    // get the current island type
+    let mut curr_dungeon: Handle<Image> = dungeon_tile_sheet.0.clone();
+    for island in island_query.iter() {
+        match island.island_type {
+            IslandType::Level1 => {
+                curr_dungeon = dungeon_tile_sheet.0.clone();
+            }
+            IslandType::Level2 => {
+                curr_dungeon = dungeon_tile_sheet.1.clone();
+            }
+            IslandType::Level3 => {
+                curr_dungeon = dungeon_tile_sheet.2.clone();
+            }
+            IslandType::Boss => {
+                curr_dungeon = dungeon_tile_sheet.3.clone();
+            }
+            _ => {
+                curr_dungeon = dungeon_tile_sheet.0.clone();
             }
         }
+    }
 
-        // spawn the according dungeon gate
-        commands.spawn((
-            SpriteBundle {
-                texture: curr_dungeon,
-                transform: Transform {
-                    translation: Vec3::new(0., 256., 10.),
-                    ..default()
-                },
+    // spawn the according dungeon gate
+    commands.spawn((
+        SpriteBundle {
+            texture: curr_dungeon,
+            transform: Transform {
+                translation: Vec3::new(0., 256., 10.),
                 ..default()
             },
-            Dungeon {
-                aabb: BoundingBox::new(Vec3::new(0., 256., 10.).truncate(), Vec2::splat(64.0)),
-                dungeon_type: curr_dungeon_type,
-                size: Vec2::splat(64.0),
-            },
-        ));
-    }
+            ..default()
+        },
+        Dungeon {
+            aabb: BoundingBox::new(Vec3::new(0., 256., 10.).truncate(), Vec2::splat(64.0)),
+            size: Vec2::splat(64.0),
+        },
+    ));
 }
 
 pub fn setup_dungeon(
     mut commands: Commands,
     game_world_state: Res<State<GameworldState>>,
-    dungeon_tile_sheet: Res<DungeonSheet>,
-    current_island_type: Res<CurrentIslandType>,
     ocean_door: Res<OceanDoorHandle>,
 ) {
     if *game_world_state.get() == GameworldState::Dungeon {
-        // Get correct dungeon texture based on island type
-        let curr_dungeon = match current_island_type.island_type {
-            IslandType::Start => dungeon_tile_sheet.0.clone(),
-            IslandType::Level1 => dungeon_tile_sheet.0.clone(),
-            IslandType::Level2 => dungeon_tile_sheet.1.clone(),
-            IslandType::Level3 => dungeon_tile_sheet.2.clone(),
-            IslandType::Boss => dungeon_tile_sheet.3.clone(),
-        };
-
         // Spawn dungeon door/exit
         commands.spawn((
             SpriteBundle {
@@ -328,7 +269,6 @@ pub fn setup_dungeon(
                     Vec3::new(-2976.0, -3200.0, 10.0).truncate(),
                     Vec2::splat(64.0),
                 ),
-                dungeon_type: current_island_type.island_type,
                 size: Vec2::splat(64.0),
             },
         ));
@@ -1,52 +1,62 @@
+//bevy systems take whatever they use as arguments, often as nested queries
+#![allow(clippy::too_many_arguments, clippy::type_complexity)]
+
 mod bat;
 mod boat;
 mod boss;
+mod chat;
 mod components;
+mod config;
 mod controls;
 mod data;
+mod dungeon_sync;
 mod enemies;
+mod enemy_sync;
 mod ghost_ship;
 mod hitbox_system;
 mod hud;
 mod kraken;
 mod level;
+mod menu;
 mod network;
 mod player;
 mod poison_skeleton;
+mod pvp;
+mod remote_player;
 mod rock;
 mod shop;
 mod skeleton;
 mod storm;
 mod systems;
-mod transition_box;
 mod wfc;
 mod whirlpool;
 mod wind;
 
 use bat::BatPlugin;
-use bevy::asset;
-use bevy::{prelude::*, window::PresentMode};
-use boat::components::Boat;
+use bevy::{log::LogPlugin, prelude::*, window::PresentMode};
 use boat::systems::*;
 use boat::BoatPlugin;
 use boss::BossPlugin;
+use chat::ChatPlugin;
+use clap::Parser;
+use config::{Cli, ClientConfig};
 use components::*;
 use controls::*;
 use data::gameworld_data::*;
+use dungeon_sync::DungeonSyncPlugin;
 use enemies::*;
-use enemies::*;
-use ghost_ship::components::*;
+use enemy_sync::EnemySyncPlugin;
 use ghost_ship::GhostShipPlugin;
 use hitbox_system::*;
 use hud::HUDPlugin;
-use kraken::components::*;
 use kraken::KrakenPlugin;
-use level::components::*;
 use level::LevelPlugin;
-use player::components::AttackCooldown;
+use menu::MenuPlugin;
 use player::systems::*;
 use player::PlayerPlugin;
 use poison_skeleton::PSkeletonPlugin;
+use pvp::PvpPlugin;
+use remote_player::RemotePlayerPlugin;
 use rock::RockPlugin;
 use shop::ShopPlugin;
 use skeleton::SkeletonPlugin;
@@ -56,110 +66,106 @@ use wfc::WFCPlugin;
 use whirlpool::WhirlpoolPlugin;
 use wind::WindPlugin;
 
-use std::io::ErrorKind;
 use std::net::*;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use network::components::*;
 use network::systems::*;
+use network::NetworkPlugin;
+use protocol::codec::MAX_PACKET_SIZE;
+use protocol::messages::*;
+use protocol::reliable::{Channel, Connection};
+use protocol::rng::GameRng;
+use protocol::simulation::DEFAULT_TICK_RATE;
 
 fn main() {
-    println!("Starting Client");
-
-    //connect to server
-    let udp_addr = "127.0.0.1:0";
-    //let tcp_addr = "127.0.0.1:8000";
-
-    let udp_socket = UdpSocket::bind(udp_addr).unwrap();
-
-    println!(
-        "UDP: Client listening on {}",
-        udp_socket.local_addr().unwrap()
-    );
-
-    let mut ocean = Vec::new();
-    let mut player = Player::default();
-
-    let server = Server {
-        addr: "127.0.0.1:5000".to_string(),
+    //nothing is logged until the app is built, failures before that are printed
+    let cli = Cli::parse();
+    let list_only = cli.list_rooms;
+    let config = match ClientConfig::load(cli) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
     };
 
-    let mut joined = false;
-    loop {
-        let mut buf = [0; 1024];
-
-        if !joined {
-            println!("Trying to join world...");
-
-            player.addr = udp_socket.local_addr().unwrap().to_string();
-            println!("Player addr = {}", player.addr);
-
-            udp_socket
-                .send_to(
-                    create_env("new_player".to_string(), player.clone()).as_bytes(),
-                    server.addr.clone(),
-                )
-                .expect("Failed to send [new_player] packet");
+    //connect to server
+    let udp_socket = match UdpSocket::bind(config.bind) {
+        Ok(socket) => socket,
+        Err(e) => {
+            eprintln!("Could not bind UDP socket to {}: {}", config.bind, e);
+            std::process::exit(1);
         }
+    };
 
-        let result = udp_socket.recv_from(&mut buf);
-
-        match result {
-            Ok((size, src)) => {
-                let env: Envelope = serde_json::from_slice(&buf[..size]).unwrap();
-
-                if env.message.eq("joined_lobby") {
-                    let packet: Packet<i32> = serde_json::from_str(&env.packet).unwrap();
-
-                    let id = packet.payload;
-                    println!("Joined lobby! You are player #{}", id);
-                    player.id = id;
-                    joined = true;
-                } else if env.message.eq("full_lobby") {
-                    panic!("{}", env.packet);
-                } else if env.message.eq("load_ocean") {
-                    let packet: Packet<OceanT> = serde_json::from_str(&env.packet).unwrap();
-
-                    ocean.push(packet.payload);
-                } else {
-                    println!("Recieved invalid packet");
-                }
+    let rng = GameRng::from_seed(config.rng_seed);
+    let mut udp = UDP {
+        socket: udp_socket,
+        connection: Connection::new(),
+    };
 
-                if ocean.len() >= OCEAN_LENGTH as usize {
-                    break;
-                }
-            }
+    if list_only {
+        let server = match config.server_addr() {
+            Ok(addr) => Server {
+                addr,
+                tick_seconds: 0.,
+            },
             Err(e) => {
-                eprintln!("Something happened: {}", e);
+                eprintln!("{}", e);
+                std::process::exit(1);
             }
-        }
-        if !joined {
-            std::thread::sleep(Duration::from_secs(2));
-        }
-    }
+        };
+
+        // wait a bit for the server's answer before asking again
+        udp.socket
+            .set_read_timeout(Some(Duration::from_secs(2)))
+            .expect("Failed to set socket timeout");
 
-    println!("Ocean map done. Final size: {}", ocean.len());
+        list_rooms(&mut udp, &server, &mut vec![0; MAX_PACKET_SIZE]);
+        return;
+    }
 
-    if !udp_socket.set_nonblocking(true).is_ok() {
-        panic!("Non blocking wasn't successful; terminating");
+    if let Err(e) = udp.socket.set_nonblocking(true) {
+        eprintln!("Could not make the UDP socket non-blocking: {}", e);
+        std::process::exit(1);
     }
 
+    //the main menu fills in the address when joining, and the tick length
+    //once the server tells us its tick rate
+    let server = Server {
+        addr: SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0)),
+        tick_seconds: (1. / DEFAULT_TICK_RATE) as f32,
+    };
+    let player = Player {
+        name: config.name.clone(),
+        ..default()
+    };
+
     App::new()
-        .insert_resource(UDP { socket: udp_socket })
-        .insert_resource(Ocean { map: ocean })
-        .insert_resource(HostPlayer { player: player })
+        .insert_resource(udp)
+        .insert_resource(HostPlayer { player })
         .insert_resource(server)
-        .add_plugins(DefaultPlugins.set(WindowPlugin {
-            primary_window: Some(Window {
-                title: "Sea of Fortune | Build 0.2".into(),
-                resolution: (WIN_W, WIN_H).into(),
-                present_mode: PresentMode::Fifo,
-                ..default()
-            }),
-            ..default()
-        }))
+        .insert_resource(rng)
+        .insert_resource(Time::<Fixed>::from_hz(DEFAULT_TICK_RATE))
+        .add_plugins(
+            DefaultPlugins
+                .set(WindowPlugin {
+                    primary_window: Some(Window {
+                        title: "Sea of Fortune | Build 0.2".into(),
+                        resolution: (WIN_W, WIN_H).into(),
+                        present_mode: PresentMode::Fifo,
+                        ..default()
+                    }),
+                    ..default()
+                })
+                .set(LogPlugin {
+                    level: config.level(),
+                    ..default()
+                }),
+        )
         .init_resource::<CurrMousePos>()
-        .add_systems(Startup, setup_gameworld)
+        .add_systems(Startup, (log_startup, setup_gameworld))
         .add_plugins(PlayerPlugin)
         .add_plugins(BoatPlugin)
         .add_plugins(BatPlugin)
@@ -175,6 +181,13 @@ fn main() {
         .add_plugins(WhirlpoolPlugin)
         .add_plugins(BossPlugin)
         .add_plugins(HUDPlugin)
+        .add_plugins(ChatPlugin)
+        .add_plugins(NetworkPlugin)
+        .add_plugins(RemotePlayerPlugin)
+        .add_plugins(EnemySyncPlugin)
+        .add_plugins(DungeonSyncPlugin)
+        .add_plugins(PvpPlugin)
+        .add_plugins(MenuPlugin)
         .add_plugins(PSkeletonPlugin)
         .add_plugins(StormPlugin)
         .add_systems(
@@ -203,29 +216,114 @@ fn main() {
         .add_systems(Update, update_dungeon_collision)
         .insert_state(GameworldState::MainMenu)
         .insert_state(GameState::Running)
-        .insert_resource(SpawnLocations::default())
-        .insert_resource(PlayerEntities::default())
         .insert_resource(CurrentIslandType::default())
-        .insert_resource(StateTransitionCooldown::default())
         .add_systems(Last, leave)
+        .insert_resource(config)
         .run();
 }
 
+/*   LOG_STARTUP FUNCTION   */
+/// Logs where the client is listening and the seed to play the session back
+/// with, once the log is up
+fn log_startup(udp: Res<UDP>, rng: Res<GameRng>) {
+    info!("Starting Client");
+    match udp.socket.local_addr() {
+        Ok(addr) => info!("UDP: Client listening on {}", addr),
+        Err(e) => warn!("Could not tell where the UDP socket is bound: {}", e),
+    }
+    info!(
+        "Random numbers from seed {}, pass --rng-seed to play this session back",
+        rng.seed()
+    );
+}
+
+/*   LIST_ROOMS FUNCTION   */
+/// Asks the server which rooms are open and prints them, for --list-rooms.
+/// Runs without an app, so there's no log to go through
+fn list_rooms(udp: &mut UDP, server: &Server, buf: &mut [u8]) {
+    for _ in 0..5 {
+        send(udp, server, &ClientMessage::ListRooms, Channel::Unreliable);
+
+        let Ok((size, src)) = udp.socket.recv_from(buf) else {
+            continue;
+        };
+        if src != server.addr {
+            continue;
+        }
+
+        let messages = match udp.connection.receive(&udp.socket, src, &buf[..size]) {
+            Ok(messages) => messages,
+            Err(e) => {
+                eprintln!("Recieved invalid packet from [{}]: {}", src.ip(), e);
+                continue;
+            }
+        };
+
+        for message in messages {
+            if let ServerMessage::RoomList(rooms) = message {
+                if rooms.is_empty() {
+                    println!("No rooms open, joining will open one");
+                }
+                for room in rooms {
+                    println!("{}  {}/{} players", room.code, room.players, room.capacity);
+                }
+                return;
+            }
+        }
+    }
+
+    eprintln!("No answer from {}", server.addr);
+}
+
 fn leave(
     exit_events: EventReader<AppExit>,
     mut exit_triggered: Local<bool>,
-    udp: Res<UDP>,
+    mut udp: ResMut<UDP>,
     player: Res<HostPlayer>,
     server: Res<Server>,
 ) {
-    if !*exit_triggered && exit_events.len() > 0 {
+    if !*exit_triggered && !exit_events.is_empty() {
         *exit_triggered = true;
 
-        udp.socket
-            .send_to(
-                create_env("player_leave".to_string(), player.player.clone()).as_bytes(),
-                server.addr.clone(),
-            )
-            .expect("Failed to send [player_leave]] packet");
+        //never made it into a room, there's nothing to leave
+        if player.player.id < 0 {
+            return;
+        }
+
+        send(
+            &mut udp,
+            &server,
+            &ClientMessage::PlayerLeave(player.player.clone()),
+            Channel::Reliable,
+        );
+
+        //the app is about to close, so give the leave a moment to get through
+        //instead of leaving a ghost player behind in the lobby
+        let UDP { socket, connection } = &mut *udp;
+        let mut buf = vec![0; MAX_PACKET_SIZE];
+        let deadline = Instant::now() + Duration::from_secs(1);
+
+        while Instant::now() < deadline {
+            while let Ok((size, src)) = socket.recv_from(&mut buf) {
+                if src != server.addr {
+                    continue;
+                }
+
+                if let Ok(messages) = connection.receive(socket, src, &buf[..size]) {
+                    if messages
+                        .iter()
+                        .any(|message| matches!(message, ServerMessage::LeaveSuccess))
+                    {
+                        info!("Left lobby");
+                        return;
+                    }
+                }
+            }
+
+            connection.resend(socket, server.addr, Instant::now());
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        warn!("Server never confirmed the leave");
     }
 }
@@ -0,0 +1,100 @@
+use bevy::prelude::*;
+use protocol::messages::{Roster, MAX_NAME_LENGTH};
+
+//menu colors
+pub const BUTTON_COLOR: Color = Color::srgb(0.15, 0.15, 0.15);
+pub const BUTTON_HOVER_COLOR: Color = Color::srgb(0.25, 0.25, 0.25);
+pub const BUTTON_PRESS_COLOR: Color = Color::srgb(0.35, 0.35, 0.35);
+pub const TEXT_COLOR: Color = Color::srgb(0.95, 0.9, 0.85);
+pub const ERROR_COLOR: Color = Color::srgb(0.9, 0.3, 0.3);
+pub const READY_COLOR: Color = Color::srgb(0.4, 0.85, 0.4);
+
+/// Root node of the menu, everything in it is rebuilt when the ConnectionState
+/// or the lobby changes
+#[derive(Component)]
+pub struct MenuUI;
+
+/// Text counting the tries while connecting
+#[derive(Component)]
+pub struct JoinProgress;
+
+/// A box the player can type into on the connect screen
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TextField {
+    Server,
+    Name,
+    Room,
+}
+
+impl TextField {
+    /// Longest text the field takes
+    pub fn max_len(self) -> usize {
+        match self {
+            TextField::Server => 64,
+            TextField::Name => MAX_NAME_LENGTH,
+            TextField::Room => 8,
+        }
+    }
+
+    /// The field Tab moves to
+    pub fn next(self) -> TextField {
+        match self {
+            TextField::Server => TextField::Name,
+            TextField::Name => TextField::Room,
+            TextField::Room => TextField::Server,
+        }
+    }
+}
+
+#[derive(Component, Clone, Copy, Debug)]
+pub enum MenuButton {
+    /// Open a new room
+    Host,
+    /// Join the room in the room field, or any room if it's empty
+    Join,
+    Ready,
+    /// Moves on to the next crew, only shown when PvP is on
+    Crew,
+    /// Only shown to the room's host
+    Start,
+    Leave,
+    /// Stop trying to join
+    Cancel,
+    /// Back to the connect screen from the error screen
+    Back,
+}
+
+/// What's been typed into the connect screen, kept when the screen is rebuilt
+#[derive(Resource)]
+pub struct MenuForm {
+    pub server: String,
+    pub name: String,
+    pub room: String,
+    pub focus: Option<TextField>,
+}
+
+impl MenuForm {
+    pub fn field(&self, field: TextField) -> &String {
+        match field {
+            TextField::Server => &self.server,
+            TextField::Name => &self.name,
+            TextField::Room => &self.room,
+        }
+    }
+
+    pub fn field_mut(&mut self, field: TextField) -> &mut String {
+        match field {
+            TextField::Server => &mut self.server,
+            TextField::Name => &mut self.name,
+            TextField::Room => &mut self.room,
+        }
+    }
+}
+
+/// The room we're in and everyone in it, as last sent by the server. Kept up to
+/// date after setting sail too, for the crews
+#[derive(Resource, Default)]
+pub struct Lobby {
+    pub room: String,
+    pub roster: Roster,
+}
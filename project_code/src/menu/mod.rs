@@ -0,0 +1,41 @@
+use bevy::prelude::*;
+
+pub mod components;
+pub mod systems;
+
+use components::{Lobby, MenuUI};
+use systems::*;
+
+use crate::{components::GameworldState, level::systems::despawn_with};
+
+/// The main menu: pick a server, a name and a room, wait in the room's lobby
+/// until the host sets sail. Joining itself is done by the network module, the
+/// menu shows how it's going
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Lobby>()
+            .add_systems(
+                OnEnter(GameworldState::MainMenu),
+                (setup_menu, join_from_command_line.run_if(run_once())),
+            )
+            .add_systems(
+                Update,
+                (
+                    rebuild_menu,
+                    focus_text_fields,
+                    type_into_text_fields,
+                    update_text_fields.after(type_into_text_fields),
+                    handle_connect_buttons,
+                    handle_lobby_buttons,
+                    color_menu_buttons,
+                    update_join_progress.after(rebuild_menu),
+                    start_session,
+                )
+                    .run_if(in_state(GameworldState::MainMenu)),
+            )
+            .add_systems(Update, update_lobby)
+            .add_systems(OnExit(GameworldState::MainMenu), despawn_with::<MenuUI>);
+    }
+}
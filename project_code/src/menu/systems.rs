@@ -0,0 +1,599 @@
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::input::ButtonState;
+use bevy::prelude::*;
+use protocol::combat::MAX_CREWS;
+use protocol::messages::{ClientMessage, RoomChoice, ServerMessage};
+use protocol::reliable::{Channel, Connection};
+
+use super::components::*;
+use crate::components::{CurrentIslandType, GameworldState};
+use crate::config::ClientConfig;
+use crate::level::components::IslandType;
+use crate::network::components::{
+    ConnectionState, HostPlayer, JoinAttempt, JoinRequest, ServerEvent, ServerLink,
+    MAX_JOIN_ATTEMPTS,
+};
+use crate::network::systems::{give_up, send};
+
+/*   SETUP_MENU FUNCTION   */
+/// Spawns the menu's root node. The connect screen is filled in from the config
+/// the first time, and keeps what was typed into it after that
+pub fn setup_menu(mut commands: Commands, config: Res<ClientConfig>, form: Option<Res<MenuForm>>) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            ..default()
+        },
+        MenuUI,
+    ));
+
+    if form.is_none() {
+        commands.insert_resource(MenuForm {
+            server: config.server.clone(),
+            name: config.name.clone(),
+            room: config.room.clone().unwrap_or_default(),
+            focus: None,
+        });
+    }
+}
+
+/*   JOIN_FROM_COMMAND_LINE FUNCTION   */
+/// Starts joining right away when a room was given on the command line
+pub fn join_from_command_line(
+    mut commands: Commands,
+    config: Res<ClientConfig>,
+    mut link: ServerLink,
+    mut next_state: ResMut<NextState<ConnectionState>>,
+) {
+    if !config.join_on_start() {
+        return;
+    }
+
+    let attempt = start_join(
+        &config,
+        &config.server,
+        &config.name,
+        config.room_choice(),
+        &mut link,
+    );
+    begin_join(&mut commands, &mut next_state, attempt);
+}
+
+/*   START_JOIN FUNCTION   */
+/// Looks up the server and gets a JoinAttempt ready for it, send_join_request
+/// sends the actual requests
+fn start_join(
+    config: &ClientConfig,
+    address: &str,
+    name: &str,
+    room: RoomChoice,
+    link: &mut ServerLink,
+) -> Result<JoinAttempt, String> {
+    let addr = config.resolve(address.trim()).map_err(|e| e.to_string())?;
+
+    //a new server hasn't seen any of our reliable packets yet
+    if addr != link.server.addr {
+        link.server.addr = addr;
+        link.udp.connection = Connection::new();
+    }
+
+    link.host.player.name = name.trim().to_string();
+    link.host.player.addr = link.udp.socket.local_addr().unwrap().to_string();
+    info!(
+        "Joining server at {} as {:?}",
+        link.server.addr, link.host.player.name
+    );
+
+    Ok(JoinAttempt::new(JoinRequest::New(room)))
+}
+
+fn begin_join(
+    commands: &mut Commands,
+    next_state: &mut NextState<ConnectionState>,
+    attempt: Result<JoinAttempt, String>,
+) {
+    match attempt {
+        Ok(attempt) => {
+            commands.insert_resource(attempt);
+            next_state.set(ConnectionState::Connecting);
+        }
+        Err(reason) => next_state.set(ConnectionState::Failed(reason)),
+    }
+}
+
+/*   UPDATE_LOBBY FUNCTION   */
+/// Keeps the lobby's room code and roster up to date
+pub fn update_lobby(mut events: EventReader<ServerEvent>, mut lobby: ResMut<Lobby>) {
+    for ServerEvent(message) in events.read() {
+        match message {
+            ServerMessage::JoinedLobby { room, .. } => lobby.room = room.clone(),
+            ServerMessage::Roster(roster) => lobby.roster = roster.clone(),
+            _ => {}
+        }
+    }
+}
+
+/*   START_SESSION FUNCTION   */
+/// Leaves the menu for the starting island once the host sets sail
+pub fn start_session(
+    mut events: EventReader<ServerEvent>,
+    mut next_state: ResMut<NextState<GameworldState>>,
+    mut current_island_type: ResMut<CurrentIslandType>,
+) {
+    for ServerEvent(message) in events.read() {
+        if let ServerMessage::SessionStarted = message {
+            info!("Setting sail!");
+            current_island_type.island_type = IslandType::Start;
+            current_island_type.zone = None;
+            next_state.set(GameworldState::Island);
+        }
+    }
+}
+
+/*   HANDLE_CONNECT_BUTTONS FUNCTION   */
+/// Host opens a new room, Join goes into the room typed in, or any room if
+/// that's left empty
+pub fn handle_connect_buttons(
+    mut commands: Commands,
+    interaction_query: Query<(&Interaction, &MenuButton), Changed<Interaction>>,
+    config: Res<ClientConfig>,
+    form: Res<MenuForm>,
+    mut link: ServerLink,
+    mut next_state: ResMut<NextState<ConnectionState>>,
+) {
+    for (interaction, button) in interaction_query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        let room = match button {
+            MenuButton::Host => RoomChoice::Create { seed: config.seed },
+            MenuButton::Join if form.room.trim().is_empty() => RoomChoice::Any,
+            MenuButton::Join => RoomChoice::Join {
+                code: form.room.trim().to_uppercase(),
+            },
+            _ => continue,
+        };
+
+        let attempt = start_join(&config, &form.server, &form.name, room, &mut link);
+        begin_join(&mut commands, &mut next_state, attempt);
+    }
+}
+
+/*   HANDLE_LOBBY_BUTTONS FUNCTION   */
+/// Ready toggles whether we're ready to set sail, Crew moves us on to the next
+/// crew, Start sets sail (host only) and Leave goes back to the connect screen.
+/// Cancel gives up on joining and Back leaves the error screen
+pub fn handle_lobby_buttons(
+    interaction_query: Query<(&Interaction, &MenuButton), Changed<Interaction>>,
+    lobby: Res<Lobby>,
+    mut link: ServerLink,
+    mut next_state: ResMut<NextState<ConnectionState>>,
+) {
+    let ServerLink { udp, server, host } = &mut link;
+
+    for (interaction, button) in interaction_query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match button {
+            MenuButton::Ready => {
+                let ready = lobby
+                    .roster
+                    .players
+                    .iter()
+                    .any(|player| player.id == host.player.id && player.ready);
+
+                send(
+                    udp,
+                    server,
+                    &ClientMessage::SetReady(!ready),
+                    Channel::Reliable,
+                );
+            }
+            MenuButton::Crew => {
+                let crew = lobby
+                    .roster
+                    .players
+                    .iter()
+                    .find(|player| player.id == host.player.id)
+                    .and_then(|player| player.crew);
+
+                //no crew, then every crew in turn, then back to no crew
+                let next = match crew {
+                    None => Some(0),
+                    Some(crew) if crew + 1 < MAX_CREWS => Some(crew + 1),
+                    Some(_) => None,
+                };
+
+                send(
+                    udp,
+                    server,
+                    &ClientMessage::SetCrew(next),
+                    Channel::Reliable,
+                );
+            }
+            MenuButton::Start => {
+                send(udp, server, &ClientMessage::StartSession, Channel::Reliable);
+            }
+            MenuButton::Leave => {
+                send(
+                    udp,
+                    server,
+                    &ClientMessage::PlayerLeave(host.player.clone()),
+                    Channel::Reliable,
+                );
+                next_state.set(ConnectionState::Disconnected);
+            }
+            MenuButton::Cancel => give_up(udp, server, host, &mut next_state),
+            MenuButton::Back => next_state.set(ConnectionState::Disconnected),
+            MenuButton::Host | MenuButton::Join => {}
+        }
+    }
+}
+
+/*   UPDATE_JOIN_PROGRESS FUNCTION   */
+/// Counts the tries on the connecting screen
+pub fn update_join_progress(
+    attempt: Option<Res<JoinAttempt>>,
+    mut text_query: Query<(&mut Text, Ref<JoinProgress>)>,
+) {
+    let Some(attempt) = attempt else {
+        return;
+    };
+
+    for (mut text, progress) in text_query.iter_mut() {
+        if attempt.is_changed() || progress.is_added() {
+            text.sections[0].value = format!("Try {} of {}", attempt.attempts, MAX_JOIN_ATTEMPTS);
+        }
+    }
+}
+
+/*   COLOR_MENU_BUTTONS FUNCTION   */
+/// Highlights buttons and text fields under the mouse, the only things that
+/// can be clicked while the menu is up
+pub fn color_menu_buttons(
+    mut interaction_query: Query<(&Interaction, &mut BackgroundColor), Changed<Interaction>>,
+) {
+    for (interaction, mut color) in interaction_query.iter_mut() {
+        *color = match *interaction {
+            Interaction::Pressed => BUTTON_PRESS_COLOR,
+            Interaction::Hovered => BUTTON_HOVER_COLOR,
+            Interaction::None => BUTTON_COLOR,
+        }
+        .into();
+    }
+}
+
+/*   FOCUS_TEXT_FIELDS FUNCTION   */
+/// Clicking a text field makes it the one typing goes into
+pub fn focus_text_fields(
+    interaction_query: Query<(&Interaction, &TextField), Changed<Interaction>>,
+    mut form: ResMut<MenuForm>,
+) {
+    for (interaction, field) in interaction_query.iter() {
+        if *interaction == Interaction::Pressed {
+            form.focus = Some(*field);
+        }
+    }
+}
+
+/*   TYPE_INTO_TEXT_FIELDS FUNCTION   */
+/// Types into the focused text field. Tab moves on to the next one
+pub fn type_into_text_fields(
+    mut keyboard_events: EventReader<KeyboardInput>,
+    mut form: ResMut<MenuForm>,
+) {
+    for event in keyboard_events.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+        let Some(field) = form.focus else {
+            continue;
+        };
+
+        match &event.logical_key {
+            Key::Character(text) => {
+                let value = form.field_mut(field);
+                for c in text.chars().filter(|c| !c.is_control()) {
+                    if value.chars().count() < field.max_len() {
+                        value.push(c);
+                    }
+                }
+            }
+            Key::Space => {
+                let value = form.field_mut(field);
+                if value.chars().count() < field.max_len() {
+                    value.push(' ');
+                }
+            }
+            Key::Backspace => {
+                form.field_mut(field).pop();
+            }
+            Key::Tab => form.focus = Some(field.next()),
+            _ => {}
+        }
+    }
+}
+
+/*   UPDATE_TEXT_FIELDS FUNCTION   */
+/// Shows what's been typed, with a cursor in the focused field
+pub fn update_text_fields(
+    form: Res<MenuForm>,
+    field_query: Query<(&TextField, &Children)>,
+    mut text_query: Query<&mut Text>,
+) {
+    if !form.is_changed() {
+        return;
+    }
+
+    for (field, children) in field_query.iter() {
+        let mut iter = text_query.iter_many_mut(children);
+        while let Some(mut text) = iter.fetch_next() {
+            text.sections[0].value = field_text(&form, *field);
+        }
+    }
+}
+
+fn field_text(form: &MenuForm, field: TextField) -> String {
+    if form.focus == Some(field) {
+        format!("{}_", form.field(field))
+    } else {
+        form.field(field).clone()
+    }
+}
+
+/*   REBUILD_MENU FUNCTION   */
+/// Rebuilds the menu whenever the ConnectionState or the lobby changes: the
+/// connect screen, the connecting screen, the room's lobby once we're in, or
+/// the error screen when something went wrong
+pub fn rebuild_menu(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    state: Res<State<ConnectionState>>,
+    lobby: Res<Lobby>,
+    form: Res<MenuForm>,
+    host: Res<HostPlayer>,
+    root_query: Query<(Entity, Ref<MenuUI>)>,
+) {
+    let Ok((root, root_ref)) = root_query.get_single() else {
+        return;
+    };
+    if !state.is_changed() && !lobby.is_changed() && !root_ref.is_added() {
+        return;
+    }
+
+    let font = asset_server.load("pixel_pirate.ttf");
+    let style = |size: f32, color: Color| TextStyle {
+        font: font.clone(),
+        font_size: size,
+        color,
+    };
+
+    commands.entity(root).despawn_descendants();
+    commands.entity(root).with_children(|parent| {
+        parent
+            .spawn(NodeBundle {
+                style: Style {
+                    width: Val::Px(480.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    padding: UiRect::all(Val::Px(20.0)),
+                    ..default()
+                },
+                background_color: Color::srgba(0.1, 0.1, 0.1, 0.85).into(),
+                ..default()
+            })
+            .with_children(|panel| match state.get() {
+                ConnectionState::Syncing | ConnectionState::Connected => {
+                    let roster = &lobby.roster;
+
+                    panel.spawn(TextBundle::from_section(
+                        format!("Room {}", lobby.room),
+                        style(40.0, TEXT_COLOR),
+                    ));
+                    panel.spawn(TextBundle::from_section(
+                        if *state.get() == ConnectionState::Syncing {
+                            "Catching up with the room..."
+                        } else {
+                            "Share the code with your crew"
+                        },
+                        style(18.0, TEXT_COLOR),
+                    ));
+                    if roster.pvp.enabled {
+                        panel.spawn(TextBundle::from_section(
+                            format!(
+                                "PvP is on, friendly fire {}. {} gold per sinking",
+                                if roster.pvp.friendly_fire {
+                                    "on"
+                                } else {
+                                    "off"
+                                },
+                                roster.pvp.bounty
+                            ),
+                            style(18.0, ERROR_COLOR),
+                        ));
+                    }
+
+                    for player in roster.players.iter() {
+                        let mut name = player.name.clone();
+                        if player.id == roster.host {
+                            name.push_str(" (captain)");
+                        }
+                        if player.id == host.player.id {
+                            name.push_str(" (you)");
+                        }
+                        let (state, color) = if player.ready {
+                            ("Ready", READY_COLOR)
+                        } else {
+                            ("Not ready", TEXT_COLOR)
+                        };
+
+                        if roster.pvp.enabled {
+                            name.push_str(&format!(" [{}]", crew_name(player.crew)));
+                        }
+
+                        panel.spawn(TextBundle::from_sections([
+                            TextSection::new(format!("{}  ", name), style(22.0, TEXT_COLOR)),
+                            TextSection::new(state, style(22.0, color)),
+                        ]));
+                    }
+
+                    if roster.pvp.enabled {
+                        let crew = roster
+                            .players
+                            .iter()
+                            .find(|player| player.id == host.player.id)
+                            .and_then(|player| player.crew);
+                        spawn_button(
+                            panel,
+                            &crew_name(crew),
+                            MenuButton::Crew,
+                            style(24.0, TEXT_COLOR),
+                        );
+                    }
+
+                    let ready = roster
+                        .players
+                        .iter()
+                        .any(|player| player.id == host.player.id && player.ready);
+                    spawn_button(
+                        panel,
+                        if ready { "Not ready" } else { "Ready" },
+                        MenuButton::Ready,
+                        style(24.0, TEXT_COLOR),
+                    );
+
+                    if roster.host == host.player.id {
+                        if roster.all_ready() {
+                            spawn_button(
+                                panel,
+                                "Set sail",
+                                MenuButton::Start,
+                                style(24.0, TEXT_COLOR),
+                            );
+                        } else {
+                            panel.spawn(TextBundle::from_section(
+                                "Waiting for the crew to be ready",
+                                style(18.0, TEXT_COLOR),
+                            ));
+                        }
+                    } else {
+                        panel.spawn(TextBundle::from_section(
+                            "Waiting for the captain to set sail",
+                            style(18.0, TEXT_COLOR),
+                        ));
+                    }
+
+                    spawn_button(panel, "Leave", MenuButton::Leave, style(24.0, TEXT_COLOR));
+                }
+                ConnectionState::Connecting => {
+                    panel.spawn(TextBundle::from_section(
+                        "Sea of Fortune",
+                        style(48.0, TEXT_COLOR),
+                    ));
+                    panel.spawn(TextBundle::from_section(
+                        format!("Connecting to {}...", form.server.trim()),
+                        style(22.0, TEXT_COLOR),
+                    ));
+                    panel.spawn((
+                        TextBundle::from_section("", style(18.0, TEXT_COLOR)),
+                        JoinProgress,
+                    ));
+                    spawn_button(panel, "Cancel", MenuButton::Cancel, style(24.0, TEXT_COLOR));
+                }
+                ConnectionState::Failed(reason) => {
+                    panel.spawn(TextBundle::from_section(
+                        "Something went wrong",
+                        style(40.0, TEXT_COLOR),
+                    ));
+                    panel.spawn(
+                        TextBundle::from_section(reason.clone(), style(20.0, ERROR_COLOR))
+                            .with_text_justify(JustifyText::Center),
+                    );
+                    spawn_button(panel, "Back", MenuButton::Back, style(24.0, TEXT_COLOR));
+                }
+                ConnectionState::Disconnected => {
+                    panel.spawn(TextBundle::from_section(
+                        "Sea of Fortune",
+                        style(48.0, TEXT_COLOR),
+                    ));
+
+                    for (label, field) in [
+                        ("Server", TextField::Server),
+                        ("Name", TextField::Name),
+                        ("Room code (empty for any room)", TextField::Room),
+                    ] {
+                        panel.spawn(TextBundle::from_section(label, style(18.0, TEXT_COLOR)));
+                        panel
+                            .spawn((
+                                ButtonBundle {
+                                    style: Style {
+                                        width: Val::Px(400.0),
+                                        height: Val::Px(40.0),
+                                        margin: UiRect::bottom(Val::Px(10.0)),
+                                        padding: UiRect::horizontal(Val::Px(8.0)),
+                                        align_items: AlignItems::Center,
+                                        border: UiRect::all(Val::Px(2.0)),
+                                        ..default()
+                                    },
+                                    background_color: BUTTON_COLOR.into(),
+                                    border_color: Color::WHITE.into(),
+                                    ..default()
+                                },
+                                field,
+                            ))
+                            .with_children(|button| {
+                                button.spawn(TextBundle::from_section(
+                                    field_text(&form, field),
+                                    style(22.0, TEXT_COLOR),
+                                ));
+                            });
+                    }
+
+                    spawn_button(panel, "Host", MenuButton::Host, style(24.0, TEXT_COLOR));
+                    spawn_button(panel, "Join", MenuButton::Join, style(24.0, TEXT_COLOR));
+                }
+            });
+    });
+}
+
+/// How a crew is shown in the lobby, crews are counted from 1
+fn crew_name(crew: Option<u8>) -> String {
+    match crew {
+        Some(crew) => format!("Crew {}", crew + 1),
+        None => "No crew".to_string(),
+    }
+}
+
+fn spawn_button(parent: &mut ChildBuilder, label: &str, button: MenuButton, style: TextStyle) {
+    parent
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    width: Val::Px(250.0),
+                    height: Val::Px(50.0),
+                    margin: UiRect::all(Val::Px(8.0)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    border: UiRect::all(Val::Px(2.0)),
+                    ..default()
+                },
+                background_color: BUTTON_COLOR.into(),
+                border_color: Color::WHITE.into(),
+                ..default()
+            },
+            button,
+        ))
+        .with_children(|button| {
+            button.spawn(TextBundle::from_section(label, style));
+        });
+}
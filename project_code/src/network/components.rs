@@ -1,139 +1,130 @@
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
-use std::io::*;
 use std::net::*;
-use std::sync::{Arc, Mutex};
-
-use level::components::*;
-
-use crate::level;
-
-#[derive(Serialize, Deserialize)]
-pub struct Envelope {
-    pub message: String,
-    pub packet: String,
-}
-
-#[derive(Serialize, Deserialize)]
-pub struct Packet<T> {
-    pub payload: T,
-}
 
+use protocol::messages::{RoomChoice, ServerMessage};
+use protocol::reliable::Connection;
+use protocol::simulation::INTERPOLATION_TICKS;
+
+pub use protocol::components::*;
+
+/// Seconds of silence from the server before the connection counts as dropped.
+/// Snapshots arrive every tick, so this is a lot of missed packets
+pub const SERVER_TIMEOUT_SECONDS: f32 = 3.;
+
+//join requests are resent after 0.5s, 1s, 2s... up to 8s between tries
+pub const RETRY_FIRST_SECONDS: f32 = 0.5;
+pub const RETRY_MAX_SECONDS: f32 = 8.;
+/// Join requests sent before giving up on the server
+pub const MAX_JOIN_ATTEMPTS: u32 = 8;
+
+/*   CONNECTION STATES   */
+/// Where the client is in talking to the server, moved along by the systems in
+/// the network module. These states include
+/// * Disconnected
+/// * Connecting
+/// * Syncing
+/// * Connected
+/// * Failed
+#[derive(States, Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub enum ConnectionState {
+    #[default]
+    Disconnected, //not in a room, or gave up on the last one
+    Connecting,     //sending a JoinAttempt until the server answers
+    Syncing,        //in a room, waiting for the first snapshot of it
+    Connected,      //in a room and hearing from the server
+    Failed(String), //couldn't get in or lost the room, with why for the error screen
+}
+
+/// What a JoinAttempt asks the server for
+#[derive(Clone, Debug)]
+pub enum JoinRequest {
+    /// A slot in a room, as picked in the main menu
+    New(RoomChoice),
+    /// Our old slot back after the connection dropped
+    Rejoin { room: String, id: i32, token: u64 },
+}
+
+/// The request being sent while Connecting, resent with a growing delay until
+/// the server answers or MAX_JOIN_ATTEMPTS run out
 #[derive(Resource)]
-pub struct Counter {
-    pub count: i32,
-}
-
-impl Counter {
-    pub fn init() -> Counter {
-        Counter { count: 0 }
-    }
-
-    pub fn next(&mut self) -> i32 {
-        self.count += 1;
-        self.count
-    }
-}
-
-#[derive(Resource, Serialize, Deserialize)]
-pub struct HostPlayer {
-    pub player: Player,
-}
-
-#[derive(Clone, Serialize, Deserialize, Component)]
-pub struct Player {
-    pub id: i32,
-    pub addr: String,
-    pub pos: Vec3,
-    pub rot: Quat,
-    pub boat: bool,
-    pub used: bool,
-}
-
-impl Player {
-    pub fn default() -> Player {
-        Player {
-            id: -1,
-            addr: "null".to_string(),
-            pos: Vec3::splat(0.),
-            rot: Quat::from_rotation_x((90.0_f32).to_radians()),
-            boat: true,
-            used: false,
+pub struct JoinAttempt {
+    pub request: JoinRequest,
+    pub attempts: u32,
+    pub retry: Timer,
+}
+
+impl JoinAttempt {
+    pub fn new(request: JoinRequest) -> JoinAttempt {
+        JoinAttempt {
+            request,
+            attempts: 0,
+            //goes off on the first tick so the first request goes out right away
+            retry: Timer::from_seconds(0., TimerMode::Once),
         }
     }
 }
 
-#[derive(Resource, Serialize, Deserialize)]
-pub struct Players {
-    pub player_array: [Player; 4],
+/// The room we're in and the token that gets our slot back if we drop out of it
+#[derive(Resource, Default)]
+pub struct Session {
+    pub room: String,
+    pub token: u64,
 }
 
-impl Players {
-    pub fn init() -> Players {
-        Players {
-            player_array: [
-                Player::default(),
-                Player::default(),
-                Player::default(),
-                Player::default(),
-            ],
-        }
-    }
-}
+/// Banner shown over the game while reconnecting
+#[derive(Component)]
+pub struct ConnectionBanner;
 
 #[derive(Resource, Serialize, Deserialize)]
-pub struct Projectiles {
-    pub list: Vec<Projectile>,
-}
-
-#[derive(Clone, Serialize, Deserialize)]
-pub struct Projectile {
-    pub owner_id: i32,
-    pub velocity: Velocity,
-    pub translation: Vec3,
-    pub lifetime: f32,
-}
-
-#[derive(Component, Clone, Serialize, Deserialize)]
-
-pub struct Velocity {
-    pub v: Vec2,
-}
-
-#[derive(Resource, Serialize, Deserialize)]
-pub struct Enemies {
-    pub list: Vec<Enemy>,
-}
-
-impl Enemies {
-    pub fn init() -> Enemies {
-        Enemies { list: Vec::new() }
-    }
-}
-
-#[derive(Serialize, Deserialize, Clone, Component)]
-pub struct Enemy {
-    pub id: i32,
-    pub etype: i32,
-    pub pos: Vec3,
-    pub animation_index: usize,
-    pub hp: f32,
-    pub alive: bool,
-    pub target_id: i32,
-}
-
-#[derive(Serialize, Deserialize, Clone)]
-pub struct Damage {
-    pub target_id: i32,
-    pub dmg: f32,
+pub struct HostPlayer {
+    pub player: Player,
 }
 
+/// The socket the client talks to the server over, along with the reliability
+/// state for that link
 #[derive(Resource)]
+#[allow(clippy::upper_case_acronyms)] //named after the server's
 pub struct UDP {
     pub socket: UdpSocket,
+    pub connection: Connection<ServerMessage>,
 }
 
+/// The server we joined and the tick length it told us to simulate at
 #[derive(Resource)]
 pub struct Server {
-    pub addr: String,
+    pub addr: SocketAddr,
+    pub tick_seconds: f32,
+}
+
+/// The socket, the server and who we are to it, for systems that talk to the
+/// server on top of everything else they need
+#[derive(SystemParam)]
+pub struct ServerLink<'w> {
+    pub udp: ResMut<'w, UDP>,
+    pub server: ResMut<'w, Server>,
+    pub host: ResMut<'w, HostPlayer>,
+}
+
+/// The newest server tick the client has heard about and when it arrived, used to
+/// work out what time it is on the server. heard_at is when anything at all
+/// last arrived, for noticing the connection has dropped
+#[derive(Resource, Default)]
+pub struct ServerClock {
+    pub tick: u32,
+    pub received_at: f32,
+    pub heard_at: f32,
+}
+
+impl ServerClock {
+    /// Server time (in seconds) that remote entities should be drawn at. This is
+    /// a little behind the newest snapshot so there's something to interpolate towards
+    pub fn render_time(&self, now: f32, tick_seconds: f32) -> f32 {
+        (self.tick as f32 - INTERPOLATION_TICKS) * tick_seconds + (now - self.received_at)
+    }
 }
+
+/// Fired for every message the server sends, in the order it should be handled
+#[derive(Event)]
+pub struct ServerEvent(pub ServerMessage);
@@ -5,7 +5,6 @@ use bevy::prelude::*;
 use bevy::window::PresentMode;
 use core::panic;
 use network::components::*;
-use rand::Rng;
 use serde::*;
 use std::net::*;
 use std::sync::{Arc, Mutex};
@@ -1,8 +1,63 @@
 use bevy::prelude::*;
+use bevy::time::common_conditions::on_timer;
+use protocol::messages::HEARTBEAT_INTERVAL;
+use protocol::ocean::OceanParams;
+use systems::*;
+
+use crate::components::GameworldState;
+
+use components::{ConnectionState, ServerClock, ServerEvent, Session};
 
 pub mod components;
 pub mod systems;
 
-use crate::components::GameState;
-use crate::{GameworldState, UDP};
-use systems::*;
+pub struct NetworkPlugin;
+impl Plugin for NetworkPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ServerEvent>()
+            .init_resource::<ServerClock>()
+            .init_resource::<Session>()
+            .init_state::<ConnectionState>()
+            .add_systems(PreUpdate, listen)
+            .add_systems(PostUpdate, resend_reliable)
+            .add_systems(
+                Update,
+                send_heartbeat.run_if(
+                    on_timer(HEARTBEAT_INTERVAL).and_then(
+                        in_state(ConnectionState::Syncing)
+                            .or_else(in_state(ConnectionState::Connected)),
+                    ),
+                ),
+            )
+            .add_systems(
+                Update,
+                //an answer or giving up wins over running out of tries
+                (send_join_request, handle_join_replies, cancel_join)
+                    .chain()
+                    .run_if(in_state(ConnectionState::Connecting)),
+            )
+            .add_systems(
+                Update,
+                finish_syncing.run_if(in_state(ConnectionState::Syncing)),
+            )
+            .add_systems(
+                Update,
+                (detect_drop, handle_kicked).run_if(
+                    in_state(ConnectionState::Syncing)
+                        .or_else(in_state(ConnectionState::Connected)),
+                ),
+            )
+            .add_systems(Update, handle_leave_success)
+            .add_systems(
+                Update,
+                reset_session.run_if(state_changed::<ConnectionState>),
+            )
+            .add_systems(
+                Update,
+                report_area.run_if(
+                    state_changed::<GameworldState>.and_then(resource_exists::<OceanParams>),
+                ),
+            )
+            .add_systems(Update, show_connection_banner);
+    }
+}
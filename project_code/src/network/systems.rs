@@ -1,19 +1,434 @@
+use bevy::prelude::*;
+use protocol::codec::{CodecError, MAX_PACKET_SIZE, PROTOCOL_VERSION};
+use protocol::components::Area;
+use protocol::delta::DeltaDecoder;
+use protocol::messages::{ClientMessage, RejectReason, ServerMessage};
+use protocol::ocean::OceanParams;
+use protocol::reliable::{Channel, Connection};
+use std::time::Instant;
+
+use crate::components::{CurrentIslandType, GameworldState};
 use crate::network::components::*;
 
-use bevy::prelude::*;
-use bevy::window::PresentMode;
-use core::panic;
-use rand::Rng;
-use serde::*;
-use std::net::*;
-use std::sync::{Arc, Mutex};
-
-pub fn create_env<T: Serialize>(message: String, object: T) -> String {
-    let packet: Packet<T> = Packet { payload: object };
-
-    serde_json::to_string(&Envelope {
-        message: message,
-        packet: serde_json::to_string(&packet).unwrap(),
-    })
-    .unwrap()
+/*   SEND FUNCTION   */
+/// Encodes a message and sends it to the server on the given channel, logging
+/// anything that goes wrong
+pub fn send(udp: &mut UDP, server: &Server, message: &ClientMessage, channel: Channel) {
+    let UDP { socket, connection } = udp;
+
+    if let Err(e) = connection.send(socket, server.addr, message, channel) {
+        warn!("Failed to send packet to [{}]: {}", server.addr, e);
+    }
+}
+
+/*   LISTEN FUNCTION   */
+/// Reads everything waiting on the socket and turns the server's messages into
+/// ServerEvents. Acks and duplicates are handled here and never reach the game
+pub fn listen(
+    mut udp: ResMut<UDP>,
+    server: Res<Server>,
+    mut clock: ResMut<ServerClock>,
+    time: Res<Time>,
+    mut events: EventWriter<ServerEvent>,
+) {
+    let UDP { socket, connection } = &mut *udp;
+    let mut buf = vec![0; MAX_PACKET_SIZE];
+
+    while let Ok((size, src)) = socket.recv_from(&mut buf) {
+        if src != server.addr {
+            continue;
+        }
+        clock.heard_at = time.elapsed_seconds();
+
+        match connection.receive(socket, src, &buf[..size]) {
+            Ok(messages) => {
+                for message in messages {
+                    //snapshots can arrive out of order, only newer ones move the clock
+                    if let ServerMessage::Snapshot(snapshot) = &message {
+                        if snapshot.tick > clock.tick {
+                            clock.tick = snapshot.tick;
+                            clock.received_at = time.elapsed_seconds();
+                        }
+                    }
+
+                    events.send(ServerEvent(message));
+                }
+            }
+            //nothing the server says can be read, but that much we can tell the player
+            Err(CodecError::VersionMismatch { version }) => {
+                events.send(ServerEvent(ServerMessage::JoinRejected(
+                    RejectReason::VersionMismatch {
+                        server: version,
+                        client: PROTOCOL_VERSION,
+                    },
+                )));
+            }
+            Err(e) => warn!("Recieved invalid packet from [{}]: {}", src.ip(), e),
+        }
+    }
+}
+
+/*   SEND_HEARTBEAT FUNCTION   */
+/// Lets the server know the client is still around. Runs every
+/// HEARTBEAT_INTERVAL so a crashed client's slot gets freed by the server's timeout
+pub fn send_heartbeat(mut udp: ResMut<UDP>, server: Res<Server>) {
+    send(&mut udp, &server, &ClientMessage::Heartbeat, Channel::Unreliable);
+}
+
+/*   RESEND_RELIABLE FUNCTION   */
+/// Resends reliable messages the server hasn't acked yet. If it stops acking
+/// them altogether detect_drop takes it from there
+pub fn resend_reliable(mut udp: ResMut<UDP>, server: Res<Server>) {
+    let UDP { socket, connection } = &mut *udp;
+
+    let dropped = connection.resend(socket, server.addr, Instant::now());
+    if dropped > 0 {
+        warn!(
+            "Gave up on the server with {} reliable packet(s) unacked",
+            dropped
+        );
+    }
+}
+
+/*   SEND_JOIN_REQUEST FUNCTION   */
+/// Sends the JoinAttempt's request whenever its timer goes off, waiting twice as
+/// long after every try. Gives up once MAX_JOIN_ATTEMPTS have gone unanswered
+pub fn send_join_request(
+    time: Res<Time>,
+    mut attempt: ResMut<JoinAttempt>,
+    mut udp: ResMut<UDP>,
+    server: Res<Server>,
+    host: Res<HostPlayer>,
+    mut next_state: ResMut<NextState<ConnectionState>>,
+) {
+    //ticking the timer isn't a change the menu has to show
+    let retry = &mut attempt.bypass_change_detection().retry;
+    retry.tick(time.delta());
+    if !retry.just_finished() {
+        return;
+    }
+
+    if attempt.attempts >= MAX_JOIN_ATTEMPTS {
+        warn!("No answer from server at {}, giving up", server.addr);
+        next_state.set(ConnectionState::Failed(format!(
+            "No answer from the server at {} after {} tries.",
+            server.addr, attempt.attempts
+        )));
+        return;
+    }
+
+    attempt.attempts += 1;
+    let delay = RETRY_FIRST_SECONDS * 2f32.powi(attempt.attempts as i32 - 1);
+    attempt.retry = Timer::from_seconds(delay.min(RETRY_MAX_SECONDS), TimerMode::Once);
+
+    let message = match &attempt.request {
+        JoinRequest::New(room) => ClientMessage::NewPlayer {
+            player: host.player.clone(),
+            room: room.clone(),
+        },
+        JoinRequest::Rejoin { room, id, token } => ClientMessage::Rejoin {
+            room: room.clone(),
+            id: *id,
+            token: *token,
+        },
+    };
+
+    info!(
+        "Trying to join world ({} of {})...",
+        attempt.attempts, MAX_JOIN_ATTEMPTS
+    );
+    send(&mut udp, &server, &message, Channel::Unreliable);
+}
+
+/*   HANDLE_JOIN_REPLIES FUNCTION   */
+/// Handles the server's answer to the JoinAttempt, moving on to Syncing once
+/// we're in a room or to Failed if the server won't have us
+pub fn handle_join_replies(
+    mut commands: Commands,
+    mut events: EventReader<ServerEvent>,
+    attempt: Res<JoinAttempt>,
+    mut next_state: ResMut<NextState<ConnectionState>>,
+    mut host: ResMut<HostPlayer>,
+    mut server: ResMut<Server>,
+    mut fixed_time: ResMut<Time<Fixed>>,
+) {
+    for ServerEvent(message) in events.read() {
+        match message {
+            ServerMessage::JoinedLobby {
+                id,
+                room,
+                ocean,
+                tick_rate,
+                token,
+            } => {
+                if let JoinRequest::Rejoin { .. } = attempt.request {
+                    info!("Rejoined room {} as player #{}", room, id);
+                } else {
+                    info!("Joined room {}! You are player #{}", room, id);
+
+                    // the server only sends the seed, the ocean gets built in setup_ocean
+                    debug!("Ocean seed: {}", ocean.seed);
+                    commands.insert_resource(*ocean);
+                }
+                host.player.id = *id;
+                //the server numbers enemy updates from scratch for us
+                commands.insert_resource(DeltaDecoder::default());
+
+                //predict at exactly the rate the server simulates at
+                server.tick_seconds = (1. / tick_rate) as f32;
+                fixed_time.set_timestep_hz(*tick_rate);
+
+                commands.insert_resource(Session {
+                    room: room.clone(),
+                    token: *token,
+                });
+                next_state.set(ConnectionState::Syncing);
+                return;
+            }
+            ServerMessage::JoinRejected(reason) => {
+                warn!("Could not join server: {}", reason);
+                next_state.set(ConnectionState::Failed(reason.to_string()));
+                return;
+            }
+            _ => {}
+        }
+    }
+}
+
+/*   FINISH_SYNCING FUNCTION   */
+/// We're caught up with the room once its first snapshot arrives
+pub fn finish_syncing(
+    mut events: EventReader<ServerEvent>,
+    mut next_state: ResMut<NextState<ConnectionState>>,
+) {
+    for ServerEvent(message) in events.read() {
+        if let ServerMessage::Snapshot(_) = message {
+            next_state.set(ConnectionState::Connected);
+        }
+    }
+}
+
+/*   HANDLE_LEAVE_SUCCESS FUNCTION   */
+/// The server forgets about our connection once we've left, so the next join
+/// starts counting packets from scratch on both sides
+pub fn handle_leave_success(mut events: EventReader<ServerEvent>, mut udp: ResMut<UDP>) {
+    for ServerEvent(message) in events.read() {
+        if let ServerMessage::LeaveSuccess = message {
+            udp.connection = Connection::new();
+            info!("Left lobby");
+        }
+    }
+}
+
+/*   HANDLE_KICKED FUNCTION   */
+/// The server took us out of the room, by an admin or because it's shutting
+/// down. Back to the main menu with the reason, there's no slot to rejoin
+pub fn handle_kicked(
+    mut events: EventReader<ServerEvent>,
+    mut udp: ResMut<UDP>,
+    mut next_state: ResMut<NextState<ConnectionState>>,
+) {
+    for ServerEvent(message) in events.read() {
+        if let ServerMessage::Kicked { reason } = message {
+            warn!("Removed from the room: {}", reason);
+            udp.connection = Connection::new();
+            next_state.set(ConnectionState::Failed(reason.clone()));
+        }
+    }
+}
+
+/*   DETECT_DROP FUNCTION   */
+/// Starts asking for our slot back when the server hasn't been heard from in
+/// SERVER_TIMEOUT_SECONDS, or has stopped acking what we send it. The server
+/// holds it for a while after dropping us
+pub fn detect_drop(
+    mut commands: Commands,
+    time: Res<Time>,
+    clock: Res<ServerClock>,
+    session: Res<Session>,
+    host: Res<HostPlayer>,
+    mut udp: ResMut<UDP>,
+    mut next_state: ResMut<NextState<ConnectionState>>,
+) {
+    let silent_for = time.elapsed_seconds() - clock.heard_at;
+    if silent_for >= SERVER_TIMEOUT_SECONDS {
+        warn!(
+            "Nothing heard from the server for {:.1}s, reconnecting",
+            silent_for
+        );
+    } else if udp.connection.gave_up() {
+        warn!("The server stopped acking our messages, reconnecting");
+    } else {
+        return;
+    }
+
+    //the server starts counting our packets from scratch when we rejoin
+    udp.connection = Connection::new();
+    commands.insert_resource(JoinAttempt::new(JoinRequest::Rejoin {
+        room: session.room.clone(),
+        id: host.player.id,
+        token: session.token,
+    }));
+    next_state.set(ConnectionState::Connecting);
+}
+
+/*   GIVE_UP FUNCTION   */
+/// Stops trying to join and goes back to Disconnected
+pub fn give_up(
+    udp: &mut UDP,
+    server: &Server,
+    host: &HostPlayer,
+    next_state: &mut NextState<ConnectionState>,
+) {
+    //in case the server let us in after all. There's no point resending it, the
+    //server drops us on its own once we go quiet
+    send(
+        udp,
+        server,
+        &ClientMessage::PlayerLeave(host.player.clone()),
+        Channel::Unreliable,
+    );
+    info!("Stopped trying to join");
+    next_state.set(ConnectionState::Disconnected);
+}
+
+/*   CANCEL_JOIN FUNCTION   */
+/// Escape gives up on joining, in the menu or in game while reconnecting
+pub fn cancel_join(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut udp: ResMut<UDP>,
+    server: Res<Server>,
+    host: Res<HostPlayer>,
+    mut next_state: ResMut<NextState<ConnectionState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        give_up(&mut udp, &server, &host, &mut next_state);
+    }
+}
+
+/*   RESET_SESSION FUNCTION   */
+/// Forgets the room once we're out of it, and goes back to the main menu if
+/// that happened in game so the error can be shown there
+pub fn reset_session(
+    state: Res<State<ConnectionState>>,
+    mut host: ResMut<HostPlayer>,
+    mut session: ResMut<Session>,
+    mut clock: ResMut<ServerClock>,
+    gameworld_state: Res<State<GameworldState>>,
+    mut next_gameworld_state: ResMut<NextState<GameworldState>>,
+) {
+    if !matches!(
+        state.get(),
+        ConnectionState::Disconnected | ConnectionState::Failed(_)
+    ) {
+        return;
+    }
+
+    host.player.id = -1;
+    host.player.area = Area::MainMenu;
+    *session = Session::default();
+    //the next room's ticks start over from 0
+    *clock = ServerClock::default();
+
+    if *gameworld_state.get() != GameworldState::MainMenu {
+        next_gameworld_state.set(GameworldState::MainMenu);
+    }
+}
+
+/*   REPORT_AREA FUNCTION   */
+/// Tells the server where we are whenever we set sail, land or go down into a
+/// dungeon, so it only sends us what's around us and the crew can see where we went
+pub fn report_area(
+    mut udp: ResMut<UDP>,
+    server: Res<Server>,
+    mut host: ResMut<HostPlayer>,
+    ocean: Res<OceanParams>,
+    current_island_type: Res<CurrentIslandType>,
+    gameworld_state: Res<State<GameworldState>>,
+) {
+    let island = current_island_type.island(&ocean);
+    let island_type = current_island_type.island_type;
+
+    let area = match gameworld_state.get() {
+        //reset_session takes care of this one, there's nobody left to tell
+        GameworldState::MainMenu => return,
+        GameworldState::Ocean => Area::Ocean,
+        GameworldState::Island => Area::Island {
+            island,
+            island_type,
+        },
+        GameworldState::Dungeon => Area::Dungeon {
+            island,
+            island_type,
+        },
+    };
+
+    host.player.area = area;
+    send(
+        &mut udp,
+        &server,
+        &ClientMessage::AreaChanged(area),
+        Channel::Reliable,
+    );
+}
+
+/*   SHOW_CONNECTION_BANNER FUNCTION   */
+/// Tells the player the connection dropped while they're in game, and how
+/// many tries are left to get it back
+pub fn show_connection_banner(
+    mut commands: Commands,
+    state: Res<State<ConnectionState>>,
+    gameworld_state: Res<State<GameworldState>>,
+    attempt: Option<Res<JoinAttempt>>,
+    banner_query: Query<Entity, With<ConnectionBanner>>,
+) {
+    let attempt_changed = attempt.as_ref().is_some_and(|attempt| attempt.is_changed());
+    if !state.is_changed() && !gameworld_state.is_changed() && !attempt_changed {
+        return;
+    }
+
+    for banner in banner_query.iter() {
+        commands.entity(banner).despawn_recursive();
+    }
+
+    //the main menu shows its own progress
+    if *state.get() != ConnectionState::Connecting
+        || *gameworld_state.get() == GameworldState::MainMenu
+    {
+        return;
+    }
+    let Some(attempt) = attempt else {
+        return;
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(20.0),
+                    width: Val::Percent(100.0),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            ConnectionBanner,
+        ))
+        .with_children(|parent| {
+            parent.spawn(
+                TextBundle::from_section(
+                    format!(
+                        "Connection lost, reconnecting ({} of {})... Esc to give up",
+                        attempt.attempts, MAX_JOIN_ATTEMPTS
+                    ),
+                    TextStyle {
+                        font_size: 24.0,
+                        color: Color::srgb(0.9, 0.3, 0.3),
+                        ..default()
+                    },
+                )
+                .with_background_color(Color::srgba(0.1, 0.1, 0.1, 0.85)),
+            );
+        });
 }
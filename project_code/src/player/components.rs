@@ -1,59 +1,47 @@
 use crate::{components::BoundingBox, shop::components::Inventory};
-use crate::network::components::HostPlayer;
 use bevy::prelude::*;
+use protocol::combat::Weapon;
 
 /// The speed at which the player accelerates
 pub const PLAYER_ACCELERATION: f32 = 5000.;
-pub const PLAYER_SPEED: f32 = 500.;
+//the server won't move a pirate any faster than this
+pub use protocol::simulation::PIRATE_SPEED as PLAYER_SPEED;
 pub const PLAYER_SIZE: f32 = 32.;
 pub const PLAYER_ANIMATION_TIME: f32 = 0.1;
 
 /// Musketball info
-pub const MUSKETBALL_SPEED: f32 = 500.;
-pub const MUSKETBALL_LIFETIME: f32 = 6.;
-pub const MAX_ACCEL: f32 = 800.;
+pub use protocol::combat::{MUSKETBALL_LIFETIME, MUSKETBALL_SPEED};
 
-pub const SWORD_COOLDOWN: f32 = 0.75;
-pub const MUSKET_COOLDOWN: f32 = 1.5;
-pub const DAGGER_COOLDOWN: f32 = 0.375; // Half of sword cooldown
-pub const PISTOL_COOLDOWN: f32 = 0.75;  // Half of musket cooldown
+//weapon cooldowns are shared with the server, which checks hits against them
+pub use protocol::combat::{DAGGER_COOLDOWN, MUSKET_COOLDOWN, PISTOL_COOLDOWN, SWORD_COOLDOWN};
 
 // Base player stats
 pub const PLAYER_MAX_HP: f32 = 3.;
 
-#[derive(Component)]
-pub struct PlayerVisibility {
-    pub visible: bool
-}
-
-
-#[derive(Component)]
-pub struct DespawnWeapon {
-    pub should_despawn: bool,
-}
-
-impl Default for DespawnWeapon {
-    fn default() -> Self {
-        Self {
-            should_despawn: false,
-        }
-    }
-}
-
 /// Struct representing the player
 #[derive(Component)]
 pub struct Player {
-    pub host_id: i32,
     pub animation_state: SpriteState,
     pub timer: Timer,
     pub health: f32,
     pub max_health: f32,
     pub inventory: Inventory,
-    pub spawn_position: Vec3,
     pub weapon: i8,
     pub aabb: BoundingBox,
 }
 
+impl Player {
+    /// The weapon the pirate has in hand, as the server knows it
+    pub fn held_weapon(&self) -> Weapon {
+        match self.weapon {
+            1 => Weapon::Dagger,
+            2 => Weapon::Musket,
+            3 => Weapon::Pistol,
+            _ => Weapon::Sword,
+        }
+    }
+}
+
 /// Struct representing the musketball projectile fired by the musket weapon
 #[derive(Component)]
 pub struct Musketball;
@@ -111,130 +99,18 @@ impl Sword {
     }
 }
 
-/// Struct representing the dagger weapon for the player
+/// Struct representing the dagger weapon for the player, its damage is
+/// protocol::combat::Weapon::Dagger's
 #[derive(Component)]
-pub struct Dagger {
-    pub damage: f32,
-    pub upgraded: bool,
-}
-
-//implementing dagger
-impl Default for Dagger {
-    fn default() -> Dagger {
-        Dagger {
-            damage: 0.5,     // Half of sword's base damage
-            upgraded: false,
-        }
-    }
-}
-
-// Dagger Damage and Upgrade Mechanics
-impl Dagger {
-    pub fn get_base_damage(&self) -> f32 {
-        if self.upgraded {
-            1.0 // Base damage for upgraded dagger (half of sword)
-        } else {
-            0.5 // Base damage for basic dagger
-        }
-    }
-
-    pub fn get_level_multiplier(level: u32) -> f32 {
-        // Each level adds 25% more damage (same as sword)
-        1.0 + (level as f32 * 0.25)
-    }
-
-    pub fn calculate_damage(&self, level: u32) -> f32 {
-        self.get_base_damage() * Self::get_level_multiplier(level)
-    }
-
-    pub fn upgrade_dagger(&mut self, level: u32) {
-        self.damage = self.calculate_damage(level);
-        self.upgraded = true;
-    }
-}
+pub struct Dagger;
 
 /// Struct representing the musket weapon for the player
 #[derive(Component)]
-pub struct Musket {
-    pub damage: f32,
-    pub upgraded: bool,
-}
-
-impl Default for Musket {
-    fn default() -> Musket {
-        Musket {
-            damage: 1.0,     // Base musket damage
-            upgraded: false,
-        }
-    }
-}
-
-// Musket Damage and Upgrade Mechanics
-impl Musket {
-    pub fn get_base_damage(&self) -> f32 {
-        if self.upgraded {
-            2.0 // Base damage for upgraded musket
-        } else {
-            1.0 // Base damage for basic musket
-        }
-    }
-
-    pub fn get_level_multiplier(level: u32) -> f32 {
-        // Each level adds 25% more damage
-        1.0 + (level as f32 * 0.25)
-    }
-
-    pub fn calculate_damage(&self, level: u32) -> f32 {
-        self.get_base_damage() * Self::get_level_multiplier(level)
-    }
-
-    pub fn upgrade_musket(&mut self, level: u32) {
-        self.damage = self.calculate_damage(level);
-        self.upgraded = true;
-    }
-}
+pub struct Musket;
 
 /// Struct representing the pistol weapon for the player
 #[derive(Component)]
-pub struct Pistol {
-    pub damage: f32,
-    pub upgraded: bool,
-}
-
-//implementing pistol
-impl Default for Pistol {
-    fn default() -> Pistol {
-        Pistol {
-            damage: 0.5,     // Half of musket's base damage
-            upgraded: false,
-        }
-    }
-}
-
-// Pistol Damage and Upgrade Mechanics
-impl Pistol {
-    pub fn get_base_damage(&self) -> f32 {
-        if self.upgraded {
-            1.0 // Base damage for upgraded pistol (half of musket)
-        } else {
-            0.5 // Base damage for basic pistol
-        }
-    }
-
-    pub fn get_level_multiplier(level: u32) -> f32 {
-        // Each level adds 25% more damage (same as musket)
-        1.0 + (level as f32 * 0.25)
-    }
-
-    pub fn calculate_damage(&self, level: u32) -> f32 {
-        self.get_base_damage() * Self::get_level_multiplier(level)
-    }
-
-    pub fn upgrade_pistol(&mut self, level: u32) {
-        self.damage = self.calculate_damage(level);
-        self.upgraded = true;
-    }
-}
+pub struct Pistol;
 
 /// Velocity struct
 #[derive(Component)]
@@ -242,11 +118,6 @@ pub struct Velocity {
     pub v: Vec2,
 }
 
-/// Struct for tracking players last position for out of transition spawning
-pub struct PlayerLastPosition {
-    pub last_pos: Vec2,
-}
-
 /// Velocity implementation
 impl Velocity {
     pub fn new() -> Self {
@@ -272,15 +143,6 @@ impl AnimationTimer {
     }
 }
 
-#[derive(Component, Deref, DerefMut)]
-pub struct TestTimer(Timer);
-
-impl TestTimer {
-    pub fn new(timer: Timer) -> TestTimer {
-        TestTimer(timer)
-    }
-}
-
 /// Struct for the count of frames in the players animation
 #[derive(Component, Deref, DerefMut)]
 pub struct AnimationFrameCount(usize);
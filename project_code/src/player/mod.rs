@@ -32,9 +32,12 @@ impl Plugin for PlayerPlugin {
                 move_musketball,
                 move_weapon.after(move_player),
                 swap_weapon,
+                report_weapon.after(swap_weapon),
+                send_player_update.after(move_player),
                 )
                 .run_if(in_state(GameworldState::Island).or_else(in_state(GameworldState::Dungeon)))
                 .run_if(in_state(GameState::Running)))
+            .add_systems(Update, receive_gold)
             .add_systems(OnExit(GameworldState::Island), (
                 despawn_player,
                 despawn_musketballs,))
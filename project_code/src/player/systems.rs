@@ -1,19 +1,20 @@
-use std::ops::Bound;
-
-use crate::components::*;
-use crate::components::{BoundingBox, GameworldState, SpawnLocations};
+use crate::components::{BoundingBox, GameworldState};
 use crate::controls::*;
 use crate::data::gameworld_data::*;
 use crate::enemies::*;
 use crate::hitbox_system::*;
-use crate::network::components::HostPlayer;
+use crate::chat::components::ChatLog;
+use crate::network::components::{HostPlayer, Server, ServerEvent, UDP};
+use crate::network::systems::send;
 use crate::player::components::*;
 
-use crate::shop::components::{Inventory, Item, ItemType};
-use crate::shop::systems::generate_loot_item;
+use crate::shop::components::{Inventory, ItemType};
 
-use bevy::input::mouse::{self, MouseButtonInput};
 use bevy::prelude::*;
+use protocol::combat::Weapon;
+use protocol::components::Player as NetPlayer;
+use protocol::messages::{ClientMessage, ServerMessage};
+use protocol::reliable::Channel;
 
 /*   MOVE_PLAYER FUNCTION */
 /// Moves the player, updating its position depending on
@@ -166,10 +167,9 @@ pub fn initial_spawn_player(
         return;
     }
 
-    // Only spawn new player if player count < 4
-
+    // Only spawn once the server has given us an id, how many players fit is up to the server
     let player_id = host.player.id;
-    if player_id >= 4 {
+    if player_id < 0 {
         return;
     }
 
@@ -214,13 +214,11 @@ pub fn initial_spawn_player(
             remaining: Timer::from_seconds(0.75, TimerMode::Once),
         },
         Player {
-            host_id: player_id,
             animation_state: SpriteState::Idle,
             timer: Timer::from_seconds(SpriteState::Idle.animation_speed(), TimerMode::Repeating),
             health: PLAYER_MAX_HP,
             max_health: PLAYER_MAX_HP,
             inventory,
-            spawn_position,
             weapon: 0,
             aabb: BoundingBox::new(
                 Vec2::new(spawn_position.x, spawn_position.y),
@@ -298,7 +296,6 @@ pub fn swap_weapon(
     weapon_query: Query<Entity>,
 ) {
     let mut player = player_query.single_mut();
-    let player_entity = player_entity_query.single();
 
     if keyboard_input.just_pressed(KeyCode::Digit1) {
         // Switch to sword
@@ -356,14 +353,6 @@ pub fn swap_weapon(
             player.weapon = 1;
             println!("Switched to dagger!");
 
-            let dagger_level = player
-                .inventory
-                .items
-                .iter()
-                .find(|item| item.item_type == ItemType::Dagger)
-                .map(|item| item.level)
-                .unwrap_or(0);
-
             let master_handle: Handle<Image> = asset_server.load("s_dagger.png");
             let master_layout =
                 TextureAtlasLayout::from_grid(UVec2::splat(TILE_SIZE), 8, 5, None, None);
@@ -385,10 +374,7 @@ pub fn swap_weapon(
                         layout: master_layout_handle,
                         index: 0,
                     },
-                    Dagger {
-                        damage: Dagger::default().calculate_damage(dagger_level),
-                        upgraded: dagger_level > 0,
-                    },
+                    Dagger,
                 ));
             });
         }
@@ -402,17 +388,7 @@ pub fn swap_weapon(
             player.weapon = 2;
             println!("Switched to musket!");
 
-            let musket_level = player
-                .inventory
-                .items
-                .iter()
-                .find(|item| item.item_type == ItemType::Musket)
-                .map(|item| item.level)
-                .unwrap_or(0);
             let master_handle: Handle<Image> = asset_server.load("s_musket.png");
-            let master_layout =
-                TextureAtlasLayout::from_grid(UVec2::splat(TILE_SIZE), 8, 5, None, None);
-            let master_layout_handle = texture_atlases.add(master_layout);
 
             let player = player_entity_query.single();
             commands.entity(player).with_children(|parent| {
@@ -426,10 +402,7 @@ pub fn swap_weapon(
                         },
                         ..default()
                     },
-                    Musket {
-                        damage: Musket::default().calculate_damage(musket_level),
-                        upgraded: musket_level > 0,
-                    },
+                    Musket,
                 ));
             });
         }
@@ -443,18 +416,7 @@ pub fn swap_weapon(
             player.weapon = 3;
             println!("Switched to pistol!");
 
-            let pistol_level = player
-                .inventory
-                .items
-                .iter()
-                .find(|item| item.item_type == ItemType::Pistol)
-                .map(|item| item.level)
-                .unwrap_or(0);
-
             let master_handle: Handle<Image> = asset_server.load("s_pistol.png");
-            let master_layout =
-                TextureAtlasLayout::from_grid(UVec2::splat(TILE_SIZE), 8, 5, None, None);
-            let master_layout_handle = texture_atlases.add(master_layout);
 
             let player = player_entity_query.single();
             commands.entity(player).with_children(|parent| {
@@ -468,10 +430,7 @@ pub fn swap_weapon(
                         },
                         ..default()
                     },
-                    Pistol {
-                        damage: Pistol::default().calculate_damage(pistol_level),
-                        upgraded: pistol_level > 0,
-                    },
+                    Pistol,
                 ));
             });
         }
@@ -534,7 +493,7 @@ pub fn sword_attack(
         With<Player>,
     >,
 ) {
-    for (entity, transform, velocity, mut cooldown, player) in player_query.iter_mut() {
+    for (entity, transform, _velocity, mut cooldown, player) in player_query.iter_mut() {
         //If the cooldown is not finished, tick and break because you can't attack anyway
         if !cooldown.remaining.finished() {
             cooldown.remaining.tick(time.delta());
@@ -548,7 +507,7 @@ pub fn sword_attack(
                 println!("Player attacked!");
                 let mouse_pos = curr_mouse_pos.0;
                 println!("Mouse world coords {} {}", mouse_pos.x, mouse_pos.y);
-                cooldown.remaining = Timer::from_seconds(0.75, TimerMode::Once);
+                cooldown.remaining = Timer::from_seconds(SWORD_COOLDOWN, TimerMode::Once);
 
                 // Player position
                 let player_position = transform.translation.truncate();
@@ -571,7 +530,7 @@ pub fn sword_attack(
                     PLAYER,
                     false,
                     false,
-                    2.,
+                    Weapon::Sword.damage(),
                 );
             }
         }
@@ -589,34 +548,34 @@ pub fn dagger_attack(
         With<Player>,
     >,
 ) {
-    for (entity, transform, velocity, mut cooldown, player) in player_query.iter_mut() {
+    for (entity, transform, _velocity, mut cooldown, player) in player_query.iter_mut() {
         if !cooldown.remaining.finished() {
             cooldown.remaining.tick(time.delta());
             break;
         }
 
-        if player.weapon == 1 {
-            if get_player_input(PlayerControl::Attack, &keyboard_input, &mouse_input) == 1. {
-                println!("Player attacked with dagger!");
-                cooldown.remaining = Timer::from_seconds(DAGGER_COOLDOWN, TimerMode::Once);
-
-                let player_position = transform.translation.truncate();
-                let direction = (curr_mouse_pos.0 - player_position).normalize();
-                let hitbox_offset = direction * 25.0; // Shorter range than sword
-                let hitbox_size = Vec2::new(30.0, 45.0); // Smaller hitbox than sword
-
-                create_hitbox(
-                    &mut commands,
-                    entity,
-                    hitbox_size,
-                    hitbox_offset,
-                    Some(0.1),
-                    PLAYER,
-                    false,
-                    false,
-                    2.,
-                );
-            }
+        if player.weapon == 1
+            && get_player_input(PlayerControl::Attack, &keyboard_input, &mouse_input) == 1.
+        {
+            println!("Player attacked with dagger!");
+            cooldown.remaining = Timer::from_seconds(DAGGER_COOLDOWN, TimerMode::Once);
+
+            let player_position = transform.translation.truncate();
+            let direction = (curr_mouse_pos.0 - player_position).normalize();
+            let hitbox_offset = direction * 25.0; // Shorter range than sword
+            let hitbox_size = Vec2::new(30.0, 45.0); // Smaller hitbox than sword
+
+            create_hitbox(
+                &mut commands,
+                entity,
+                hitbox_size,
+                hitbox_offset,
+                Some(0.1),
+                PLAYER,
+                false,
+                false,
+                Weapon::Dagger.damage(),
+            );
         }
     }
 }
@@ -640,7 +599,7 @@ pub fn musket_attack(
     >,
     asset_server: Res<AssetServer>,
 ) {
-    for (entity, transform, velocity, mut cooldown, player) in player_query.iter_mut() {
+    for (_entity, transform, _velocity, mut cooldown, player) in player_query.iter_mut() {
         //If the cooldown is not finished, tick and break because you can't attack anyway
         if !cooldown.remaining.finished() {
             cooldown.remaining.tick(time.delta());
@@ -653,11 +612,9 @@ pub fn musket_attack(
                 println!("Player attacked!");
                 let mouse_pos = curr_mouse_pos.0;
                 println!("Mouse world coords {} {}", mouse_pos.x, mouse_pos.y);
-                cooldown.remaining = Timer::from_seconds(0.75, TimerMode::Once);
+                cooldown.remaining = Timer::from_seconds(MUSKET_COOLDOWN, TimerMode::Once);
 
                 // Player position
-                let player_position = transform.translation.truncate();
-
                 //getting angle to fire at
                 let pos2 = curr_mouse_pos.0;
                 let original_direction =
@@ -693,12 +650,12 @@ pub fn musket_attack(
                     },
                     Hitbox {
                         size: hitbox_size,
-                        offset: offset,
+                        offset,
                         lifetime: Some(Timer::from_seconds(MUSKETBALL_LIFETIME, TimerMode::Once)),
                         entity: PLAYER,
                         projectile: true,
                         enemy: false,
-                        dmg: 2.,
+                        dmg: Weapon::Musket.damage(),
                     },
                 ));
             }
@@ -718,54 +675,54 @@ pub fn pistol_attack(
     >,
     asset_server: Res<AssetServer>,
 ) {
-    for (entity, transform, velocity, mut cooldown, player) in player_query.iter_mut() {
+    for (_entity, transform, _velocity, mut cooldown, player) in player_query.iter_mut() {
         if !cooldown.remaining.finished() {
             cooldown.remaining.tick(time.delta());
             break;
         }
 
-        if player.weapon == 3 {
-            if get_player_input(PlayerControl::Attack, &keyboard_input, &mouse_input) == 1. {
-                println!("Player attacked with pistol!");
-                cooldown.remaining = Timer::from_seconds(PISTOL_COOLDOWN, TimerMode::Once);
+        if player.weapon == 3
+            && get_player_input(PlayerControl::Attack, &keyboard_input, &mouse_input) == 1.
+        {
+            println!("Player attacked with pistol!");
+            cooldown.remaining = Timer::from_seconds(PISTOL_COOLDOWN, TimerMode::Once);
 
-                let player_position = transform.translation;
-                let original_direction = (Vec3::new(curr_mouse_pos.0.x, curr_mouse_pos.0.y, 0.)
-                    - transform.translation)
-                    .normalize();
-                let angle = original_direction.x.atan2(original_direction.y);
-                let firing_angle = Vec3::new(angle.sin(), angle.cos(), 0.0);
-                let projectile_start_position = player_position + firing_angle * 10.0;
+            let player_position = transform.translation;
+            let original_direction = (Vec3::new(curr_mouse_pos.0.x, curr_mouse_pos.0.y, 0.)
+                - transform.translation)
+                .normalize();
+            let angle = original_direction.x.atan2(original_direction.y);
+            let firing_angle = Vec3::new(angle.sin(), angle.cos(), 0.0);
+            let projectile_start_position = player_position + firing_angle * 10.0;
 
-                let hitbox_size = Vec2::new(12., 12.);
-                let offset = Vec2::splat(0.);
+            let hitbox_size = Vec2::new(12., 12.);
+            let offset = Vec2::splat(0.);
 
-                commands.spawn((
-                    SpriteBundle {
-                        texture: asset_server.load("s_cannonball.png"),
-                        transform: Transform {
-                            translation: projectile_start_position,
-                            scale: Vec3::splat(0.6),
-                            ..default()
-                        },
+            commands.spawn((
+                SpriteBundle {
+                    texture: asset_server.load("s_cannonball.png"),
+                    transform: Transform {
+                        translation: projectile_start_position,
+                        scale: Vec3::splat(0.6),
                         ..default()
                     },
-                    Musketball,
-                    MusketballLifetime(MUSKETBALL_LIFETIME),
-                    MusketballVelocity {
-                        v: firing_angle * (MUSKETBALL_SPEED), // Slightly slower than musket
-                    },
-                    Hitbox {
-                        size: hitbox_size,
-                        offset: offset,
-                        lifetime: Some(Timer::from_seconds(MUSKETBALL_LIFETIME, TimerMode::Once)),
-                        entity: PLAYER,
-                        projectile: true,
-                        enemy: false,
-                        dmg: 2.,
-                    },
-                ));
-            }
+                    ..default()
+                },
+                Musketball,
+                MusketballLifetime(MUSKETBALL_LIFETIME),
+                MusketballVelocity {
+                    v: firing_angle * (MUSKETBALL_SPEED), // Slightly slower than musket
+                },
+                Hitbox {
+                    size: hitbox_size,
+                    offset,
+                    lifetime: Some(Timer::from_seconds(MUSKETBALL_LIFETIME, TimerMode::Once)),
+                    entity: PLAYER,
+                    projectile: true,
+                    enemy: false,
+                    dmg: Weapon::Pistol.damage(),
+                },
+            ));
         }
     }
 }
@@ -789,7 +746,7 @@ pub fn check_player_health(
     mut player_query: Query<(&mut Player, Entity, &mut Hurtbox, &mut Transform), With<Player>>,
     gameworld_state: Res<State<GameworldState>>,
 ) {
-    for (mut player, entity, mut hurtbox, mut transform) in player_query.iter_mut() {
+    for (mut player, _entity, mut hurtbox, mut transform) in player_query.iter_mut() {
         if !hurtbox.colliding.is {
             continue;
         }
@@ -863,3 +820,77 @@ pub fn despawn_weapon_internal(
         }
     }
 }
+
+/*   REPORT_WEAPON FUNCTION   */
+/// Tells the server whenever the pirate has another weapon in hand, it checks
+/// every hit against that one. A freshly spawned pirate holds a sword again
+pub fn report_weapon(
+    player_query: Query<&Player, Changed<Player>>,
+    mut sent: Local<Option<Weapon>>,
+    mut udp: ResMut<UDP>,
+    server: Res<Server>,
+) {
+    let Ok(player) = player_query.get_single() else {
+        return;
+    };
+
+    let weapon = player.held_weapon();
+    if *sent == Some(weapon) {
+        return;
+    }
+    *sent = Some(weapon);
+
+    send(
+        &mut udp,
+        &server,
+        &ClientMessage::EquipWeapon(weapon),
+        Channel::Reliable,
+    );
+}
+
+/*   SEND_PLAYER_UPDATE FUNCTION   */
+/// Tells the server where the pirate is so the rest of the lobby can see it
+pub fn send_player_update(
+    player_query: Query<&Transform, With<Player>>,
+    host: Res<HostPlayer>,
+    mut udp: ResMut<UDP>,
+    server: Res<Server>,
+) {
+    let Ok(transform) = player_query.get_single() else {
+        return;
+    };
+
+    let pirate = NetPlayer {
+        pos: transform.translation,
+        rot: transform.rotation,
+        boat: false,
+        used: true,
+        ..host.player.clone()
+    };
+
+    send(
+        &mut udp,
+        &server,
+        &ClientMessage::PlayerUpdate(pirate),
+        Channel::Unreliable,
+    );
+}
+
+/*   RECEIVE_GOLD FUNCTION   */
+/// Adds gold handed out by the server admin to the pirate's purse
+pub fn receive_gold(
+    mut events: EventReader<ServerEvent>,
+    mut player_query: Query<&mut Player>,
+    mut chat_log: ResMut<ChatLog>,
+) {
+    for ServerEvent(message) in events.read() {
+        let ServerMessage::GoldGiven { amount } = message else {
+            continue;
+        };
+
+        for mut player in player_query.iter_mut() {
+            player.inventory.money = player.inventory.money.saturating_add(*amount);
+        }
+        chat_log.notice(format!("The server gave you {} gold", amount));
+    }
+}
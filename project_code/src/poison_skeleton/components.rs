@@ -2,7 +2,6 @@ use bevy::prelude::*;
 
 //constants
 pub const PSKELETON_ANIMATION_TIME: f32 = 0.4;
-pub const PSKELETON_PROJECTILE_LIFETIME: f32 = 2.;
 pub const PSKELETON_PROJECTILE_SPEED: f32 = 300.;
 
 //SKELETON base stats
@@ -17,13 +16,10 @@ pub const PSKELETON_AGRO_RANGE: f32 = 600.;
 pub struct PoisonSkeleton {
     pub rotation_speed: f32,
     pub current_hp: f32,
-    pub max_hp: f32,
 }
 
 #[derive(Component)]
-pub struct PSkeletonProjectile {
-    pub timer: Timer,
-}
+pub struct PSkeletonProjectile;
 
 #[derive(Component)]
-pub struct Lifetime(pub f32);
+pub struct Lifetime;
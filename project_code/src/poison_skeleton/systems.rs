@@ -1,5 +1,4 @@
 use bevy::prelude::*;
-use bevy::render::texture;
 use bevy::sprite::TextureAtlas;
 
 use crate::data::gameworld_data::*;
@@ -56,24 +55,8 @@ pub fn rotate_pskeleton(
     }
 }
 
-/*   ANIMATE_SKELETON FUNCTION   */
-/// Animates a skeleton entity
-pub fn animate_pskeleton(
-    time: Res<Time>,
-    mut query: Query<(&mut AnimationTimer, &mut TextureAtlas, &AnimationFrameCount)>,
-) {
-    for (mut timer, mut texture_atlas, frame_count) in &mut query {
-        timer.tick(time.delta());
-
-        if timer.finished() {
-            texture_atlas.index = (texture_atlas.index + 1) % **frame_count;
-        }
-    }
-}
-
 /*  SPAWN_SKELETON FUNCTION  */
 /// Spawns a skeleton entity in the gameworld
-
 pub fn spawn_pskeleton(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
@@ -132,6 +115,7 @@ pub fn despawn_all_pskeletons(mut commands: Commands, query: Query<Entity, With<
 /// Things not added:
 /// * Attack cooldown timer
 /// * Projectile shooting
+///
 /// Things currently added:
 /// * Distance-to-player checking
 /// * Attack cooldown timer
@@ -194,14 +178,9 @@ pub fn pskeleton_attack(
                 },
                 ..default()
             },
-            TextureAtlas {
-                layout: layout,
-                index: 0,
-            },
-            PSkeletonProjectile {
-                timer: Timer::from_seconds(0.2, TimerMode::Once),
-            },
-            PoisonSkeletonLifetime(PSKELETON_PROJECTILE_LIFETIME),
+            TextureAtlas { layout, index: 0 },
+            PSkeletonProjectile,
+            PoisonSkeletonLifetime,
             Velocity {
                 v: angle_direction.truncate() * PSKELETON_PROJECTILE_SPEED, // (direction * speed of projectile)
             },
@@ -218,39 +197,6 @@ pub fn pskeleton_attack(
         ));
     }
 }
-/*   MOVE_SKELETON_PROJECTILE FUNCTION   */
-/// Updates the locations of skeleton projectiles
-/// Things to add:
-/// * Collision handling, dealing damage on collision
-pub fn move_pskeleton_projectile(
-    mut proj_query: Query<(
-        &mut Transform,
-        &mut Velocity,
-        &mut TextureAtlas,
-        &mut PSkeletonProjectile,
-    )>,
-    time: Res<Time>,
-) {
-    for (mut transform, velocity, mut texture_atlas, mut skeleton) in proj_query.iter_mut() {
-        // Calculates/moves the projectile
-        transform.translation += velocity.to_vec3(0.) * time.delta_seconds();
-
-        if !skeleton.timer.finished() {
-            skeleton.timer.tick(time.delta());
-            continue;
-        }
-
-        skeleton.timer = Timer::from_seconds(0.2, TimerMode::Once);
-
-        // Animates projectile
-
-        if texture_atlas.index < 7 {
-            texture_atlas.index += 1;
-        } else {
-            texture_atlas.index = 0;
-        };
-    }
-}
 
 pub fn pmove_skeleton(
     time: Res<Time>,
@@ -287,9 +233,9 @@ pub fn pmove_skeleton(
 /// Despawns all the skeleton's projectiles
 pub fn despawn_all_pskeleton_proj(
     mut commands: Commands,
-    mut proj_query: Query<(Entity, &mut PoisonSkeletonLifetime)>,
+    proj_query: Query<Entity, With<PoisonSkeletonLifetime>>,
 ) {
-    for (entity, mut lifetime) in proj_query.iter_mut() {
+    for entity in proj_query.iter() {
         commands.entity(entity).despawn();
     }
 }
@@ -0,0 +1,25 @@
+mod systems;
+
+use bevy::prelude::*;
+use systems::*;
+
+use crate::components::GameworldState;
+use crate::GameState;
+
+/// Boat combat between players, when the server has PvP turned on. The host's
+/// cannonballs can hit the boats of players the room's rules make hostile, and
+/// the server decides how much damage sticks and who gets paid for a sinking
+pub struct PvpPlugin;
+
+impl Plugin for PvpPlugin {
+    /// Builds the pvp plugin
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (mark_pvp_targets, report_boat_hits)
+                .run_if(in_state(GameworldState::Ocean))
+                .run_if(in_state(GameState::Running)),
+        )
+        .add_systems(Update, (take_boat_damage, announce_sinkings));
+    }
+}
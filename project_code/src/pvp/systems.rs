@@ -0,0 +1,152 @@
+use bevy::prelude::*;
+use protocol::combat::Weapon;
+use protocol::components::Damage;
+use protocol::messages::{ClientMessage, ServerMessage};
+use protocol::reliable::Channel;
+use protocol::simulation::BoatState;
+
+use crate::boat::components::*;
+use crate::boat::systems::sink_boat;
+use crate::chat::components::ChatLog;
+use crate::enemies::BOAT;
+use crate::hitbox_system::{create_hurtbox, Hurtbox};
+use crate::menu::components::Lobby;
+use crate::network::components::{HostPlayer, Server, ServerEvent, UDP};
+use crate::network::systems::send;
+use crate::player::components::Player;
+use crate::remote_player::components::RemotePlayer;
+
+/*   MARK_PVP_TARGETS FUNCTION   */
+/// Gives the boats of players the host is allowed to fire on a hurtbox, and
+/// takes it away from everyone else. As far as the hitbox system is concerned
+/// they're enemies, so only the host's own cannonballs hit them
+pub fn mark_pvp_targets(
+    mut commands: Commands,
+    lobby: Res<Lobby>,
+    host: Res<HostPlayer>,
+    query: Query<(Entity, &RemotePlayer, Has<Hurtbox>)>,
+) {
+    for (entity, remote, marked) in query.iter() {
+        let hostile = remote.boat && lobby.roster.hostile(host.player.id, remote.id);
+
+        if hostile && !marked {
+            create_hurtbox(
+                &mut commands,
+                entity,
+                BOAT_HURTBOX_SIZE,
+                Vec2::ZERO,
+                BOAT,
+                true,
+            );
+        } else if !hostile && marked {
+            commands.entity(entity).remove::<Hurtbox>();
+        }
+    }
+}
+
+/*   REPORT_BOAT_HITS FUNCTION   */
+/// Tells the server whenever the host hits another player's boat. Nothing
+/// happens to the boat until the server says so
+pub fn report_boat_hits(
+    mut udp: ResMut<UDP>,
+    server: Res<Server>,
+    mut query: Query<(&RemotePlayer, &mut Hurtbox)>,
+) {
+    for (remote, mut hurtbox) in query.iter_mut() {
+        if !hurtbox.colliding.is {
+            continue;
+        }
+
+        send(
+            &mut udp,
+            &server,
+            &ClientMessage::PlayerDamaged(Damage {
+                target_id: remote.id,
+                dmg: hurtbox.colliding.dmg,
+                weapon: Weapon::Cannon,
+            }),
+            Channel::Reliable,
+        );
+
+        hurtbox.colliding.dmg = 0.;
+        hurtbox.colliding.is = false;
+    }
+}
+
+/*   TAKE_BOAT_DAMAGE FUNCTION   */
+/// Takes another player's hit off the host's boat, on top of whatever enemies
+/// have done to it. A sunk boat is put back in the water at the spawn and starts
+/// over on the server, the same way setting sail from an island does
+pub fn take_boat_damage(
+    mut events: EventReader<ServerEvent>,
+    host: Res<HostPlayer>,
+    mut query: Query<(&mut Boat, &mut BoatPrediction)>,
+    mut udp: ResMut<UDP>,
+    server: Res<Server>,
+) {
+    for ServerEvent(message) in events.read() {
+        match message {
+            ServerMessage::BoatDamaged { id, by, dmg, hp } if *id == host.player.id => {
+                for (mut boat, mut prediction) in query.iter_mut() {
+                    //enemies and storms wear the boat down too, and only we count those
+                    boat.health -= dmg;
+                    info!(
+                        "Ouch! Boat was hit by player #{}... HP: {}",
+                        by, boat.health
+                    );
+
+                    //when their hits alone sank it, boat_sunk is on its way instead
+                    if boat.health <= 0. && *hp > 0. {
+                        sink_boat(&mut boat, &mut prediction, &mut udp, &server);
+                        info!("Boat sunk... back to port!");
+                    }
+                }
+            }
+            ServerMessage::BoatSunk(sinking) if sinking.id == host.player.id => {
+                for (mut boat, mut prediction) in query.iter_mut() {
+                    boat.health = boat.max_health;
+                    *prediction = BoatPrediction::new(BoatState::spawn());
+
+                    //the server took the sunk boat out already
+                    send(
+                        &mut udp,
+                        &server,
+                        &ClientMessage::BoatSpawned,
+                        Channel::Reliable,
+                    );
+                    info!("Boat sunk by player #{}... back to port!", sinking.by);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/*   ANNOUNCE_SINKINGS FUNCTION   */
+/// Pays the host the bounty on every boat they sink, and tells the room who
+/// sank who through the chat
+pub fn announce_sinkings(
+    mut events: EventReader<ServerEvent>,
+    host: Res<HostPlayer>,
+    mut player_query: Query<&mut Player>,
+    mut chat_log: ResMut<ChatLog>,
+) {
+    for ServerEvent(message) in events.read() {
+        let ServerMessage::BoatSunk(sinking) = message else {
+            continue;
+        };
+
+        let notice = if sinking.by == host.player.id {
+            for mut player in player_query.iter_mut() {
+                player.inventory.money = player.inventory.money.saturating_add(sinking.bounty);
+            }
+            format!("You sank {}'s boat! +{} gold", sinking.name, sinking.bounty)
+        } else if sinking.id == host.player.id {
+            format!("{} sank your boat", sinking.by_name)
+        } else {
+            format!("{} sank {}'s boat", sinking.by_name, sinking.name)
+        };
+
+        chat_log.notice(notice);
+    }
+}
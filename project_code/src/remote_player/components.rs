@@ -0,0 +1,73 @@
+use bevy::prelude::*;
+use protocol::components::Area;
+use std::collections::VecDeque;
+
+//how many snapshots are kept per remote player, a little over a second's worth
+pub const SNAPSHOT_BUFFER_SIZE: usize = 40;
+
+/// Struct to represent another player in the lobby. Proxies are driven entirely
+/// by snapshots from the server and never simulate anything
+#[derive(Component)]
+pub struct RemotePlayer {
+    pub id: i32,
+    pub boat: bool,
+    /// Where the server last saw them
+    pub area: Area,
+}
+
+/// A remote player's position at a point in server time (in seconds)
+#[derive(Clone, Copy)]
+pub struct TimedPose {
+    pub time: f32,
+    pub pos: Vec3,
+    pub rot: Quat,
+}
+
+/// Recent snapshots of a remote player, oldest first. The proxy is drawn
+/// slightly in the past by interpolating between the two snapshots around
+/// ServerClock::render_time()
+#[derive(Component)]
+pub struct SnapshotBuffer {
+    pub poses: VecDeque<TimedPose>,
+}
+
+impl SnapshotBuffer {
+    pub fn new(pose: TimedPose) -> SnapshotBuffer {
+        SnapshotBuffer {
+            poses: VecDeque::from([pose]),
+        }
+    }
+
+    /// Adds a snapshot, ignoring any that arrive after a newer one
+    pub fn push(&mut self, pose: TimedPose) {
+        if let Some(last) = self.poses.back() {
+            if pose.time <= last.time {
+                return;
+            }
+        }
+
+        self.poses.push_back(pose);
+        while self.poses.len() > SNAPSHOT_BUFFER_SIZE {
+            self.poses.pop_front();
+        }
+    }
+
+    /// Where the player was at `time`. Holds the oldest or newest snapshot when
+    /// `time` falls outside of the buffer rather than guessing
+    pub fn sample(&self, time: f32) -> Option<(Vec3, Quat)> {
+        let first = self.poses.front()?;
+        if time <= first.time {
+            return Some((first.pos, first.rot));
+        }
+
+        for (from, to) in self.poses.iter().zip(self.poses.iter().skip(1)) {
+            if time <= to.time {
+                let t = (time - from.time) / (to.time - from.time);
+                return Some((from.pos.lerp(to.pos, t), from.rot.slerp(to.rot, t)));
+            }
+        }
+
+        let last = self.poses.back()?;
+        Some((last.pos, last.rot))
+    }
+}
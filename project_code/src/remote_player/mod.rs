@@ -0,0 +1,21 @@
+pub(crate) mod components;
+mod systems;
+
+use bevy::prelude::*;
+use systems::*;
+
+pub struct RemotePlayerPlugin;
+
+impl Plugin for RemotePlayerPlugin {
+    /// Builds the remote player plugin
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                apply_player_updates,
+                interpolate_remote_players.after(apply_player_updates),
+                remote_player_visibility.after(apply_player_updates),
+            ),
+        );
+    }
+}
@@ -0,0 +1,179 @@
+use bevy::prelude::*;
+use protocol::components::Area;
+use protocol::messages::{PlayerPose, ServerMessage};
+
+use crate::network::components::{HostPlayer, Server, ServerClock, ServerEvent};
+use crate::remote_player::components::*;
+
+/*   APPLY_PLAYER_UPDATES FUNCTION   */
+/// Spawns, updates and despawns the proxies for the other players in the lobby
+/// from the server's snapshot and player_left messages
+pub fn apply_player_updates(
+    mut commands: Commands,
+    mut events: EventReader<ServerEvent>,
+    mut query: Query<(Entity, &mut RemotePlayer, &mut SnapshotBuffer)>,
+    asset_server: Res<AssetServer>,
+    mut texture_atlases: ResMut<Assets<TextureAtlasLayout>>,
+    host: Res<HostPlayer>,
+    server: Res<Server>,
+) {
+    for ServerEvent(message) in events.read() {
+        match message {
+            ServerMessage::Snapshot(snapshot) => {
+                let time = snapshot.tick as f32 * server.tick_seconds;
+
+                //anyone missing from the snapshot has left the lobby, or
+                //the part of the world we're in
+                for (entity, remote, _) in query.iter() {
+                    if !snapshot.players.iter().any(|player| player.id == remote.id) {
+                        commands.entity(entity).despawn_recursive();
+                    }
+                }
+
+                for player in snapshot.players.iter() {
+                    if player.id == host.player.id {
+                        continue;
+                    }
+
+                    let existing = query
+                        .iter_mut()
+                        .find(|(_, remote, _)| remote.id == player.id);
+
+                    match existing {
+                        //same player in the same form, just move it
+                        Some((_, mut remote, mut buffer)) if remote.boat == player.boat => {
+                            remote.area = player.area;
+                            buffer.push(TimedPose {
+                                time,
+                                pos: player.pos.into(),
+                                rot: player.rot.into(),
+                            });
+                        }
+                        //swapped between boat and pirate
+                        Some((entity, ..)) => {
+                            commands.entity(entity).despawn_recursive();
+                            spawn_remote_player(
+                                &mut commands,
+                                &asset_server,
+                                &mut texture_atlases,
+                                player,
+                                time,
+                            );
+                        }
+                        None => {
+                            spawn_remote_player(
+                                &mut commands,
+                                &asset_server,
+                                &mut texture_atlases,
+                                player,
+                                time,
+                            );
+                        }
+                    }
+                }
+            }
+            ServerMessage::PlayerLeft { id } => {
+                for (entity, remote, _) in query.iter() {
+                    if remote.id == *id {
+                        info!("Player #{} left the lobby", id);
+                        commands.entity(entity).despawn_recursive();
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/*   SPAWN_REMOTE_PLAYER FUNCTION   */
+/// Spawns a proxy for another player, as a boat at sea or a pirate on land
+fn spawn_remote_player(
+    commands: &mut Commands,
+    asset_server: &Res<AssetServer>,
+    texture_atlases: &mut ResMut<Assets<TextureAtlasLayout>>,
+    player: &PlayerPose,
+    time: f32,
+) {
+    //boats share the host's ship sheet, pirates the pirate sheet
+    let (texture, layout) = if player.boat {
+        (
+            asset_server.load("s_basic_ship.png"),
+            TextureAtlasLayout::from_grid(UVec2::splat(100), 2, 2, None, None),
+        )
+    } else {
+        (
+            asset_server.load("s_pirate.png"),
+            TextureAtlasLayout::from_grid(UVec2::splat(64), 8, 5, None, None),
+        )
+    };
+
+    commands.spawn((
+        SpriteBundle {
+            texture,
+            transform: Transform {
+                translation: player.pos.into(),
+                rotation: player.rot.into(),
+                ..default()
+            },
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        TextureAtlas {
+            layout: texture_atlases.add(layout),
+            index: 0,
+        },
+        RemotePlayer {
+            id: player.id,
+            boat: player.boat,
+            area: player.area,
+        },
+        SnapshotBuffer::new(TimedPose {
+            time,
+            pos: player.pos.into(),
+            rot: player.rot.into(),
+        }),
+    ));
+}
+
+/*   INTERPOLATE_REMOTE_PLAYERS FUNCTION   */
+/// Draws every proxy where it was a little while ago on the server, smoothly
+/// interpolated between the snapshots around that moment
+pub fn interpolate_remote_players(
+    time: Res<Time>,
+    clock: Res<ServerClock>,
+    server: Res<Server>,
+    mut query: Query<(&mut Transform, &SnapshotBuffer), With<RemotePlayer>>,
+) {
+    let render_time = clock.render_time(time.elapsed_seconds(), server.tick_seconds);
+
+    for (mut transform, buffer) in query.iter_mut() {
+        if let Some((pos, rot)) = buffer.sample(render_time) {
+            transform.translation = pos;
+            transform.rotation = rot;
+        }
+    }
+}
+
+/*   REMOTE_PLAYER_VISIBILITY FUNCTION   */
+/// Only shows the players in the same part of the world as the host, as boats
+/// at sea and pirates on land. The server stops sending the others, this hides
+/// them until it has caught up with where everyone is
+pub fn remote_player_visibility(
+    host: Res<HostPlayer>,
+    mut query: Query<(&RemotePlayer, &mut Visibility)>,
+) {
+    for (remote, mut visibility) in query.iter_mut() {
+        let shown = remote.area == host.player.area
+            && match remote.area {
+                Area::Ocean => remote.boat,
+                Area::Island { .. } | Area::Dungeon { .. } => !remote.boat,
+                Area::MainMenu => false,
+            };
+
+        *visibility = if shown {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
@@ -1,40 +1,26 @@
 use bevy::prelude::*;
-use bevy::render::texture;
 
-use crate::data::gameworld_data::*;
+use crate::dungeon_sync::components::DungeonEnemy;
+use crate::dungeon_sync::systems::report_dungeon_kill;
 use crate::enemies::*;
 use crate::hitbox_system::*;
+use crate::network::components::{Server, UDP};
 use crate::player::components::*;
 use crate::rock::components::*;
 use crate::shop::systems::generate_loot_item;
 
-/*  Spawn Rock FUNCTION  */
-/// Spawns a kraken entity in the gameworld
-pub fn spawn_rock(
-    mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    mut texture_atlases: ResMut<Assets<TextureAtlasLayout>>,
-) {
-    let transform = Transform::from_xyz(0., -(WIN_H / 1.5) + ((TILE_SIZE as f32) * 1.5), 900.);
-
-    spawn_enemy(
-        &mut commands,
-        EnemyT::Rock,
-        transform,
-        &asset_server,
-        &mut texture_atlases,
-    );
-}
-
 /*   ROCK_DAMAGED FUNCTION   */
 /// Current functionality: Detects when a player is within player attack range (this will later be replaced with
 // player weapon/attack collision) and then takes 1 damage (dies)
 pub fn rock_damaged(
     mut commands: Commands,
     mut rock_query: Query<(&mut Rock, Entity, &mut Hurtbox, &Transform), With<Rock>>,
+    dungeon_enemies: Query<&DungeonEnemy>,
     mut player_query: Query<&mut Player>,
+    mut udp: ResMut<UDP>,
+    server: Res<Server>,
 ) {
-    for (mut rock, entity, mut hurtbox, transform) in rock_query.iter_mut() {
+    for (mut rock, entity, mut hurtbox, _transform) in rock_query.iter_mut() {
         if !hurtbox.colliding.is {
             continue;
         }
@@ -44,11 +30,16 @@ pub fn rock_damaged(
 
         if rock.current_hp <= 0. {
             println!("Rock was attacked by player, it is dead :(");
-            let loot = generate_loot_item(EnemyT::Rock);
-            if loot.price > 0 {
-                println!("Rock dropped: {}", loot.name);
-                if let Ok(mut player) = player_query.get_single_mut() {
-                    player.inventory.add_item(loot);
+            if let Ok(enemy) = dungeon_enemies.get(entity) {
+                //the loot comes once the server says nobody beat us to it
+                report_dungeon_kill(&mut udp, &server, enemy);
+            } else {
+                let loot = generate_loot_item(EnemyT::Rock);
+                if loot.price > 0 {
+                    println!("Rock dropped: {}", loot.name);
+                    if let Ok(mut player) = player_query.get_single_mut() {
+                        player.inventory.add_item(loot);
+                    }
                 }
             }
             commands.entity(entity).despawn();
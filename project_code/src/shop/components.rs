@@ -149,15 +149,10 @@ pub enum ShopEvent {
     UpgradeBoatCannon,
 }
 
-#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum ShopPage {
+    #[default]
     PlayerUpgrades,
     ShipUpgrades,
     Sell,
 }
-
-impl Default for ShopPage {
-    fn default() -> Self {
-        ShopPage::PlayerUpgrades
-    }
-}
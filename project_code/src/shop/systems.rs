@@ -1,12 +1,11 @@
 use super::components::*;
 use crate::boat::components::Boat;
 use crate::enemies::*;
-use crate::player;
 use crate::player::components::Player;
 use crate::player::components::Sword;
 use bevy::prelude::*;
 
-pub fn setup_shop_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
+pub fn setup_shop_ui(mut commands: Commands) {
     commands.spawn((
         NodeBundle {
             style: Style {
@@ -39,22 +38,17 @@ pub fn update_shop_text(
         }
 
         // Update button texts
-        if let Ok((button, _)) = button_query.get(parent.get()) {
-            match button {
-                ShopButton::UpgradeItem(index) => {
-                    if let Some(shop_item) = shop.items.get(*index) {
-                        if let Some(owned_item) = player
-                            .inventory
-                            .items
-                            .iter()
-                            .find(|i| i.item_type == shop_item.item_type)
-                        {
-                            text.sections[0].value =
-                                format!("Upgrade {} (Lvl {})", shop_item.name, owned_item.level);
-                        }
-                    }
+        if let Ok((ShopButton::UpgradeItem(index), _)) = button_query.get(parent.get()) {
+            if let Some(shop_item) = shop.items.get(*index) {
+                if let Some(owned_item) = player
+                    .inventory
+                    .items
+                    .iter()
+                    .find(|i| i.item_type == shop_item.item_type)
+                {
+                    text.sections[0].value =
+                        format!("Upgrade {} (Lvl {})", shop_item.name, owned_item.level);
                 }
-                _ => {}
             }
         }
     }
@@ -63,7 +57,6 @@ pub fn update_shop_text(
 pub fn rebuild_shop_ui(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
-    shop: Res<Shop>,
     player: Query<&Player>,
     shop_page: Res<ShopPage>,
     shop_ui_query: Query<Entity, With<ShopUI>>,
@@ -91,7 +84,7 @@ pub fn rebuild_shop_ui(
                     margin: UiRect::all(Val::Px(10.0)),
                     ..default()
                 },
-                background_color: Color::rgb(0.1, 0.1, 0.1).into(),
+                background_color: Color::srgb(0.1, 0.1, 0.1).into(),
                 ..default()
             })
             .with_children(|panel| {
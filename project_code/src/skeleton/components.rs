@@ -1,5 +1,4 @@
 use bevy::prelude::*;
-use crate::shop::components::Item;
 
 //constants
 pub const SKELETON_ANIMATION_TIME: f32 = 0.4;
@@ -18,7 +17,6 @@ pub const SKELETON_AGRO_RANGE: f32 = 600.;
 pub struct Skeleton {
     pub rotation_speed: f32,
     pub current_hp: f32,
-    pub max_hp: f32,
 }
 
 #[derive(Component)]
@@ -26,12 +24,3 @@ pub struct SkeletonProjectile {
     pub timer: Timer,
 }
 
-#[derive(Component)]
-pub struct Loot;
-
-#[derive(Bundle)]
-pub struct LootBundle {
-    pub item: Item,
-    pub sprite_bundle: SpriteBundle,
-    pub marker: Loot,
-}
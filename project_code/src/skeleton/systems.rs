@@ -1,10 +1,11 @@
 use bevy::prelude::*;
-use bevy::render::texture;
 use bevy::sprite::TextureAtlas;
 
-use crate::data::gameworld_data::*;
+use crate::dungeon_sync::components::DungeonEnemy;
+use crate::dungeon_sync::systems::report_dungeon_kill;
 use crate::enemies::*;
 use crate::hitbox_system::*;
+use crate::network::components::{Server, UDP};
 use crate::player::components::*;
 use crate::shop::systems::*;
 use crate::skeleton::components::*;
@@ -56,51 +57,18 @@ pub fn rotate_skeleton(
     }
 }
 
-/*   ANIMATE_SKELETON FUNCTION   */
-/// Animates a skeleton entity
-pub fn animate_skeleton(
-    time: Res<Time>,
-    mut query: Query<(&mut AnimationTimer, &mut TextureAtlas, &AnimationFrameCount)>,
-) {
-    for (mut timer, mut texture_atlas, frame_count) in &mut query {
-        timer.tick(time.delta());
-
-        if timer.finished() {
-            texture_atlas.index = (texture_atlas.index + 1) % **frame_count;
-        }
-    }
-}
-
-/*  SPAWN_SKELETON FUNCTION  */
-/// Spawns a skeleton entity in the gameworld
-
-pub fn spawn_skeleton(
-    mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    mut texture_atlases: ResMut<Assets<TextureAtlasLayout>>,
-) {
-    let transform = Transform::from_xyz(0., -(WIN_H / 2.) + ((TILE_SIZE as f32) * 1.5), 900.)
-        .with_scale(Vec3::splat(2.0));
-
-    // Spawning skeleton 1
-    spawn_enemy(
-        &mut commands,
-        EnemyT::RSkeleton,
-        transform,
-        &asset_server,
-        &mut texture_atlases,
-    );
-}
-
 /*   Skeleton_DAMAGED FUNCTION   */
 /// Current functionality: Detects when a player is within player attack range (this will later be replaced with
 // player weapon/attack collision) and then takes 1 damage (dies)
 pub fn skeleton_damaged(
     mut commands: Commands,
     mut skeleton_query: Query<(&mut Skeleton, Entity, &mut Hurtbox, &Transform), With<Skeleton>>,
+    dungeon_enemies: Query<&DungeonEnemy>,
     mut player_query: Query<&mut Player>,
+    mut udp: ResMut<UDP>,
+    server: Res<Server>,
 ) {
-    for (mut skeleton, entity, mut hurtbox, transform) in skeleton_query.iter_mut() {
+    for (mut skeleton, entity, mut hurtbox, _transform) in skeleton_query.iter_mut() {
         if !hurtbox.colliding.is {
             continue;
         }
@@ -110,11 +78,16 @@ pub fn skeleton_damaged(
 
         if skeleton.current_hp <= 0. {
             println!("Skeleton was attacked by player, it is dead :(");
-            let loot = generate_loot_item(EnemyT::RSkeleton);
-            if loot.price > 0 {
-                println!("Skeleton dropped: {}", loot.name);
-                if let Ok(mut player) = player_query.get_single_mut() {
-                    player.inventory.add_item(loot);
+            if let Ok(enemy) = dungeon_enemies.get(entity) {
+                //the loot comes once the server says nobody beat us to it
+                report_dungeon_kill(&mut udp, &server, enemy);
+            } else {
+                let loot = generate_loot_item(EnemyT::RSkeleton);
+                if loot.price > 0 {
+                    println!("Skeleton dropped: {}", loot.name);
+                    if let Ok(mut player) = player_query.get_single_mut() {
+                        player.inventory.add_item(loot);
+                    }
                 }
             }
             commands.entity(entity).despawn();
@@ -140,6 +113,7 @@ pub fn despawn_all_skeletons(mut commands: Commands, query: Query<Entity, With<S
 /// Things not added:
 /// * Attack cooldown timer
 /// * Projectile shooting
+///
 /// Things currently added:
 /// * Distance-to-player checking
 /// * Attack cooldown timer
@@ -202,10 +176,7 @@ pub fn skeleton_attack(
                 },
                 ..default()
             },
-            TextureAtlas {
-                layout: layout,
-                index: 0,
-            },
+            TextureAtlas { layout, index: 0 },
             SkeletonProjectile {
                 timer: Timer::from_seconds(0.2, TimerMode::Once),
             },
@@ -316,9 +287,9 @@ pub fn move_skeleton(
 /// Despawns all the skeleton's projectiles
 pub fn despawn_all_skeleton_proj(
     mut commands: Commands,
-    mut proj_query: Query<(Entity, &mut Lifetime)>,
+    proj_query: Query<Entity, With<Lifetime>>,
 ) {
-    for (entity, mut lifetime) in proj_query.iter_mut() {
+    for entity in proj_query.iter() {
         commands.entity(entity).despawn();
     }
 }
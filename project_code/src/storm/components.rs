@@ -1,8 +1,5 @@
 use bevy::prelude::*;
 
-pub const STORM_DAMAGE_INTERVAL: f32 = 5.0;
-
+/// Struct to represent the storm entity, which damages boats caught inside of it
 #[derive(Component)]
-pub struct Storm {
-    pub damage_timer: Timer,
-}
+pub struct Storm;
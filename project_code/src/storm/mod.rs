@@ -16,19 +16,16 @@ fn cleanup_storms(mut commands: Commands, storm_query: Query<Entity, With<Storm>
 
 impl Plugin for StormPlugin {
     fn build(&self, app: &mut App) {
+        //storms are spawned and despawned by the server, see enemy_sync
         app
             // Initialize resources
-            .init_resource::<StormSpawnTimer>()
             .init_resource::<StormDamageCooldownTimer>()
             // Setup systems on enter
-            .add_systems(
-                OnEnter(GameworldState::Ocean),
-                (setup_storm_timer, setup_storm_damage_cooldown),
-            )
+            .add_systems(OnEnter(GameworldState::Ocean), setup_storm_damage_cooldown)
             // Update systems
             .add_systems(
                 Update,
-                (spawn_storm, storm_damage_system).run_if(in_state(GameworldState::Ocean)),
+                storm_damage_system.run_if(in_state(GameworldState::Ocean)),
             )
             // Cleanup on exit
             .add_systems(OnExit(GameworldState::Ocean), cleanup_storms);
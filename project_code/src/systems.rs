@@ -1,12 +1,12 @@
 use crate::components::*;
 use crate::data::gameworld_data::*;
-use crate::hitbox_system::{Hitbox, Hurtbox};
-use crate::level::components::{Dungeon, IslandType, OceanDoor};
+use crate::hitbox_system::Hurtbox;
+use crate::level::components::{Dungeon, OceanDoor};
 use crate::player::components::*;
 use crate::{boat::components::*, level::components::Island};
 use bevy::math::bounding::BoundingVolume;
 use bevy::math::bounding::IntersectsVolume;
-use bevy::{prelude::*, window::PresentMode};
+use bevy::prelude::*;
 
 use crate::bat::components::Bat;
 use crate::player::components::Player;
@@ -14,8 +14,6 @@ use crate::rock::components::Rock;
 use crate::skeleton::components::Skeleton;
 use crate::wfc::components::Wall;
 
-use crate::components::*;
-
 /*   MOVE_CAMERA FUNCTIONS  */
 /// Updates the cameras position to center the current player
 /// and tracks the player wherever they go
@@ -97,28 +95,19 @@ pub fn change_gameworld_state(
     islands_query: Query<&mut Island, With<Island>>,
     dungeon_query: Query<&mut Dungeon, With<Dungeon>>,
     gameworld_state: Res<State<GameworldState>>,
-    keyboard_input: Res<ButtonInput<KeyCode>>,
     mut player_query: Query<&mut Player, With<Player>>,
     mut boat_query: Query<&mut Boat, With<Boat>>,
     door_query: Query<&mut OceanDoor, With<OceanDoor>>,
     query: Query<(Entity, &Transform), (With<Player>, Without<TransitionImmunity>)>,
 ) {
-    if keyboard_input.just_pressed(KeyCode::Enter)
-        && *gameworld_state.get() != GameworldState::Ocean
-        && *gameworld_state.get() != GameworldState::Island
-        && *gameworld_state.get() != GameworldState::Dungeon
-    {
-        current_island_type.island_type = IslandType::Start;
-        next_state.set(GameworldState::Island);
-        return;
-    }
-    for (entity, transform) in query.iter() {
+    for (entity, _transform) in query.iter() {
         //  CASE: OCEAN --> ISLAND
         if *gameworld_state.get() == GameworldState::Ocean {
             let boat = boat_query.single_mut();
             for island in islands_query.iter() {
                 if island.aabb.aabb.intersects(&boat.aabb.aabb) {
                     current_island_type.island_type = island.island_type;
+                    current_island_type.zone = Some(island.zone);
                     println!("going to the island!");
                     next_state.set(GameworldState::Island);
                 }
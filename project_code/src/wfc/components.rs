@@ -22,9 +22,7 @@ pub struct DungeonTileSheet(
 pub struct Wall;
 
 #[derive(Component)]
-pub struct Tile {
-    pub tile_type: TileType,
-}
+pub struct Tile;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Ord, PartialOrd)]
 
@@ -32,7 +30,6 @@ pub enum TileType {
     Wall,
     Ground,
     Void,
-    Hole,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
@@ -172,23 +169,17 @@ impl Pattern {
     }
 }
 
+/// How the template is cut into patterns. The size of the dungeon and where
+/// its spawn and door go come from the server, see DungeonParams
 #[derive(Resource)]
 pub struct WFCSettings {
     pub pattern_size: usize,
-    pub output_width: usize,
-    pub output_height: usize,
-    pub spawn_area: (usize, usize), // bottom left corner coordinates
-    pub door_area: (usize, usize),  // top right corner coordinates
 }
 
 impl Default for WFCSettings {
     fn default() -> Self {
         Self {
             pattern_size: 3,
-            output_width: 100,
-            output_height: 100,
-            spawn_area: (3, 3),    // x,y coordinates for spawn area
-            door_area: (97, 97),     // x,y coordinates for door area
         }
     }
 }
@@ -198,8 +189,8 @@ impl Default for WFCSettings {
 pub struct WFCState {
     pub patterns: Vec<Pattern>,
     pub weights: Vec<f32>,
-    pub wave: Vec<Vec<WaveCell>>,
-    pub entropy_heap: BinaryHeap<EntropyCell>,
+    wave: Vec<Vec<WaveCell>>,
+    entropy_heap: BinaryHeap<EntropyCell>,
     pub pattern_compatibility: Vec<Vec<Vec<usize>>>,
 }
 
@@ -299,8 +290,12 @@ impl WFCState {
             }
         }
     }
-    pub fn collapse(&mut self) -> Option<(Vec<Vec<TileType>>, Vec2, Vec2, Vec2)> {
-        let mut rng = rand::thread_rng();
+    /// Collapses the wave, picking patterns with `rng` so the same numbers
+    /// always give the same output
+    pub fn collapse<R: Rng>(
+        &mut self,
+        rng: &mut R,
+    ) -> Option<(Vec<Vec<TileType>>, Vec2, Vec2, Vec2)> {
         println!("Starting new collapse attempt");
     
         while let Some(EntropyCell { x, y, entropy }) = self.entropy_heap.pop() {
@@ -358,14 +353,13 @@ impl WFCState {
         let width = self.wave[0].len();
         let mut output = vec![vec![TileType::Void; width]; height];
 
-        for y in 0..height {
-            for x in 0..width {
-                let cell = &self.wave[y][x];
+        for (row, cells) in output.iter_mut().zip(self.wave.iter()) {
+            for (tile, cell) in row.iter_mut().zip(cells.iter()) {
                 if let Some(pattern_idx) = cell.possible_patterns.iter()
                     .enumerate()
                     .find(|(_, &valid)| valid)
                     .map(|(i, _)| i) {
-                    output[y][x] = self.patterns[pattern_idx].data[0];
+                    *tile = self.patterns[pattern_idx].data[0];
                 }
             }
         }
@@ -5,9 +5,7 @@ use bevy::prelude::*;
 use components::*;
 use systems::*;
 use crate::components::{Background, GameworldState};
-use crate::level::components::*;
 use crate::level::systems::*;
-use crate::handle_door_translation;
 
 pub struct WFCPlugin;
 
@@ -19,7 +17,9 @@ impl Plugin for WFCPlugin {
                 load_dungeon,
             ).chain())
             .add_systems(OnEnter(GameworldState::Dungeon), 
-               (create_patterns_from_template, generate_dungeon, despawn_with::<Background>).chain())
+               (create_patterns_from_template, despawn_with::<Background>).chain())
+            //built once the server sends the dungeon, see request_dungeon
+            .add_systems(Update, generate_dungeon.run_if(in_state(GameworldState::Dungeon)))
             .add_systems(OnExit(GameworldState::Dungeon),(despawn_with::<Tile>, cleanup_debug_markers))
             .add_systems(OnExit(GameworldState::Dungeon), cleanup_debug_markers);
 
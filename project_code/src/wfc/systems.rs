@@ -3,11 +3,12 @@ use crate::components::BoundingBox;
 use crate::components::*;
 use crate::level::components::*;
 use bevy::prelude::*;
-use rand::Rng;
+use protocol::dungeon;
+use protocol::messages::ServerMessage;
 
 use crate::data::gameworld_data::*;
-use crate::enemies::*;
-use crate::level::systems::*;
+use crate::dungeon_sync::systems::spawn_dungeon_enemies;
+use crate::network::components::ServerEvent;
 
 #[derive(Resource)]
 pub struct DungeonTemplates {
@@ -129,7 +130,6 @@ fn extract_patterns(image: &Image, pattern_size: usize) -> Vec<Pattern> {
     }
 
     // Step 4: Convert to final vector with weights normalized
-    let total_count: f32 = pattern_counts.values().sum();
     let mut final_patterns: Vec<Pattern> = pattern_counts.keys().cloned().collect();
 
     // Sort patterns to ensure consistent ordering
@@ -174,7 +174,7 @@ pub fn load_dungeon(
 
 fn spawn_dungeon_tiles(
     commands: &mut Commands,
-    dungeon: &Vec<Vec<TileType>>,
+    dungeon: &[Vec<TileType>],
     dungeon_tile_sheet: &Res<DungeonTileSheet>,
     current_island_type: &Res<CurrentIslandType>,
 ) {
@@ -196,10 +196,8 @@ fn spawn_dungeon_tiles(
         -1.0,
     );
 
-    for y in 0..height {
-        for x in 0..width {
-            let tile_type = dungeon[y][x];
-
+    for row in dungeon {
+        for &tile_type in row {
             let mut entity = commands.spawn((
                 SpriteBundle {
                     texture: texture_handle.clone(),
@@ -216,10 +214,9 @@ fn spawn_dungeon_tiles(
                         TileType::Wall => 0,
                         TileType::Ground => 1,
                         TileType::Void => 2,
-                        TileType::Hole => 3,
                     },
                 },
-                Tile { tile_type },
+                Tile,
             ));
 
             if tile_type == TileType::Wall {
@@ -239,7 +236,7 @@ fn spawn_dungeon_tiles(
     }
 }
 
-fn add_outer_walls(grid: &mut Vec<TileType>, width: usize, height: usize) {
+fn add_outer_walls(grid: &mut [TileType], width: usize, height: usize) {
     // Add top and bottom walls
     for x in 0..width {
         grid[x] = TileType::Wall; // Top wall
@@ -254,7 +251,7 @@ fn add_outer_walls(grid: &mut Vec<TileType>, width: usize, height: usize) {
 }
 
 fn place_landmarks(
-    grid: &mut Vec<TileType>,
+    grid: &mut [TileType],
     width: usize,
     spawn_pos: (usize, usize),
     door_pos: (usize, usize),
@@ -282,46 +279,50 @@ fn place_landmarks(
     }
 }
 
+/*   GENERATE_DUNGEON FUNCTION   */
+/// Builds the dungeon the server sent us into. The path and the enemies along
+/// it come from the dungeon's seed and the walls from the WFC template seeded
+/// the same way, so everyone going into it gets the same dungeon
 pub fn generate_dungeon(
     mut commands: Commands,
-    mut wfc_state: Option<ResMut<WFCState>>,
-    settings: Res<WFCSettings>,
+    mut events: EventReader<ServerEvent>,
+    wfc_state: Option<ResMut<WFCState>>,
     dungeon_tile_sheet: Res<DungeonTileSheet>,
     asset_server: Res<AssetServer>,
     mut texture_atlases: ResMut<Assets<TextureAtlasLayout>>,
     current_island_type: Res<CurrentIslandType>,
 ) {
+    let Some(instance) = events
+        .read()
+        .filter_map(|ServerEvent(message)| match message {
+            ServerMessage::DungeonEntered(instance) => Some(instance),
+            _ => None,
+        })
+        .last()
+    else {
+        return;
+    };
+    let params = &instance.params;
+
     if let Some(mut wfc_state) = wfc_state {
-        for attempt in 0..20 {
-            // First create guaranteed path between spawn and door
-            let mut grid = vec![TileType::Wall; settings.output_width * settings.output_height];
-            create_path(
-                &mut grid,
-                settings.output_width,
-                settings.spawn_area,
-                settings.door_area,
-                &mut commands,
-                &asset_server,
-                &mut texture_atlases,
-            );
-            spawn_debug_path_markers(&mut commands, &grid, settings.output_width);
+        // First lay the path between spawn and door, the same one the server has
+        let mut grid = vec![TileType::Wall; params.width * params.height];
+        for (x, y) in dungeon::generate_dungeon(params).path {
+            grid[y * params.width + x] = TileType::Ground;
+        }
+        spawn_debug_path_markers(&mut commands, &grid, params.width);
 
-            // Store the path positions for later enemy spawning
-            let path_positions: Vec<(usize, usize)> = grid
-                .iter()
-                .enumerate()
-                .filter(|(_, &tile)| tile == TileType::Ground)
-                .map(|(i, _)| (i % settings.output_width, i / settings.output_width))
-                .collect();
+        let mut rng = dungeon::layout_rng(params);
 
+        for _ in 0..20 {
             // Then run WFC on remaining tiles
-            wfc_state.initialize(settings.output_width, settings.output_height);
-            if let Some((mut dungeon, player_pos, _, door_pos)) = wfc_state.collapse() {
+            wfc_state.initialize(params.width, params.height);
+            if let Some((mut dungeon, _, _, _)) = wfc_state.collapse(&mut rng) {
                 // Merge the path with WFC generated dungeon
                 for (i, tile) in grid.iter().enumerate() {
                     if *tile == TileType::Ground {
-                        let y = i / settings.output_width;
-                        let x = i % settings.output_width;
+                        let y = i / params.width;
+                        let x = i % params.width;
                         dungeon[y][x] = TileType::Ground;
                     }
                 }
@@ -330,28 +331,19 @@ pub fn generate_dungeon(
                 let mut final_grid: Vec<TileType> = dungeon.into_iter().flatten().collect();
 
                 // Add outer walls and landmarks
-                add_outer_walls(
-                    &mut final_grid,
-                    settings.output_width,
-                    settings.output_height,
-                );
-                place_landmarks(
-                    &mut final_grid,
-                    settings.output_width,
-                    settings.spawn_area,
-                    settings.door_area,
-                );
+                add_outer_walls(&mut final_grid, params.width, params.height);
+                place_landmarks(&mut final_grid, params.width, params.spawn, params.door);
 
                 if ensure_connectivity(
                     &mut final_grid,
-                    settings.output_width,
-                    settings.output_height,
-                    settings.spawn_area,
-                    settings.door_area,
+                    params.width,
+                    params.height,
+                    params.spawn,
+                    params.door,
                 ) {
                     // Convert back to 2D for rendering
                     let dungeon: Vec<Vec<TileType>> = final_grid
-                        .chunks(settings.output_width)
+                        .chunks(params.width)
                         .map(|chunk| chunk.to_vec())
                         .collect();
 
@@ -363,55 +355,13 @@ pub fn generate_dungeon(
                         &current_island_type,
                     );
 
-                    // Now spawn enemies along the stored path positions
-                    let mut rng = rand::thread_rng();
-                    let mut steps = 0;
-
-                    for (x, y) in path_positions {
-                        steps += 1;
-                        // Skip first 10 steps to avoid spawn area
-                        if steps > 10 {
-                            let world_x = -(settings.output_width as f32) * TILE_SIZE as f32
-                                + (x as f32 * TILE_SIZE as f32 * 2.0)
-                                + TILE_SIZE as f32;
-                            let world_y = -(settings.output_width as f32) * TILE_SIZE as f32
-                                + (y as f32 * TILE_SIZE as f32 * 2.0)
-                                + TILE_SIZE as f32;
-
-                            let transform = Transform::from_xyz(world_x, world_y, 900.0)
-                                .with_scale(Vec3::splat(2.0));
-
-                            // Roll for each enemy type
-                            if rng.gen_bool(0.01) {
-                                // 1% chance for skeleton
-                                spawn_enemy(
-                                    &mut commands,
-                                    EnemyT::RSkeleton,
-                                    transform,
-                                    &asset_server,
-                                    &mut texture_atlases,
-                                );
-                            } else if rng.gen_bool(0.01) {
-                                // 1% chance for bat
-                                spawn_enemy(
-                                    &mut commands,
-                                    EnemyT::Bat,
-                                    transform,
-                                    &asset_server,
-                                    &mut texture_atlases,
-                                );
-                            } else if rng.gen_bool(0.0025) {
-                                // 0.25% chance for rock
-                                spawn_enemy(
-                                    &mut commands,
-                                    EnemyT::Rock,
-                                    transform,
-                                    &asset_server,
-                                    &mut texture_atlases,
-                                );
-                            }
-                        }
-                    }
+                    // Now spawn whichever enemies along the path are still alive
+                    spawn_dungeon_enemies(
+                        &mut commands,
+                        instance,
+                        &asset_server,
+                        &mut texture_atlases,
+                    );
                     return;
                 }
             }
@@ -419,56 +369,8 @@ pub fn generate_dungeon(
     }
 }
 
-fn create_path(
-    grid: &mut Vec<TileType>,
-    width: usize,
-    start: (usize, usize),
-    end: (usize, usize),
-    commands: &mut Commands,
-    asset_server: &Res<AssetServer>,
-    texture_atlases: &mut ResMut<Assets<TextureAtlasLayout>>,
-) {
-    let mut rng = rand::thread_rng();
-    let mut current = start;
-
-    while current != end {
-        grid[current.1 * width + current.0] = TileType::Ground;
-
-        // Path creation logic
-        if current.0 != end.0 && current.1 != end.1 {
-            if rng.gen_bool(0.5) {
-                current.0 = if end.0 > current.0 {
-                    current.0 + 1
-                } else {
-                    current.0 - 1
-                };
-            } else {
-                current.1 = if end.1 > current.1 {
-                    current.1 + 1
-                } else {
-                    current.1 - 1
-                };
-            }
-        } else if current.0 != end.0 {
-            current.0 = if end.0 > current.0 {
-                current.0 + 1
-            } else {
-                current.0 - 1
-            };
-        } else {
-            current.1 = if end.1 > current.1 {
-                current.1 + 1
-            } else {
-                current.1 - 1
-            };
-        }
-    }
-
-    grid[end.1 * width + end.0] = TileType::Ground;
-}
-
 fn ensure_connectivity(
-    grid: &mut Vec<TileType>,
+    grid: &mut [TileType],
     width: usize,
     height: usize,
     spawn_pos: (usize, usize),
@@ -504,7 +406,7 @@ fn ensure_connectivity(
     false
 }
 
-fn spawn_debug_path_markers(commands: &mut Commands, grid: &Vec<TileType>, width: usize) {
+fn spawn_debug_path_markers(commands: &mut Commands, grid: &[TileType], width: usize) {
     let offset_x = -3200.0;
     let offset_y = -3200.0;
 
@@ -529,7 +431,7 @@ fn spawn_debug_path_markers(commands: &mut Commands, grid: &Vec<TileType>, width
     }
 }
 
-pub fn find_spawn_points(dungeon: &Vec<Vec<TileType>>) -> Option<(Vec2, Vec2, Vec2)> {
+pub fn find_spawn_points(_dungeon: &Vec<Vec<TileType>>) -> Option<(Vec2, Vec2, Vec2)> {
     // Calculate spawn position in bottom left 5x5 area
     let spawn_pos = Vec2::new(
         3.0 * TILE_SIZE as f32 * 2.0, // Center of 5x5 area
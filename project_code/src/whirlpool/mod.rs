@@ -3,7 +3,6 @@ use bevy::prelude::*;
 pub(crate) mod components;
 mod systems;
 
-use crate::GameState;
 use crate::GameworldState;
 use systems::*;
 use crate::whirlpool::components::Whirlpool;
@@ -27,23 +26,13 @@ fn cleanup_whirlpools(
 
 impl Plugin for WhirlpoolPlugin {
     fn build(&self, app: &mut App) {
+        //whirlpools are spawned and despawned by the server, see enemy_sync
         app
-            .init_resource::<WhirlpoolSpawnTimer>()
             .init_resource::<WhirlpoolCooldownTimer>() // Add this line
-            .add_systems(OnEnter(GameworldState::Ocean), 
-                (
-                    setup_whirlpool_timer,
-                    setup_whirlpool_cooldown, // Add this line
-                )
-            )
+            .add_systems(OnEnter(GameworldState::Ocean), setup_whirlpool_cooldown)
             .add_systems(
                 Update, 
-                (
-                    spawn_whirlpool,
-                    despawn_whirlpool_system,
-                    check_whirlpool_collisions,
-                )
-                    .run_if(in_state(GameworldState::Ocean))
+                check_whirlpool_collisions.run_if(in_state(GameworldState::Ocean))
             )
             .add_systems(OnExit(GameworldState::Ocean), cleanup_whirlpools);
     }
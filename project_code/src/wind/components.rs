@@ -4,8 +4,3 @@ use bevy::prelude::*;
 pub struct Wind {
     pub direction: Vec2,
 }
-
-#[derive(Component)]
-pub struct CountdownTimer {
-    pub timer: Timer,
-}
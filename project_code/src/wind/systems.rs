@@ -1,9 +1,12 @@
+use crate::network::components::ServerEvent;
 use crate::wind::components::*;
 use bevy::prelude::*;
+use protocol::messages::ServerMessage;
+use protocol::rng::{GameRng, RngStream};
 use rand::Rng;
 
-pub fn init_wind(mut commands: Commands) {
-    let mut rng = rand::thread_rng();
+pub fn init_wind(mut commands: Commands, mut rng: ResMut<GameRng>) {
+    let rng = rng.stream(RngStream::Wind);
     let x = rng.gen_range(0.0..360.0);
     let y = rng.gen_range(0.0..360.0);
 
@@ -12,28 +15,16 @@ pub fn init_wind(mut commands: Commands) {
     });
 }
 
-pub fn change_wind_dir(
-    time: Res<Time>,
-    mut query: Query<&mut CountdownTimer>,
-    mut wind: ResMut<Wind>,
-) {
-    for mut countdown in query.iter_mut() {
-        // Tick the timer
-        countdown.timer.tick(time.delta());
-
-        // Check if the timer has finished
-        if countdown.timer.finished() {
-            let mut rng = rand::thread_rng();
-            let x = rng.gen_range(0.0..=360.0);
-            let y = rng.gen_range(0.0..=360.0);
-            wind.direction = Vec2::new(x, y);
-            print!("changing wind {}\n", wind.direction);
+/*   SYNC_WIND FUNCTION   */
+/// The server decides which way the wind blows, since it changes how fast every
+/// boat sails. The random wind from init_wind is only used until the first snapshot
+pub fn sync_wind(mut events: EventReader<ServerEvent>, mut wind: ResMut<Wind>) {
+    for ServerEvent(message) in events.read() {
+        if let ServerMessage::Snapshot(snapshot) = message {
+            if wind.direction != snapshot.wind {
+                wind.direction = snapshot.wind;
+                debug!("changing wind {}", wind.direction);
+            }
         }
     }
 }
-
-pub fn init_timer(mut commands: Commands) {
-    let timer = Timer::from_seconds(30.0, TimerMode::Repeating);
-
-    commands.spawn(CountdownTimer { timer });
-}
@@ -0,0 +1,199 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fmt;
+
+/*   CODEC   */
+// Messages are serialized with flexbuffers and wrapped in a small frame:
+//
+//   | magic "SF" (2 bytes) | protocol version, little endian u16 (2 bytes) |
+//   | body length, little endian u32 (4 bytes) | body |
+//
+// The version is checked before anything else, so a peer on another version is
+// told so instead of its messages failing to decode. The length in the header is
+// checked against the number of bytes that actually arrived, so a datagram that
+// got cut short is rejected instead of being decoded into garbage.
+
+/// Version of the protocol, sent in every frame header. Bump this whenever a
+/// message or one of its payloads changes shape.
+pub const PROTOCOL_VERSION: u16 = 22;
+
+/// Bytes every frame starts with
+pub const FRAME_MAGIC: [u8; 2] = *b"SF";
+
+/// Size of the frame header (magic + version + body length)
+pub const FRAME_HEADER_SIZE: usize = 8;
+
+/// Largest payload a single UDP datagram can carry. Receive buffers should be
+/// at least this large so nothing gets silently truncated.
+pub const MAX_PACKET_SIZE: usize = 65_507;
+
+/// Everything that can go wrong while framing or unframing a message
+#[derive(Debug)]
+pub enum CodecError {
+    /// Fewer bytes than a frame header arrived
+    TooShort { len: usize },
+    /// The datagram doesn't start with FRAME_MAGIC, so it isn't one of ours
+    BadMagic,
+    /// The frame was sent by a peer on another protocol version
+    VersionMismatch { version: u16 },
+    /// The header promised a different body length than what arrived
+    LengthMismatch { expected: usize, actual: usize },
+    /// The encoded message doesn't fit in a single datagram
+    TooLarge { len: usize },
+    Serialize(flexbuffers::SerializationError),
+    Deserialize(flexbuffers::DeserializationError),
+}
+
+impl fmt::Display for CodecError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CodecError::TooShort { len } => write!(f, "packet too short ({} bytes)", len),
+            CodecError::BadMagic => write!(f, "packet is not a Sea of Fortune frame"),
+            CodecError::VersionMismatch { version } => write!(
+                f,
+                "packet is from protocol v{}, this side speaks v{}",
+                version, PROTOCOL_VERSION
+            ),
+            CodecError::LengthMismatch { expected, actual } => write!(
+                f,
+                "frame length mismatch (header says {} bytes, got {})",
+                expected, actual
+            ),
+            CodecError::TooLarge { len } => write!(
+                f,
+                "message is {} bytes, larger than the {} byte packet limit",
+                len, MAX_PACKET_SIZE
+            ),
+            CodecError::Serialize(e) => write!(f, "failed to serialize message: {}", e),
+            CodecError::Deserialize(e) => write!(f, "failed to deserialize message: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for CodecError {}
+
+/// Serializes a message and wraps it in a frame
+pub fn encode<T: Serialize>(message: &T) -> Result<Vec<u8>, CodecError> {
+    let body = flexbuffers::to_vec(message).map_err(CodecError::Serialize)?;
+
+    let len = FRAME_HEADER_SIZE + body.len();
+    if len > MAX_PACKET_SIZE {
+        return Err(CodecError::TooLarge { len });
+    }
+
+    let mut frame = Vec::with_capacity(len);
+    frame.extend_from_slice(&FRAME_MAGIC);
+    frame.extend_from_slice(&PROTOCOL_VERSION.to_le_bytes());
+    frame.extend_from_slice(&(body.len() as u32).to_le_bytes());
+    frame.extend_from_slice(&body);
+
+    Ok(frame)
+}
+
+/// Checks a frame and deserializes the message inside of it
+pub fn decode<T: DeserializeOwned>(frame: &[u8]) -> Result<T, CodecError> {
+    if frame.len() < FRAME_HEADER_SIZE {
+        return Err(CodecError::TooShort { len: frame.len() });
+    }
+
+    if frame[..2] != FRAME_MAGIC {
+        return Err(CodecError::BadMagic);
+    }
+
+    //before the body, whose shape may be anything on another version
+    let version = u16::from_le_bytes([frame[2], frame[3]]);
+    if version != PROTOCOL_VERSION {
+        return Err(CodecError::VersionMismatch { version });
+    }
+
+    let expected = u32::from_le_bytes([frame[4], frame[5], frame[6], frame[7]]) as usize;
+    let body = &frame[FRAME_HEADER_SIZE..];
+    if body.len() != expected {
+        return Err(CodecError::LengthMismatch {
+            expected,
+            actual: body.len(),
+        });
+    }
+
+    flexbuffers::from_slice(body).map_err(CodecError::Deserialize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messages::ClientMessage;
+
+    fn chat(text: &str) -> Vec<u8> {
+        encode(&ClientMessage::Chat(text.to_string())).unwrap()
+    }
+
+    #[test]
+    fn frames_carry_the_magic_version_and_length() {
+        let frame = chat("ahoy");
+        let body = (frame.len() - FRAME_HEADER_SIZE) as u32;
+
+        assert_eq!(frame[..2], FRAME_MAGIC);
+        assert_eq!(frame[2..4], PROTOCOL_VERSION.to_le_bytes());
+        assert_eq!(frame[4..8], body.to_le_bytes());
+        match decode(&frame) {
+            Ok(ClientMessage::Chat(text)) => assert_eq!(text, "ahoy"),
+            _ => panic!("chat didn't come back as sent"),
+        }
+    }
+
+    #[test]
+    fn short_and_foreign_datagrams_are_rejected() {
+        let frame = chat("ahoy");
+
+        assert!(matches!(
+            decode::<ClientMessage>(&frame[..FRAME_HEADER_SIZE - 1]),
+            Err(CodecError::TooShort { len: 7 })
+        ));
+
+        let mut foreign = frame.clone();
+        foreign[0] = b'X';
+        assert!(matches!(
+            decode::<ClientMessage>(&foreign),
+            Err(CodecError::BadMagic)
+        ));
+    }
+
+    #[test]
+    fn other_versions_are_rejected_before_the_body_is_looked_at() {
+        //a body this side couldn't make sense of, with a length that's wrong too
+        let mut frame = FRAME_MAGIC.to_vec();
+        frame.extend_from_slice(&(PROTOCOL_VERSION + 1).to_le_bytes());
+        frame.extend_from_slice(&1000u32.to_le_bytes());
+        frame.extend_from_slice(b"not flexbuffers");
+
+        match decode::<ClientMessage>(&frame) {
+            Err(CodecError::VersionMismatch { version }) => {
+                assert_eq!(version, PROTOCOL_VERSION + 1)
+            }
+            _ => panic!("another version got past the header"),
+        }
+    }
+
+    #[test]
+    fn cut_short_frames_are_rejected() {
+        let frame = chat("ahoy");
+        let body = frame.len() - FRAME_HEADER_SIZE;
+
+        match decode::<ClientMessage>(&frame[..frame.len() - 1]) {
+            Err(CodecError::LengthMismatch { expected, actual }) => {
+                assert_eq!((expected, actual), (body, body - 1))
+            }
+            _ => panic!("a truncated frame was decoded"),
+        }
+    }
+
+    #[test]
+    fn messages_too_big_for_a_datagram_are_not_sent() {
+        let text = "a".repeat(MAX_PACKET_SIZE);
+
+        assert!(matches!(
+            encode(&ClientMessage::Chat(text)),
+            Err(CodecError::TooLarge { .. })
+        ));
+    }
+}
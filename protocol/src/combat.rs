@@ -0,0 +1,109 @@
+use serde::{Deserialize, Serialize};
+
+/*   COMBAT   */
+// Clients decide when their attacks land, the server decides whether to believe
+// them. Every weapon's numbers live here so a damage claim can be checked
+// against what the attacker could actually have done.
+
+//projectiles
+pub const CANNONBALL_SPEED: f32 = 800.;
+pub const CANNONBALL_LIFETIME: f32 = 6.;
+pub const MUSKETBALL_SPEED: f32 = 500.;
+pub const MUSKETBALL_LIFETIME: f32 = 6.;
+
+//time between attacks, in seconds
+pub const CANNON_COOLDOWN: f32 = 1.5;
+pub const SWORD_COOLDOWN: f32 = 0.75;
+pub const MUSKET_COOLDOWN: f32 = 1.5;
+pub const DAGGER_COOLDOWN: f32 = 0.375; // Half of sword cooldown
+pub const PISTOL_COOLDOWN: f32 = 0.75; // Half of musket cooldown
+
+/// Extra distance allowed on top of a weapon's reach, for the size of the
+/// target and for the enemy having moved since the client saw it
+pub const HIT_RANGE_SLACK: f32 = 256.;
+
+/// Furthest off a player can see anything, corner to corner of the 1280x720
+/// window since the camera stops at the edge of the level. Nothing further than
+/// this could have been aimed at, however far the weapon carries
+pub const SIGHT_RANGE: f32 = 1469.;
+
+/// Hit points of a boat fresh out of port. The server counts down from this
+/// when players fire on each other
+pub const BOAT_MAX_HP: f32 = 5.;
+
+/// How many crews players can split into when PvP is on
+pub const MAX_CREWS: u8 = 4;
+
+/// Fraction of a weapon's cooldown two hits by the same player have to be
+/// apart, whatever they were hit with. Only a little is given, for projectiles
+/// flying different distances and for the link bunching claims up
+pub const HIT_COOLDOWN_TOLERANCE: f32 = 0.9;
+
+/// What a player hit an enemy with
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Weapon {
+    Cannon,
+    Sword,
+    Dagger,
+    Musket,
+    Pistol,
+}
+
+impl Weapon {
+    /// Damage one hit from this weapon does
+    pub fn damage(self) -> f32 {
+        match self {
+            Weapon::Cannon => 1.,
+            Weapon::Sword | Weapon::Dagger | Weapon::Musket | Weapon::Pistol => 2.,
+        }
+    }
+
+    /// Seconds the attacker has to wait between attacks
+    pub fn cooldown(self) -> f32 {
+        match self {
+            Weapon::Cannon => CANNON_COOLDOWN,
+            Weapon::Sword => SWORD_COOLDOWN,
+            Weapon::Dagger => DAGGER_COOLDOWN,
+            Weapon::Musket => MUSKET_COOLDOWN,
+            Weapon::Pistol => PISTOL_COOLDOWN,
+        }
+    }
+
+    /// Furthest from the attacker a hit can land, not counting HIT_RANGE_SLACK.
+    /// Projectiles fly further than that, but nobody can aim past SIGHT_RANGE
+    pub fn range(self) -> f32 {
+        let reach = match self {
+            Weapon::Cannon => CANNONBALL_SPEED * CANNONBALL_LIFETIME,
+            Weapon::Sword => 80.,
+            Weapon::Dagger => 48.,
+            Weapon::Musket | Weapon::Pistol => MUSKETBALL_SPEED * MUSKETBALL_LIFETIME,
+        };
+        reach.min(SIGHT_RANGE)
+    }
+
+    /// Whether this weapon is the boat's rather than the pirate's
+    pub fn is_boat_weapon(self) -> bool {
+        self == Weapon::Cannon
+    }
+}
+
+/// Whether and how players can fire on each other in a room. Set by the server,
+/// and sent along with the room's roster
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PvpRules {
+    /// Whether cannonballs hurt other players' boats at all
+    pub enabled: bool,
+    /// Whether cannonballs hurt boats of the same crew
+    pub friendly_fire: bool,
+    /// Gold paid to whoever sinks another player's boat
+    pub bounty: u32,
+}
+
+impl PvpRules {
+    /// Whether a player on `attacker`'s crew can hurt a player on `target`'s.
+    /// Players without a crew sail for themselves and are fair game to everyone
+    pub fn can_hurt(self, attacker: Option<u8>, target: Option<u8>) -> bool {
+        self.enabled && (self.friendly_fire || attacker.is_none() || attacker != target)
+    }
+}
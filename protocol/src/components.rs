@@ -0,0 +1,164 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::combat::Weapon;
+
+/// Hands out ids for entities that are shared between the server and the clients
+#[derive(Resource, Component)]
+pub struct Counter {
+    pub count: i32,
+}
+
+impl Counter {
+    pub fn init() -> Counter {
+        Counter { count: 5 }
+    }
+
+    pub fn next_id(&mut self) -> i32 {
+        self.count += 1;
+        self.count
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Component)]
+pub struct Player {
+    pub id: i32,
+    pub addr: String,
+    pub name: String,
+    /// Whether the player is ready to set sail, only used in the lobby
+    pub ready: bool,
+    /// Crew the player sails with when PvP is on, picked in the lobby
+    pub crew: Option<u8>,
+    pub pos: Vec3,
+    pub rot: Quat,
+    pub boat: bool,
+    pub used: bool,
+    /// Where the player is, the server only sends them what's around them
+    pub area: Area,
+}
+
+impl Default for Player {
+    fn default() -> Player {
+        Player {
+            id: -1,
+            addr: "null".to_string(),
+            name: String::new(),
+            ready: false,
+            crew: None,
+            pos: Vec3::splat(0.),
+            rot: Quat::from_rotation_x((90.0_f32).to_radians()),
+            boat: true,
+            used: false,
+            area: Area::MainMenu,
+        }
+    }
+}
+
+/// The kind of island, which decides its dungeon's tiles and enemies
+#[derive(Component, Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum IslandType {
+    Start,
+    Level1,
+    Level2,
+    Level3,
+    Boss,
+}
+
+impl fmt::Display for IslandType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IslandType::Start => write!(f, "start"),
+            IslandType::Level1 => write!(f, "level 1"),
+            IslandType::Level2 => write!(f, "level 2"),
+            IslandType::Level3 => write!(f, "level 3"),
+            IslandType::Boss => write!(f, "boss"),
+        }
+    }
+}
+
+/// Which part of the gameworld a player is in, following the client's
+/// GameworldState. Islands and dungeons are told apart by the island's zone,
+/// see OceanParams::start_island
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum Area {
+    MainMenu,
+    Ocean,
+    Island {
+        island: u32,
+        island_type: IslandType,
+    },
+    Dungeon {
+        island: u32,
+        island_type: IslandType,
+    },
+}
+
+impl fmt::Display for Area {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Area::MainMenu => write!(f, "in the menus"),
+            Area::Ocean => write!(f, "at sea"),
+            Area::Island {
+                island,
+                island_type,
+            } => write!(f, "on island {} ({})", island, island_type),
+            Area::Dungeon {
+                island,
+                island_type,
+            } => write!(
+                f,
+                "in the dungeon under island {} ({})",
+                island, island_type
+            ),
+        }
+    }
+}
+
+#[derive(Resource, Component, Clone, Serialize, Deserialize)]
+pub struct Projectiles {
+    pub list: Vec<Projectile>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Projectile {
+    pub owner_id: i32,
+    pub etype: i32,
+    pub velocity: Velocity,
+    pub translation: Vec3,
+    pub lifetime: f32,
+}
+
+#[derive(Component, Clone, Serialize, Deserialize)]
+pub struct Velocity {
+    pub v: Vec2,
+}
+
+#[derive(Resource, Serialize, Deserialize, Clone)]
+pub struct Enemies {
+    pub list: Vec<Enemy>,
+}
+
+impl Enemies {
+    pub fn init() -> Enemies {
+        Enemies { list: Vec::new() }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Component)]
+pub struct Enemy {
+    pub id: i32,
+    pub etype: i32,
+    pub pos: Vec3,
+    pub animation_index: usize,
+    pub hp: f32,
+    pub alive: bool,
+    pub target_id: i32,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Damage {
+    pub target_id: i32,
+    pub dmg: f32,
+    pub weapon: Weapon,
+}
@@ -0,0 +1,427 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+
+use crate::components::Enemy;
+
+/*   DELTA COMPRESSION   */
+// update_enemies goes out unreliably every tick to every player at sea. Rather
+// than every field of every enemy in view, it only carries what changed since
+// the newest update the client has acked, its baseline. Until there's a baseline
+// to go on, or the client has gone quiet for too long, it carries everything.
+//
+// Positions and rotations are rounded before they're sent, so both sides keep
+// exactly what went over the wire and agree on what changed.
+
+/// Smallest step a position is sent in, in world units
+pub const POSITION_STEP: f32 = 0.125;
+
+/// How many updates the server keeps waiting for acks, and the client keeps to
+/// build the next ones on. An older baseline than this is no use to either side
+pub const DELTA_HISTORY: usize = 32;
+
+/// A position rounded to POSITION_STEP
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct QuantizedPos(pub i32, pub i32, pub i32);
+
+impl From<Vec3> for QuantizedPos {
+    fn from(pos: Vec3) -> QuantizedPos {
+        let step = |v: f32| (v / POSITION_STEP).round() as i32;
+        QuantizedPos(step(pos.x), step(pos.y), step(pos.z))
+    }
+}
+
+impl From<QuantizedPos> for Vec3 {
+    fn from(pos: QuantizedPos) -> Vec3 {
+        Vec3::new(pos.0 as f32, pos.1 as f32, pos.2 as f32) * POSITION_STEP
+    }
+}
+
+/// A rotation with each part of the quaternion rounded to 1/i16::MAX
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct QuantizedRot(pub [i16; 4]);
+
+impl From<Quat> for QuantizedRot {
+    fn from(rot: Quat) -> QuantizedRot {
+        QuantizedRot(rot.to_array().map(|v| (v * i16::MAX as f32).round() as i16))
+    }
+}
+
+impl From<QuantizedRot> for Quat {
+    fn from(rot: QuantizedRot) -> Quat {
+        Quat::from_array(rot.0.map(|v| v as f32 / i16::MAX as f32)).normalize()
+    }
+}
+
+/// The fields of an enemy that changed since the baseline, the rest are left
+/// out. An enemy the baseline doesn't have comes with every field
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct EnemyChange {
+    pub id: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etype: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pos: Option<QuantizedPos>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub animation_index: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hp: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alive: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_id: Option<i32>,
+}
+
+impl EnemyChange {
+    /// What changed between `old` and `new`, None if nothing did
+    fn between(old: Option<&Enemy>, new: &Enemy) -> Option<EnemyChange> {
+        fn changed<T: PartialEq>(old: Option<T>, new: T) -> Option<T> {
+            (old.as_ref() != Some(&new)).then_some(new)
+        }
+
+        let change = EnemyChange {
+            id: new.id,
+            etype: changed(old.map(|old| old.etype), new.etype),
+            pos: changed(
+                old.map(|old| QuantizedPos::from(old.pos)),
+                QuantizedPos::from(new.pos),
+            ),
+            animation_index: changed(old.map(|old| old.animation_index), new.animation_index),
+            hp: changed(old.map(|old| old.hp), new.hp),
+            alive: changed(old.map(|old| old.alive), new.alive),
+            target_id: changed(old.map(|old| old.target_id), new.target_id),
+        };
+
+        let unchanged = change.etype.is_none()
+            && change.pos.is_none()
+            && change.animation_index.is_none()
+            && change.hp.is_none()
+            && change.alive.is_none()
+            && change.target_id.is_none();
+        (!unchanged).then_some(change)
+    }
+
+    /// Writes the fields that changed over `enemy`
+    fn apply(&self, enemy: &mut Enemy) {
+        if let Some(etype) = self.etype {
+            enemy.etype = etype;
+        }
+        if let Some(pos) = self.pos {
+            enemy.pos = pos.into();
+        }
+        if let Some(animation_index) = self.animation_index {
+            enemy.animation_index = animation_index;
+        }
+        if let Some(hp) = self.hp {
+            enemy.hp = hp;
+        }
+        if let Some(alive) = self.alive {
+            enemy.alive = alive;
+        }
+        if let Some(target_id) = self.target_id {
+            enemy.target_id = target_id;
+        }
+    }
+}
+
+/// update_enemies as it goes over the wire
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct EnemyDelta {
+    /// Numbers the update, the client acks it with ack_enemies
+    pub seq: u32,
+    /// The update this one builds on, None when it carries every enemy in full
+    pub baseline: Option<u32>,
+    pub changed: Vec<EnemyChange>,
+    /// Enemies in the baseline that are gone from view
+    pub removed: Vec<i32>,
+}
+
+/// Every enemy in an update by id, as the client ends up with it
+type EnemyView = BTreeMap<i32, Enemy>;
+
+/// The server's end of one client's enemy updates
+#[derive(Default)]
+pub struct DeltaEncoder {
+    next_seq: u32,
+    /// Updates sent since the baseline, oldest first
+    sent: VecDeque<(u32, EnemyView)>,
+    /// The newest update the client acked
+    baseline: Option<(u32, EnemyView)>,
+}
+
+impl DeltaEncoder {
+    /// The next update for the client, taking it from its baseline to `enemies`
+    pub fn encode(&mut self, enemies: &[Enemy]) -> EnemyDelta {
+        let seq = self.next_seq;
+        self.next_seq = self.next_seq.wrapping_add(1);
+
+        //the client may have forgotten a baseline this old
+        if let Some((baseline, _)) = self.baseline {
+            if seq.wrapping_sub(baseline) >= DELTA_HISTORY as u32 {
+                self.baseline = None;
+            }
+        }
+
+        let empty = EnemyView::new();
+        let (baseline, old) = match &self.baseline {
+            Some((baseline, view)) => (Some(*baseline), view),
+            None => (None, &empty),
+        };
+
+        let view: EnemyView = enemies
+            .iter()
+            .map(|enemy| {
+                let mut sent = enemy.clone();
+                sent.pos = QuantizedPos::from(enemy.pos).into();
+                (enemy.id, sent)
+            })
+            .collect();
+
+        let delta = EnemyDelta {
+            seq,
+            baseline,
+            changed: view
+                .values()
+                .filter_map(|enemy| EnemyChange::between(old.get(&enemy.id), enemy))
+                .collect(),
+            removed: old
+                .keys()
+                .filter(|id| !view.contains_key(id))
+                .copied()
+                .collect(),
+        };
+
+        self.sent.push_back((seq, view));
+        while self.sent.len() > DELTA_HISTORY {
+            self.sent.pop_front();
+        }
+        delta
+    }
+
+    /// Starts over with a full update, for a client that may have lost track.
+    /// The numbering carries on so the client can still tell old updates apart
+    pub fn forget(&mut self) {
+        self.sent.clear();
+        self.baseline = None;
+    }
+
+    /// The client has update `seq`, so the next ones can build on it
+    pub fn ack(&mut self, seq: u32) {
+        let Some(index) = self.sent.iter().position(|(sent, _)| *sent == seq) else {
+            return;
+        };
+
+        //anything older than an acked update is no use anymore
+        self.baseline = self.sent.drain(..=index).next_back();
+    }
+}
+
+/// The client's end of its enemy updates
+#[derive(Resource, Default)]
+pub struct DeltaDecoder {
+    /// The updates received lately, oldest first
+    received: VecDeque<(u32, EnemyView)>,
+}
+
+impl DeltaDecoder {
+    /// Every enemy in view as of `delta`. None when it arrived after a newer
+    /// one, or its baseline is one we don't have
+    pub fn decode(&mut self, delta: &EnemyDelta) -> Option<Vec<Enemy>> {
+        //full updates too, one from before the newest would set the view back
+        if let Some((newest, _)) = self.received.back() {
+            let ahead = delta.seq.wrapping_sub(*newest);
+            if ahead == 0 || ahead > u32::MAX / 2 {
+                return None;
+            }
+        }
+
+        //a full update stands on its own. The ones before it stay around, deltas
+        //still in flight may be built on them
+        let mut view = match delta.baseline {
+            Some(baseline) => self
+                .received
+                .iter()
+                .find(|(seq, _)| *seq == baseline)?
+                .1
+                .clone(),
+            None => EnemyView::new(),
+        };
+
+        for id in delta.removed.iter() {
+            view.remove(id);
+        }
+        for change in delta.changed.iter() {
+            let enemy = view.entry(change.id).or_insert_with(|| Enemy {
+                id: change.id,
+                etype: -1,
+                pos: Vec3::ZERO,
+                animation_index: 0,
+                hp: 0.,
+                alive: true,
+                target_id: -1,
+            });
+            change.apply(enemy);
+        }
+
+        let enemies = view.values().cloned().collect();
+        self.received.push_back((delta.seq, view));
+        while self.received.len() > DELTA_HISTORY {
+            self.received.pop_front();
+        }
+        Some(enemies)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn enemy(id: i32, x: f32) -> Enemy {
+        Enemy {
+            id,
+            etype: 3,
+            pos: Vec3::new(x, 10., 1.),
+            animation_index: 0,
+            hp: 2.,
+            alive: true,
+            target_id: -1,
+        }
+    }
+
+    /// Ids and positions, enough to tell two lists of enemies apart
+    fn poses(enemies: &[Enemy]) -> Vec<(i32, Vec3)> {
+        enemies.iter().map(|enemy| (enemy.id, enemy.pos)).collect()
+    }
+
+    #[test]
+    fn only_what_changed_since_the_acked_update_is_sent() {
+        let mut encoder = DeltaEncoder::default();
+        let mut decoder = DeltaDecoder::default();
+
+        let first = encoder.encode(&[enemy(1, 0.), enemy(2, 0.)]);
+        assert_eq!(first.baseline, None);
+        assert_eq!(first.changed.len(), 2);
+        decoder.decode(&first).unwrap();
+        encoder.ack(first.seq);
+
+        let enemies = [enemy(1, 0.), enemy(3, 5.)];
+        let second = encoder.encode(&enemies);
+        assert_eq!(second.baseline, Some(first.seq));
+        assert_eq!(second.removed, vec![2]);
+        //1 didn't change, 3 is new
+        assert_eq!(second.changed.len(), 1);
+        assert_eq!(second.changed[0].id, 3);
+        assert!(second.changed[0].etype.is_some());
+
+        let decoded = decoder.decode(&second).unwrap();
+        assert_eq!(poses(&decoded), poses(&enemies));
+    }
+
+    #[test]
+    fn stale_and_repeated_updates_are_dropped() {
+        let mut encoder = DeltaEncoder::default();
+        let mut decoder = DeltaDecoder::default();
+
+        let old = encoder.encode(&[enemy(1, 0.)]);
+        let new = encoder.encode(&[enemy(1, 8.)]);
+        assert!(decoder.decode(&new).is_some());
+
+        //full updates too, they'd set the view back
+        assert!(old.baseline.is_none());
+        assert!(decoder.decode(&old).is_none());
+        assert!(decoder.decode(&new).is_none());
+    }
+
+    #[test]
+    fn updates_on_a_baseline_we_never_got_are_dropped() {
+        let mut encoder = DeltaEncoder::default();
+        let mut decoder = DeltaDecoder::default();
+
+        //the client never got this one, but the server thinks it did
+        let lost = encoder.encode(&[enemy(1, 0.)]);
+        encoder.ack(lost.seq);
+
+        let delta = encoder.encode(&[enemy(1, 4.)]);
+        assert_eq!(delta.baseline, Some(lost.seq));
+        assert!(decoder.decode(&delta).is_none());
+
+        //starting over gets the client back on track
+        encoder.forget();
+        let full = encoder.encode(&[enemy(1, 4.)]);
+        assert_eq!(full.baseline, None);
+        assert_eq!(full.seq, delta.seq.wrapping_add(1));
+        assert_eq!(
+            poses(&decoder.decode(&full).unwrap()),
+            vec![(1, Vec3::new(4., 10., 1.))]
+        );
+    }
+
+    #[test]
+    fn deltas_still_in_flight_survive_a_full_update() {
+        let mut encoder = DeltaEncoder::default();
+        let mut decoder = DeltaDecoder::default();
+
+        let first = encoder.encode(&[enemy(1, 0.)]);
+        decoder.decode(&first).unwrap();
+        encoder.ack(first.seq);
+
+        //a full update gets there first, and the next delta still builds on the first
+        let full = DeltaEncoder {
+            next_seq: 1,
+            ..default()
+        }
+        .encode(&[enemy(1, 1.)]);
+        encoder.next_seq = 2;
+        let delta = encoder.encode(&[enemy(1, 2.)]);
+        assert_eq!(delta.baseline, Some(first.seq));
+
+        assert!(decoder.decode(&full).is_some());
+        let decoded = decoder.decode(&delta).unwrap();
+        assert_eq!(poses(&decoded), vec![(1, Vec3::new(2., 10., 1.))]);
+    }
+
+    #[test]
+    fn baselines_older_than_the_history_are_not_built_on() {
+        let mut encoder = DeltaEncoder::default();
+
+        let first = encoder.encode(&[enemy(1, 0.)]);
+        encoder.ack(first.seq);
+        for _ in 1..DELTA_HISTORY {
+            assert_eq!(encoder.encode(&[enemy(1, 0.)]).baseline, Some(first.seq));
+        }
+
+        assert_eq!(encoder.encode(&[enemy(1, 0.)]).baseline, None);
+    }
+
+    #[test]
+    fn numbering_carries_on_across_the_wrap() {
+        let mut encoder = DeltaEncoder {
+            next_seq: u32::MAX,
+            ..default()
+        };
+        let mut decoder = DeltaDecoder::default();
+
+        let before = encoder.encode(&[enemy(1, 0.)]);
+        decoder.decode(&before).unwrap();
+        encoder.ack(before.seq);
+
+        let after = encoder.encode(&[enemy(1, 3.)]);
+        assert_eq!((before.seq, after.seq), (u32::MAX, 0));
+        assert_eq!(after.baseline, Some(u32::MAX));
+        assert!(decoder.decode(&after).is_some());
+        assert!(decoder.decode(&before).is_none());
+    }
+
+    #[test]
+    fn positions_are_rounded_the_same_on_both_sides() {
+        let pos = Vec3::new(1.06, -2.3, 0.5);
+        let sent: Vec3 = QuantizedPos::from(pos).into();
+
+        assert!((sent - pos).abs().max_element() <= POSITION_STEP / 2.);
+        assert_eq!(QuantizedPos::from(sent), QuantizedPos::from(pos));
+
+        let rot = Quat::from_rotation_z(1.2);
+        let sent: Quat = QuantizedRot::from(rot).into();
+        assert!(sent.angle_between(rot) < 0.001);
+    }
+}
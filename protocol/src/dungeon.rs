@@ -0,0 +1,192 @@
+use bevy::prelude::*;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::gameworld_data::*;
+
+/*   DUNGEON GENERATION   */
+// Each island has one dungeon per room, owned by the server. The server picks
+// its seed the first time anyone goes in, and generate_dungeon() gives both
+// sides the same path from the entrance to the door and the same enemies along
+// it. The walls around the path come from the client's WFC template, which is
+// run on layout_rng() so every client ends up with the same ones.
+
+/// Tiles along each side of a dungeon
+pub const DUNGEON_SIZE: usize = 100;
+
+/// Tile the player comes in on, in the bottom left corner
+pub const DUNGEON_SPAWN: (usize, usize) = (3, 3);
+
+/// Tile of the door back out, in the top right corner
+pub const DUNGEON_DOOR: (usize, usize) = (97, 97);
+
+/// Path tiles at the start that are kept clear of enemies
+pub const DUNGEON_SAFE_TILES: usize = 10;
+
+//chance of each enemy turning up on a path tile, rolled in this order
+const DUNGEON_ROLLS: [(i32, f64); 3] = [(SKELETON, 0.01), (BAT, 0.01), (ROCK, 0.0025)];
+
+/// Everything needed to rebuild a dungeon
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct DungeonParams {
+    pub seed: u64,
+    pub width: usize,
+    pub height: usize,
+    pub spawn: (usize, usize),
+    pub door: (usize, usize),
+}
+
+impl DungeonParams {
+    /// Parameters for the standard dungeon with the given seed
+    pub fn new(seed: u64) -> DungeonParams {
+        DungeonParams {
+            seed,
+            width: DUNGEON_SIZE,
+            height: DUNGEON_SIZE,
+            spawn: DUNGEON_SPAWN,
+            door: DUNGEON_DOOR,
+        }
+    }
+
+    /// Where the middle of a tile is in the world
+    pub fn tile_position(&self, (x, y): (usize, usize)) -> Vec2 {
+        let tile = TILE_SIZE as f32;
+        Vec2::new(
+            -(self.width as f32) * tile + x as f32 * tile * 2. + tile,
+            -(self.height as f32) * tile + y as f32 * tile * 2. + tile,
+        )
+    }
+}
+
+/// An enemy placed in a dungeon. Ids count up from 0 in each dungeon
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct DungeonSpawn {
+    pub id: i32,
+    pub etype: i32,
+    pub position: Vec2,
+}
+
+/// The path and enemies of a generated dungeon
+#[derive(Clone, Debug, PartialEq)]
+pub struct DungeonLayout {
+    /// Every tile of the path from the spawn to the door, row by row from the bottom
+    pub path: Vec<(usize, usize)>,
+    pub enemies: Vec<DungeonSpawn>,
+}
+
+/// A dungeon as the server keeps it, sent to everyone going in
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DungeonInstance {
+    /// Zone of the island it's under, see OceanParams::start_island
+    pub island: u32,
+    pub params: DungeonParams,
+    /// The enemies nobody has killed yet
+    pub enemies: Vec<DungeonSpawn>,
+}
+
+impl DungeonInstance {
+    /// A dungeon nobody has been in yet, with all of its enemies
+    pub fn new(island: u32, params: DungeonParams) -> DungeonInstance {
+        DungeonInstance {
+            island,
+            params,
+            enemies: generate_dungeon(&params).enemies,
+        }
+    }
+
+    /// Whether every enemy in it has been killed
+    pub fn cleared(&self) -> bool {
+        self.enemies.is_empty()
+    }
+}
+
+/*   GENERATE_DUNGEON FUNCTION   */
+/// Walks a random path from the spawn to the door and places enemies along it.
+/// The same parameters always produce the same layout.
+pub fn generate_dungeon(params: &DungeonParams) -> DungeonLayout {
+    let mut rng = ChaCha8Rng::seed_from_u64(params.seed);
+    let mut grid = vec![false; params.width * params.height];
+    let (start, end) = (params.spawn, params.door);
+    let mut current = start;
+
+    //step towards the door, at random along x or y until one of them lines up
+    while current != end {
+        grid[current.1 * params.width + current.0] = true;
+
+        let step_x = current.1 == end.1 || (current.0 != end.0 && rng.gen_bool(0.5));
+        if step_x {
+            current.0 = if end.0 > current.0 {
+                current.0 + 1
+            } else {
+                current.0 - 1
+            };
+        } else {
+            current.1 = if end.1 > current.1 {
+                current.1 + 1
+            } else {
+                current.1 - 1
+            };
+        }
+    }
+    grid[end.1 * params.width + end.0] = true;
+
+    let path: Vec<(usize, usize)> = grid
+        .iter()
+        .enumerate()
+        .filter(|(_, &ground)| ground)
+        .map(|(i, _)| (i % params.width, i / params.width))
+        .collect();
+
+    let mut enemies = Vec::new();
+
+    for tile in path.iter().skip(DUNGEON_SAFE_TILES) {
+        let rolled = DUNGEON_ROLLS
+            .iter()
+            .find(|(_, chance)| rng.gen_bool(*chance));
+
+        if let Some((etype, _)) = rolled {
+            enemies.push(DungeonSpawn {
+                id: enemies.len() as i32,
+                etype: *etype,
+                position: params.tile_position(*tile),
+            });
+        }
+    }
+
+    DungeonLayout { path, enemies }
+}
+
+/// Random numbers for the client's WFC pass over a dungeon. Kept apart from the
+/// ones generate_dungeon() uses, so the path doesn't depend on the template
+pub fn layout_rng(params: &DungeonParams) -> ChaCha8Rng {
+    let mut rng = ChaCha8Rng::seed_from_u64(params.seed);
+    rng.set_stream(1);
+    rng
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_same_seed_builds_the_same_dungeon() {
+        let params = DungeonParams::new(1234);
+        let dungeon = generate_dungeon(&params);
+
+        assert_eq!(dungeon, generate_dungeon(&DungeonParams::new(1234)));
+        assert_eq!(
+            DungeonInstance::new(2, params).enemies,
+            DungeonInstance::new(2, DungeonParams::new(1234)).enemies
+        );
+        assert_eq!(dungeon.path.first(), Some(&params.spawn));
+        assert_eq!(dungeon.path.last(), Some(&params.door));
+    }
+
+    #[test]
+    fn another_seed_builds_another_dungeon() {
+        let dungeon = generate_dungeon(&DungeonParams::new(1234));
+
+        assert_ne!(dungeon, generate_dungeon(&DungeonParams::new(4321)));
+    }
+}
@@ -0,0 +1,22 @@
+//setting level constants
+pub const TILE_SIZE: u32 = 32;
+
+//REMEMBER TO CHANGE THIS WHEN WE CHANGE MAP SIZE
+pub const OCEAN_LEVEL_H: f32 = 4000.;
+pub const OCEAN_LEVEL_W: f32 = 4000.;
+pub const OCEAN_H_CENTER: f32 = OCEAN_LEVEL_H / 2.;
+pub const OCEAN_W_CENTER: f32 = OCEAN_LEVEL_W / 2.;
+
+//ENTITIES
+pub const PLAYER: i32 = 0;
+pub const BOAT: i32 = 1;
+pub const BAT: i32 = 2;
+pub const KRAKEN: i32 = 3;
+pub const GHOSTSHIP: i32 = 4;
+pub const ROCK: i32 = 5;
+pub const SKELETON: i32 = 6;
+pub const SKEL2: i32 = 7;
+pub const WHIRLPOOL: i32 = 8;
+pub const BOSS: i32 = 9;
+pub const STORM: i32 = 10;
+pub const PSKELETON: i32 = 11;
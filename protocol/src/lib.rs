@@ -0,0 +1,17 @@
+//! Everything the server and the game client need to agree on lives in this crate:
+//! the types that go over the socket, the messages that carry them, the entity
+//! codes and the dimensions of the ocean. Both binaries depend on it, so the two
+//! sides can no longer drift apart.
+
+pub mod codec;
+pub mod combat;
+pub mod components;
+pub mod delta;
+pub mod dungeon;
+pub mod gameworld_data;
+pub mod messages;
+pub mod netsim;
+pub mod ocean;
+pub mod reliable;
+pub mod rng;
+pub mod simulation;
@@ -0,0 +1,296 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::Duration;
+
+use crate::combat::{PvpRules, Weapon};
+use crate::components::{Area, Damage, Enemies, Player, Projectiles};
+use crate::delta::{EnemyDelta, QuantizedPos, QuantizedRot};
+use crate::dungeon::{DungeonInstance, DungeonSpawn};
+use crate::ocean::OceanParams;
+use crate::simulation::{BoatInput, BoatState};
+
+/*   PROTOCOL   */
+// Every message that crosses the socket is a variant of one of the two enums below,
+// so a misspelled message name or a payload mismatch is a compile error on both
+// sides instead of a runtime unwrap() panic.
+//
+// A message is tagged as {"message": "<name>", "payload": <data>}, wrapped in a
+// reliable::Packet and then framed and encoded by the codec module.
+
+/// How often a client tells the server it's still there, even when it has
+/// nothing else to say
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Longest player name the server keeps, anything after is cut off
+pub const MAX_NAME_LENGTH: usize = 16;
+
+/// Longest chat message the server passes on, anything after is cut off
+pub const MAX_CHAT_LENGTH: usize = 200;
+
+/// Messages a client sends to the server
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "message", content = "payload", rename_all = "snake_case")]
+pub enum ClientMessage {
+    NewPlayer {
+        player: Player,
+        room: RoomChoice,
+    },
+    /// Asks for a dropped player's slot back, with the token from joined_lobby
+    Rejoin {
+        room: String,
+        id: i32,
+        token: u64,
+    },
+    ListRooms,
+    SetReady(bool),
+    /// Joins one of the crews, or none, only while the room is in its lobby
+    SetCrew(Option<u8>),
+    /// Sent by the room's host to leave the lobby and set sail
+    StartSession,
+    PlayerLeave(Player),
+    PlayerUpdate(Player),
+    /// Setting sail, the server puts the boat in the water at BOAT_SPAWN_POSITION.
+    /// Ignored while the player's boat is still afloat
+    BoatSpawned,
+    /// Sunk by an enemy, the boat is out of the water until the next boat_spawned
+    BoatSank,
+    BoatInputs(Vec<BoatInput>),
+    EnemyDamaged(Damage),
+    /// Swapped pirate weapons, hits from now on are checked against this one
+    EquipWeapon(Weapon),
+    /// Hit another player's boat, target_id is their player id
+    PlayerDamaged(Damage),
+    GotHereLate(Player),
+    /// Sailed off, landed or went underground
+    AreaChanged(Area),
+    /// Something to say to everyone in the room
+    Chat(String),
+    /// Going down into the dungeon of an island, by the island's zone
+    EnterDungeon { island: u32 },
+    /// Killed one of the enemies in a dungeon, loot only comes once the server agrees
+    DungeonEnemyKilled { island: u32, id: i32 },
+    /// Got the update_enemies with this seq, later ones can build on it
+    AckEnemies(u32),
+    Heartbeat,
+}
+
+/// Messages the server sends to a client
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "message", content = "payload", rename_all = "snake_case")]
+pub enum ServerMessage {
+    JoinedLobby {
+        id: i32,
+        /// Code other players can use to join the same room
+        room: String,
+        ocean: OceanParams,
+        /// Simulation ticks per second, the client has to predict at the same rate
+        tick_rate: f64,
+        /// Proves it's the same player when rejoining after a dropped connection
+        token: u64,
+    },
+    JoinRejected(RejectReason),
+    RoomList(Vec<RoomInfo>),
+    Roster(Roster),
+    SessionStarted,
+    LeaveSuccess,
+    PlayerLeft { id: i32 },
+    Snapshot(Box<Snapshot>),
+    /// Every enemy in view, as a delta from the last update the client acked
+    UpdateEnemies(EnemyDelta),
+    NewEnemies(Enemies),
+    DeadEnemies(Enemies),
+    UpdateProjectiles(Projectiles),
+    /// A player's boat was hit by another player for `dmg`, `hp` is what it has
+    /// left as far as other players' hits go
+    BoatDamaged { id: i32, by: i32, dmg: f32, hp: f32 },
+    BoatSunk(Sinking),
+    Chat(ChatLine),
+    /// The last chat message was dropped for coming too soon after the others
+    ChatThrottled { retry_in: f32 },
+    /// A message from whoever runs the server, to everyone on it
+    Announcement(String),
+    /// Gold handed out by whoever runs the server
+    GoldGiven { amount: u32 },
+    /// Taken out of the room by the server, the client should go back to the menu
+    Kicked { reason: String },
+    /// The dungeon the player asked to go into, with the enemies still left in it
+    DungeonEntered(DungeonInstance),
+    DungeonEnemyDied(DungeonKill),
+}
+
+/// A chat message passed on by the server, with the name of the player who sent it
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ChatLine {
+    pub from: i32,
+    pub name: String,
+    pub text: String,
+}
+
+/// A player's boat sunk by another player, who is paid `bounty` gold for it
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Sinking {
+    pub id: i32,
+    pub name: String,
+    pub by: i32,
+    pub by_name: String,
+    pub bounty: u32,
+}
+
+/// An enemy in a dungeon killed by a player, who is the only one it drops loot for
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DungeonKill {
+    pub island: u32,
+    pub enemy: DungeonSpawn,
+    pub by: i32,
+}
+
+/// The state of the lobby at the end of a server tick
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Snapshot {
+    pub tick: u32,
+    pub wind: Vec2,
+    /// Everyone in the lobby, empty slots are left out
+    pub players: Vec<PlayerPose>,
+    pub boats: Vec<BoatSnapshot>,
+}
+
+/// Where a player is and which way they face, all a snapshot needs of them
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PlayerPose {
+    pub id: i32,
+    pub pos: QuantizedPos,
+    pub rot: QuantizedRot,
+    pub boat: bool,
+    pub area: Area,
+}
+
+impl From<&Player> for PlayerPose {
+    fn from(player: &Player) -> PlayerPose {
+        PlayerPose {
+            id: player.id,
+            pos: player.pos.into(),
+            rot: player.rot.into(),
+            boat: player.boat,
+            area: player.area,
+        }
+    }
+}
+
+/// The authoritative state of one player's boat, along with the last input of
+/// theirs that went into it
+#[derive(Serialize, Deserialize, Clone)]
+pub struct BoatSnapshot {
+    pub id: i32,
+    pub state: BoatState,
+    pub last_input: u32,
+}
+
+/// Which room a new player wants to be put in
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum RoomChoice {
+    /// Any room with space, a new one is opened if they're all full
+    #[default]
+    Any,
+    /// A new room, on the given ocean seed if there is one
+    Create { seed: Option<u64> },
+    /// The room with this code
+    Join { code: String },
+}
+
+/// One open room, as listed in room_list
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RoomInfo {
+    pub code: String,
+    pub players: usize,
+    pub capacity: usize,
+}
+
+/// Everyone in a room as shown in the lobby, sent whenever someone joins,
+/// leaves, changes their ready state or goes somewhere else
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct Roster {
+    /// Id of the player who can start the session
+    pub host: i32,
+    pub started: bool,
+    pub pvp: PvpRules,
+    pub players: Vec<RosterEntry>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RosterEntry {
+    pub id: i32,
+    pub name: String,
+    pub ready: bool,
+    pub crew: Option<u8>,
+    pub area: Area,
+}
+
+impl Roster {
+    /// Whether the host is allowed to start the session
+    pub fn all_ready(&self) -> bool {
+        self.players.iter().all(|player| player.ready)
+    }
+
+    /// Whether `attacker`'s cannonballs can hurt `target`'s boat under the
+    /// room's PvP rules. The server checks again before believing a hit
+    pub fn hostile(&self, attacker: i32, target: i32) -> bool {
+        let crew = |id| {
+            self.players
+                .iter()
+                .find(|player| player.id == id)
+                .map(|player| player.crew)
+        };
+
+        match (crew(attacker), crew(target)) {
+            (Some(attacker_crew), Some(target_crew)) => {
+                attacker != target && self.pvp.can_hurt(attacker_crew, target_crew)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Why the server turned down a new_player or rejoin request
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum RejectReason {
+    LobbyFull { capacity: usize, current: usize },
+    VersionMismatch { server: u16, client: u16 },
+    NoSuchRoom { code: String },
+    TooManyRooms { max: usize },
+    /// The slot a rejoin asked for has been given up on, or was never theirs
+    SlotLost,
+}
+
+impl fmt::Display for RejectReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RejectReason::LobbyFull { capacity, current } => write!(
+                f,
+                "Lobby is full ({}/{} players), cannot join right now. Try again later!",
+                current, capacity
+            ),
+            RejectReason::VersionMismatch { server, client } => write!(
+                f,
+                "Protocol version mismatch: server speaks v{}, this client speaks v{}. \
+                 Update your game to join this server.",
+                server, client
+            ),
+            RejectReason::NoSuchRoom { code } => {
+                write!(f, "There is no room with the code {}.", code)
+            }
+            RejectReason::TooManyRooms { max } => write!(
+                f,
+                "The server already has {} rooms open, cannot create another one. \
+                 Join an existing room or try again later!",
+                max
+            ),
+            RejectReason::SlotLost => write!(
+                f,
+                "Your place in the room was given away while you were gone. \
+                 Join the room again to keep playing."
+            ),
+        }
+    }
+}
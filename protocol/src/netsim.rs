@@ -0,0 +1,289 @@
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::collections::HashMap;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::codec::MAX_PACKET_SIZE;
+
+/*   NETWORK SIMULATION   */
+// A UDP proxy that sits between clients and the server and makes the link
+// between them worse on purpose. Clients talk to the proxy instead of the
+// server, and every datagram in either direction can be dropped, held back,
+// delivered twice or overtaken by the ones after it. Neither side needs to know
+// it's there, so the game and the server are tested exactly as they ship.
+//
+// The proxy rolls its dice with a seeded ChaCha8, so a test that fails on a bad
+// link fails the same way every time it's run with the same seed.
+
+/// Extra hold given to datagrams picked to be overtaken, on top of their delay
+pub const REORDER_DELAY: Duration = Duration::from_millis(50);
+
+/// How long the proxy sleeps when there's nothing to forward
+const IDLE_SLEEP: Duration = Duration::from_millis(1);
+
+/// How bad the simulated link is. Applied to each direction on its own
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LinkConditions {
+    /// Chance of a datagram being dropped, from 0 to 1
+    pub loss: f32,
+    /// Delay every datagram gets
+    pub latency: Duration,
+    /// Up to this much extra delay, picked at random for every datagram
+    pub jitter: Duration,
+    /// Chance of a datagram being delivered twice
+    pub duplicate: f32,
+    /// Chance of a datagram being held back by REORDER_DELAY so the ones sent
+    /// after it arrive first
+    pub reorder: f32,
+}
+
+impl LinkConditions {
+    /// A link that only adds delay
+    pub fn delay(latency: Duration, jitter: Duration) -> LinkConditions {
+        LinkConditions {
+            latency,
+            jitter,
+            ..Default::default()
+        }
+    }
+
+    /// Checks every chance is between 0 and 1
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, chance) in [
+            ("loss", self.loss),
+            ("duplicate", self.duplicate),
+            ("reorder", self.reorder),
+        ] {
+            //written so NaN fails too
+            if !(0. ..=1.).contains(&chance) {
+                return Err(format!("{} must be between 0 and 1, got {}", name, chance));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// What the proxy has done to the datagrams passing through it so far
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LinkStats {
+    /// Datagrams that came in, from either side
+    pub received: u64,
+    pub dropped: u64,
+    pub duplicated: u64,
+    pub reordered: u64,
+    /// Datagrams sent on, duplicates included
+    pub delivered: u64,
+}
+
+/// Which way a datagram is going, and for which client
+#[derive(Clone, Copy)]
+enum Leg {
+    ToServer(SocketAddr),
+    ToClient(SocketAddr),
+}
+
+/// A datagram waiting out its delay
+struct InFlight {
+    at: Instant,
+    leg: Leg,
+    data: Vec<u8>,
+}
+
+/// A running proxy. Stopped when dropped
+pub struct LinkProxy {
+    addr: SocketAddr,
+    stats: Arc<Mutex<LinkStats>>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl LinkProxy {
+    /// Starts forwarding between clients sending to `listen` and the server at
+    /// `server`. Every client gets its own socket towards the server, so the
+    /// server sees each of them at a different address
+    pub fn spawn(
+        listen: SocketAddr,
+        server: SocketAddr,
+        conditions: LinkConditions,
+        seed: u64,
+    ) -> io::Result<LinkProxy> {
+        conditions
+            .validate()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+
+        let downstream = UdpSocket::bind(listen)?;
+        downstream.set_nonblocking(true)?;
+        let addr = downstream.local_addr()?;
+
+        let stats = Arc::new(Mutex::new(LinkStats::default()));
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let mut link = Link {
+            downstream,
+            upstream: HashMap::new(),
+            server,
+            conditions,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+            in_flight: Vec::new(),
+            stats: stats.clone(),
+        };
+        let stopped = stop.clone();
+        let thread = thread::Builder::new()
+            .name("netsim".to_string())
+            .spawn(move || {
+                while !stopped.load(Ordering::Relaxed) {
+                    if !link.step() {
+                        thread::sleep(IDLE_SLEEP);
+                    }
+                }
+            })?;
+
+        Ok(LinkProxy {
+            addr,
+            stats,
+            stop,
+            thread: Some(thread),
+        })
+    }
+
+    /// Address clients should send to instead of the server's
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    pub fn stats(&self) -> LinkStats {
+        *self.stats.lock().unwrap()
+    }
+}
+
+impl Drop for LinkProxy {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// The proxy's side of the link, owned by its thread
+struct Link {
+    downstream: UdpSocket,
+    /// Socket towards the server for every client, keyed by the client's address
+    upstream: HashMap<SocketAddr, UdpSocket>,
+    server: SocketAddr,
+    conditions: LinkConditions,
+    rng: ChaCha8Rng,
+    /// Sorted by when each datagram is due
+    in_flight: Vec<InFlight>,
+    stats: Arc<Mutex<LinkStats>>,
+}
+
+impl Link {
+    /// Takes in everything waiting on the sockets and sends on everything whose
+    /// delay is up. Returns whether there was anything to do
+    fn step(&mut self) -> bool {
+        let mut busy = false;
+        let mut buf = vec![0; MAX_PACKET_SIZE];
+
+        while let Ok((bytes, client)) = self.downstream.recv_from(&mut buf) {
+            if !self.upstream.contains_key(&client) {
+                let Ok(socket) = bind_towards(self.server) else {
+                    continue;
+                };
+                self.upstream.insert(client, socket);
+            }
+            self.admit(Leg::ToServer(client), &buf[..bytes]);
+            busy = true;
+        }
+
+        let mut replies = Vec::new();
+        for (client, socket) in self.upstream.iter() {
+            while let Ok((bytes, _)) = socket.recv_from(&mut buf) {
+                replies.push((*client, buf[..bytes].to_vec()));
+            }
+        }
+        for (client, data) in replies {
+            self.admit(Leg::ToClient(client), &data);
+            busy = true;
+        }
+
+        //in_flight is kept in delivery order, so whatever is due is at the front
+        let due = self
+            .in_flight
+            .partition_point(|datagram| datagram.at <= Instant::now());
+        for datagram in self.in_flight.drain(..due) {
+            let sent = match datagram.leg {
+                Leg::ToServer(client) => self
+                    .upstream
+                    .get(&client)
+                    .map(|socket| socket.send_to(&datagram.data, self.server)),
+                Leg::ToClient(client) => Some(self.downstream.send_to(&datagram.data, client)),
+            };
+            if let Some(Ok(_)) = sent {
+                self.stats.lock().unwrap().delivered += 1;
+            }
+            busy = true;
+        }
+
+        busy
+    }
+
+    /// Rolls the dice for a datagram that just came in and queues whatever
+    /// survives
+    fn admit(&mut self, leg: Leg, data: &[u8]) {
+        let mut stats = self.stats.lock().unwrap();
+        stats.received += 1;
+
+        if self.rng.gen::<f32>() < self.conditions.loss {
+            stats.dropped += 1;
+            return;
+        }
+
+        let copies = if self.rng.gen::<f32>() < self.conditions.duplicate {
+            stats.duplicated += 1;
+            2
+        } else {
+            1
+        };
+
+        for _ in 0..copies {
+            let mut delay =
+                self.conditions.latency + self.conditions.jitter.mul_f32(self.rng.gen());
+            if self.rng.gen::<f32>() < self.conditions.reorder {
+                stats.reordered += 1;
+                delay += REORDER_DELAY;
+            }
+
+            //after anything due at the same time, so equal delays keep their order
+            let at = Instant::now() + delay;
+            let index = self.in_flight.partition_point(|datagram| datagram.at <= at);
+            self.in_flight.insert(
+                index,
+                InFlight {
+                    at,
+                    leg,
+                    data: data.to_vec(),
+                },
+            );
+        }
+    }
+}
+
+/// A non-blocking socket on any local address that can reach `server`
+fn bind_towards(server: SocketAddr) -> io::Result<UdpSocket> {
+    let any = match server.ip() {
+        IpAddr::V4(ip) if ip.is_loopback() => IpAddr::V4(Ipv4Addr::LOCALHOST),
+        IpAddr::V4(_) => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+        IpAddr::V6(ip) if ip.is_loopback() => IpAddr::V6(Ipv6Addr::LOCALHOST),
+        IpAddr::V6(_) => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+    };
+
+    let socket = UdpSocket::bind(SocketAddr::new(any, 0))?;
+    socket.set_nonblocking(true)?;
+    Ok(socket)
+}
@@ -0,0 +1,150 @@
+use bevy::prelude::*;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::gameworld_data::*;
+
+/*   OCEAN GENERATION   */
+// The server picks a seed and sends it to every client along with the rest of
+// the generation parameters. Both sides then run generate_ocean() and end up
+// with the exact same tiles and islands. ChaCha8 is used instead of StdRng
+// because its output is guaranteed to be the same on every platform.
+
+/// Everything needed to rebuild the ocean overworld
+#[derive(Resource, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct OceanParams {
+    pub seed: u64,
+    pub width: f32,
+    pub height: f32,
+    pub tile_size: u32,
+    pub island_count: u32,
+}
+
+impl OceanParams {
+    /// Parameters for the standard ocean with the given seed
+    pub fn new(seed: u64) -> OceanParams {
+        OceanParams {
+            seed,
+            width: OCEAN_LEVEL_W,
+            height: OCEAN_LEVEL_H,
+            tile_size: TILE_SIZE,
+            island_count: 4,
+        }
+    }
+
+    /// What the starting island goes by wherever islands are told apart by
+    /// zone. It isn't in one, so it takes the number after the last zone
+    pub fn start_island(&self) -> u32 {
+        self.island_count
+    }
+}
+
+/// A single tile of the ocean background
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct OceanT {
+    pub translation: Vec3,
+    pub tile_index: usize,
+}
+
+/// Where an island sits. Islands are placed one per horizontal zone, starting
+/// from the bottom of the ocean, and the zone decides how hard the island is.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct IslandSpawn {
+    pub zone: u32,
+    pub position: Vec2,
+}
+
+/// The generated ocean overworld
+#[derive(Clone, Debug, PartialEq)]
+pub struct OceanLayout {
+    pub tiles: Vec<OceanT>,
+    pub islands: Vec<IslandSpawn>,
+}
+
+/*   GENERATE_OCEAN FUNCTION   */
+/// Builds the ocean tiles and island placements for the given parameters. The
+/// same parameters always produce the same layout.
+pub fn generate_ocean(params: &OceanParams) -> OceanLayout {
+    let mut rng = ChaCha8Rng::seed_from_u64(params.seed);
+    let tile_size = params.tile_size as f32;
+    let w_center = params.width / 2.;
+    let h_center = params.height / 2.;
+
+    //creating width and height
+    let mut w = 0;
+    let mut h = 0;
+
+    //creating vec3 to store tile translation
+    let mut t = Vec3::new(-w_center + tile_size / 2., -h_center + tile_size / 2., 0.);
+
+    let mut tiles = Vec::new();
+
+    while (h as f32) * tile_size < params.height {
+        while (w as f32) * tile_size < params.width {
+            // weigh it so that its mostly dark blue just for aesthetic reasons
+            let rand = rng.gen_range(0..=10);
+            let tile_index = if rand < 9 { 0 } else { 1 };
+
+            tiles.push(OceanT {
+                translation: t,
+                tile_index,
+            });
+
+            //incrementing
+            w += 1;
+            t += Vec3::new(tile_size * 2., 0., 0.);
+        }
+
+        //incrementing
+        w = 0;
+        t += Vec3::new(0., tile_size * 2., 0.);
+        t.x = -w_center + (tile_size * 2.) / 2.0;
+        h += 1;
+    }
+
+    // one island per zone, zones split the ocean from bottom to top
+    let zone_size = Vec2::new(
+        params.width / params.island_count as f32,
+        params.height / params.island_count as f32,
+    );
+
+    let mut islands = Vec::new();
+
+    for zone in 0..params.island_count {
+        let zone_y = zone as f32 * zone_size.y;
+
+        let rand_x = rng.gen_range(-w_center + 64.0..w_center - 64.0);
+        let rand_y = rng.gen_range(-h_center + zone_y..(-h_center + zone_y + zone_size.y) - 128.0);
+
+        islands.push(IslandSpawn {
+            zone,
+            position: Vec2::new(rand_x, rand_y),
+        });
+    }
+
+    OceanLayout { tiles, islands }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_same_seed_builds_the_same_ocean() {
+        let params = OceanParams::new(1234);
+        let ocean = generate_ocean(&params);
+
+        assert_eq!(ocean, generate_ocean(&params));
+        assert_eq!(ocean, generate_ocean(&OceanParams::new(1234)));
+        assert!(!ocean.tiles.is_empty());
+        assert!(!ocean.islands.is_empty());
+    }
+
+    #[test]
+    fn another_seed_builds_another_ocean() {
+        let ocean = generate_ocean(&OceanParams::new(1234));
+
+        assert_ne!(ocean, generate_ocean(&OceanParams::new(4321)));
+    }
+}
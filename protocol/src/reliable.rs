@@ -0,0 +1,477 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io;
+use std::net::{SocketAddr, UdpSocket};
+use std::time::{Duration, Instant};
+
+use crate::codec::{self, CodecError};
+
+/*   RELIABILITY LAYER   */
+// Plain UDP loses, duplicates and reorders packets. That's fine for position
+// updates, which are stale by the time they'd be resent anyway, but messages like
+// joined_lobby, leave_success, enemy damage or an enemy dying have to arrive, and
+// in the order they were sent.
+//
+// Every message is sent on one of two channels:
+// * Unreliable - sent once, delivered whenever (and if ever) it shows up
+// * Reliable   - numbered, resent until the other side acks it, and handed to the
+//                game exactly once and in order
+
+/// How long to wait for an ack before resending a reliable packet
+pub const RESEND_INTERVAL: Duration = Duration::from_millis(200);
+
+/// How many times a reliable packet is resent before the other side is given up on
+pub const MAX_RESENDS: u32 = 25;
+
+/// How far ahead of the next expected packet a reliable packet can be and still
+/// be held on to. Anything further ahead isn't acked, so it's resent once the
+/// gap has been filled
+pub const MAX_OUT_OF_ORDER: u32 = 256;
+
+/// Which guarantees a message is sent with
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Channel {
+    Reliable,
+    Unreliable,
+}
+
+/// What actually goes over the wire: a message plus the bookkeeping needed to
+/// deliver it, or an acknowledgement for a reliable message
+#[derive(Serialize, Deserialize)]
+pub enum Packet<M> {
+    Unreliable(M),
+    Reliable { seq: u32, message: M },
+    Ack { seq: u32 },
+}
+
+/// A reliable packet waiting to be acked
+struct Pending {
+    frame: Vec<u8>,
+    last_sent: Instant,
+    resends: u32,
+}
+
+/// Bytes that went over the wire to and from one peer, acks and resends included
+#[derive(Clone, Copy, Debug)]
+pub struct Traffic {
+    pub sent: u64,
+    pub received: u64,
+    /// When the connection was opened
+    pub since: Instant,
+}
+
+impl Traffic {
+    /// Average bytes per second sent and received since the connection was opened
+    pub fn rates(&self) -> (f64, f64) {
+        let secs = self.since.elapsed().as_secs_f64().max(1.);
+        (self.sent as f64 / secs, self.received as f64 / secs)
+    }
+}
+
+/// Reliability state for one peer. `In` is the type of message this side
+/// receives (ClientMessage on the server, ServerMessage on the client)
+pub struct Connection<In> {
+    next_seq: u32,
+    unacked: BTreeMap<u32, Pending>,
+    next_expected: u32,
+    out_of_order: BTreeMap<u32, In>,
+    traffic: Traffic,
+    gave_up: bool,
+}
+
+impl<In: DeserializeOwned> Default for Connection<In> {
+    fn default() -> Self {
+        Connection::new()
+    }
+}
+
+impl<In: DeserializeOwned> Connection<In> {
+    pub fn new() -> Connection<In> {
+        Connection {
+            next_seq: 0,
+            unacked: BTreeMap::new(),
+            next_expected: 0,
+            out_of_order: BTreeMap::new(),
+            traffic: Traffic {
+                sent: 0,
+                received: 0,
+                since: Instant::now(),
+            },
+            gave_up: false,
+        }
+    }
+
+    /*   SEND FUNCTION   */
+    /// Sends a message to the peer. Reliable messages are kept around and resent
+    /// by resend() until the peer acks them
+    pub fn send<Out: Serialize>(
+        &mut self,
+        socket: &UdpSocket,
+        addr: SocketAddr,
+        message: &Out,
+        channel: Channel,
+    ) -> Result<(), SendError> {
+        match channel {
+            Channel::Unreliable => {
+                let frame = codec::encode(&Packet::Unreliable(message))?;
+                self.traffic.sent += frame.len() as u64;
+                socket.send_to(&frame, addr)?;
+            }
+            Channel::Reliable => {
+                let seq = self.next_seq;
+                self.next_seq = self.next_seq.wrapping_add(1);
+
+                let frame = codec::encode(&Packet::Reliable { seq, message })?;
+                self.traffic.sent += frame.len() as u64;
+
+                // keep the packet even if this send fails, resend() will retry it
+                let result = socket.send_to(&frame, addr);
+                self.unacked.insert(
+                    seq,
+                    Pending {
+                        frame,
+                        last_sent: Instant::now(),
+                        resends: 0,
+                    },
+                );
+                result?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /*   RECEIVE FUNCTION   */
+    /// Handles a frame received from the peer. Acks reliable packets and returns
+    /// every message that is now ready for the game, in order. Duplicates and
+    /// acks produce no messages
+    pub fn receive(
+        &mut self,
+        socket: &UdpSocket,
+        addr: SocketAddr,
+        frame: &[u8],
+    ) -> Result<Vec<In>, CodecError> {
+        let mut ready = Vec::new();
+        self.traffic.received += frame.len() as u64;
+
+        match codec::decode::<Packet<In>>(frame)? {
+            Packet::Unreliable(message) => ready.push(message),
+            Packet::Ack { seq } => {
+                self.unacked.remove(&seq);
+            }
+            Packet::Reliable { seq, message } => {
+                //how far past the next expected packet this one is, counting
+                //around the wrap. The upper half of the range is behind it
+                let ahead = seq.wrapping_sub(self.next_expected);
+                let duplicate = ahead > u32::MAX / 2;
+
+                //too far ahead to hold on to, the peer resends it if it isn't acked
+                if !duplicate && ahead >= MAX_OUT_OF_ORDER {
+                    return Ok(ready);
+                }
+
+                // always ack, even duplicates, in case our previous ack got lost
+                if let Ok(ack) = codec::encode(&Packet::<()>::Ack { seq }) {
+                    self.traffic.sent += ack.len() as u64;
+                    let _ = socket.send_to(&ack, addr);
+                }
+
+                if ahead == 0 {
+                    ready.push(message);
+                    self.next_expected = self.next_expected.wrapping_add(1);
+
+                    // anything that arrived early can go out now too
+                    while let Some(message) = self.out_of_order.remove(&self.next_expected) {
+                        ready.push(message);
+                        self.next_expected = self.next_expected.wrapping_add(1);
+                    }
+                } else if !duplicate {
+                    self.out_of_order.entry(seq).or_insert(message);
+                }
+                // anything behind next_expected is a duplicate we already delivered
+            }
+        }
+
+        Ok(ready)
+    }
+
+    /*   RESEND FUNCTION   */
+    /// Resends every reliable packet that hasn't been acked within RESEND_INTERVAL.
+    /// Once a packet goes unacked after MAX_RESENDS the peer is given up on, see
+    /// gave_up, and the number of packets dropped with it is returned
+    pub fn resend(&mut self, socket: &UdpSocket, addr: SocketAddr, now: Instant) -> usize {
+        //everything after a lost packet would wait on it forever, so one is all it takes
+        if self
+            .unacked
+            .values()
+            .any(|pending| pending.resends >= MAX_RESENDS)
+        {
+            self.gave_up = true;
+            let dropped = self.unacked.len();
+            self.unacked.clear();
+            return dropped;
+        }
+
+        for pending in self.unacked.values_mut() {
+            if now.duration_since(pending.last_sent) < RESEND_INTERVAL {
+                continue;
+            }
+
+            let _ = socket.send_to(&pending.frame, addr);
+            self.traffic.sent += pending.frame.len() as u64;
+            pending.last_sent = now;
+            pending.resends += 1;
+        }
+
+        0
+    }
+
+    /// True when every reliable message sent to the peer has been acked
+    pub fn is_idle(&self) -> bool {
+        self.unacked.is_empty()
+    }
+
+    /// True once the peer left a reliable packet unacked after MAX_RESENDS. The
+    /// connection is no use after that and the peer should be treated as gone
+    pub fn gave_up(&self) -> bool {
+        self.gave_up
+    }
+
+    /// How much has gone to and come from the peer so far
+    pub fn traffic(&self) -> Traffic {
+        self.traffic
+    }
+}
+
+/// Why a message couldn't be sent
+#[derive(Debug)]
+pub enum SendError {
+    Codec(CodecError),
+    Io(io::Error),
+}
+
+impl From<CodecError> for SendError {
+    fn from(e: CodecError) -> Self {
+        SendError::Codec(e)
+    }
+}
+
+impl From<io::Error> for SendError {
+    fn from(e: io::Error) -> Self {
+        SendError::Io(e)
+    }
+}
+
+impl std::fmt::Display for SendError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            SendError::Codec(e) => write!(f, "{}", e),
+            SendError::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for SendError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Both ends of a connection over loopback, nothing goes anywhere unless a
+    /// test hands it over
+    struct Link {
+        socket: UdpSocket,
+        peer: SocketAddr,
+        connection: Connection<u32>,
+    }
+
+    fn link(first_seq: u32) -> (Link, Link) {
+        let a = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let b = UdpSocket::bind("127.0.0.1:0").unwrap();
+        for socket in [&a, &b] {
+            socket
+                .set_read_timeout(Some(Duration::from_millis(200)))
+                .unwrap();
+        }
+        let (a_addr, b_addr) = (a.local_addr().unwrap(), b.local_addr().unwrap());
+
+        //starting near the end of the range gets to the wrap without sending
+        //four billion packets first
+        let connection = || Connection {
+            next_seq: first_seq,
+            next_expected: first_seq,
+            ..Connection::new()
+        };
+        (
+            Link {
+                socket: a,
+                peer: b_addr,
+                connection: connection(),
+            },
+            Link {
+                socket: b,
+                peer: a_addr,
+                connection: connection(),
+            },
+        )
+    }
+
+    impl Link {
+        fn send(&mut self, message: u32) {
+            self.connection
+                .send(&self.socket, self.peer, &message, Channel::Reliable)
+                .unwrap();
+        }
+
+        /// The next datagram from the peer, None if nothing came
+        fn next_frame(&self) -> Option<Vec<u8>> {
+            let mut buf = vec![0; codec::MAX_PACKET_SIZE];
+            let (bytes, _) = self.socket.recv_from(&mut buf).ok()?;
+            Some(buf[..bytes].to_vec())
+        }
+
+        fn receive(&mut self, frame: &[u8]) -> Vec<u32> {
+            self.connection
+                .receive(&self.socket, self.peer, frame)
+                .unwrap()
+        }
+
+        /// Takes in every ack waiting on the socket
+        fn take_acks(&mut self) {
+            self.socket.set_nonblocking(true).unwrap();
+            while let Some(frame) = self.next_frame() {
+                self.receive(&frame);
+            }
+            self.socket.set_nonblocking(false).unwrap();
+        }
+    }
+
+    /// Sends `count` messages, each one its own seq, and hands back their frames
+    fn send_all(sender: &mut Link, receiver: &Link, count: u32) -> Vec<Vec<u8>> {
+        (0..count)
+            .map(|_| {
+                let seq = sender.connection.next_seq;
+                sender.send(seq);
+                receiver.next_frame().unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn reordered_packets_are_delivered_in_order() {
+        let (mut sender, mut receiver) = link(0);
+        let frames = send_all(&mut sender, &receiver, 5);
+
+        let mut delivered = Vec::new();
+        for i in [2, 0, 4, 1, 3] {
+            delivered.extend(receiver.receive(&frames[i]));
+        }
+
+        assert_eq!(delivered, vec![0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn duplicates_are_delivered_once_and_acked_again() {
+        let (mut sender, mut receiver) = link(0);
+        let frames = send_all(&mut sender, &receiver, 2);
+
+        assert_eq!(receiver.receive(&frames[0]), vec![0]);
+        assert_eq!(receiver.receive(&frames[0]), Vec::<u32>::new());
+        assert_eq!(receiver.receive(&frames[1]), vec![1]);
+        assert_eq!(receiver.receive(&frames[0]), Vec::<u32>::new());
+
+        //one ack for every copy, in case the first ones were lost
+        let mut acks = 0;
+        sender.socket.set_nonblocking(true).unwrap();
+        while let Some(frame) = sender.next_frame() {
+            sender.receive(&frame);
+            acks += 1;
+        }
+        assert_eq!(acks, 4);
+        assert!(sender.connection.is_idle());
+    }
+
+    #[test]
+    fn lost_packets_are_resent_until_acked() {
+        let (mut sender, mut receiver) = link(0);
+
+        //lost on the way
+        send_all(&mut sender, &receiver, 1);
+        let sent = Instant::now();
+
+        //not due yet
+        sender.connection.resend(&sender.socket, sender.peer, sent);
+        receiver.socket.set_nonblocking(true).unwrap();
+        assert!(receiver.next_frame().is_none());
+        receiver.socket.set_nonblocking(false).unwrap();
+
+        sender
+            .connection
+            .resend(&sender.socket, sender.peer, sent + RESEND_INTERVAL);
+        let frame = receiver.next_frame().unwrap();
+        assert_eq!(receiver.receive(&frame), vec![0]);
+
+        sender.take_acks();
+        assert!(sender.connection.is_idle());
+        assert!(!sender.connection.gave_up());
+    }
+
+    #[test]
+    fn peers_that_never_ack_are_given_up_on() {
+        let (mut sender, _receiver) = link(0);
+        sender.send(0);
+        sender.send(1);
+
+        let mut now = Instant::now();
+        for _ in 0..MAX_RESENDS {
+            now += RESEND_INTERVAL;
+            assert_eq!(
+                sender.connection.resend(&sender.socket, sender.peer, now),
+                0
+            );
+        }
+        assert!(!sender.connection.gave_up());
+
+        now += RESEND_INTERVAL;
+        assert_eq!(
+            sender.connection.resend(&sender.socket, sender.peer, now),
+            2
+        );
+        assert!(sender.connection.gave_up());
+        assert!(sender.connection.is_idle());
+    }
+
+    #[test]
+    fn seqs_keep_counting_across_the_wrap() {
+        let (mut sender, mut receiver) = link(u32::MAX - 1);
+        let frames = send_all(&mut sender, &receiver, 4);
+
+        //u32::MAX - 1, u32::MAX, 0, 1, with the ones after the wrap first
+        let mut delivered = Vec::new();
+        for i in [2, 3, 0, 1] {
+            delivered.extend(receiver.receive(&frames[i]));
+        }
+        assert_eq!(delivered, vec![u32::MAX - 1, u32::MAX, 0, 1]);
+
+        //and from before the wrap is behind, not far ahead
+        assert_eq!(receiver.receive(&frames[0]), Vec::<u32>::new());
+        assert!(receiver.connection.out_of_order.is_empty());
+    }
+
+    #[test]
+    fn packets_too_far_ahead_are_neither_kept_nor_acked() {
+        let (sender, mut receiver) = link(0);
+        let far = codec::encode(&Packet::Reliable {
+            seq: MAX_OUT_OF_ORDER,
+            message: 7u32,
+        })
+        .unwrap();
+
+        assert_eq!(receiver.receive(&far), Vec::<u32>::new());
+        assert!(receiver.connection.out_of_order.is_empty());
+
+        sender.socket.set_nonblocking(true).unwrap();
+        assert!(sender.next_frame().is_none());
+    }
+}
@@ -0,0 +1,161 @@
+use bevy::prelude::*;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use std::collections::HashMap;
+
+/*   GAME RNG   */
+// Everything random in a session is drawn from one seed, so the seed is all it
+// takes to play the session back for a bug report or a replay. Each part of the
+// game draws from its own stream of it, so one of them drawing more or less
+// often doesn't change what the others get.
+
+/// The parts of the game that draw random numbers. The numbers pick the stream,
+/// so changing them changes what an old seed plays back as
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RngStream {
+    Wind = 1,
+    /// Sand tiles of the island the player is on
+    Island = 2,
+    /// Where and when the server spawns ocean enemies
+    OceanEnemies = 3,
+    /// Ocean seeds of rooms that didn't ask for one
+    Oceans = 4,
+    /// Seeds of the dungeons under the islands
+    Dungeons = 5,
+    RoomCodes = 6,
+}
+
+/// The random numbers of the whole game, one stream per RngStream. The server
+/// also gives every room one of its own, see for_room
+#[derive(Resource, Component)]
+pub struct GameRng {
+    seed: u64,
+    streams: HashMap<RngStream, ChaCha8Rng>,
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> GameRng {
+        GameRng {
+            seed,
+            streams: HashMap::new(),
+        }
+    }
+
+    /// Seeded from `seed`, or from a random seed when it's left out
+    pub fn from_seed(seed: Option<u64>) -> GameRng {
+        GameRng::new(seed.unwrap_or_else(rand::random))
+    }
+
+    /// What to pass back in to play the session back
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// The random numbers of one room, worked out from this seed and the room's
+    /// code so rooms don't draw from each other's streams and still play back
+    pub fn for_room(&self, code: &str) -> GameRng {
+        //FNV-1a, std's hashers aren't promised to stay the same between releases
+        let hash = code.bytes().fold(0xcbf29ce484222325_u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        });
+        GameRng::new(self.seed ^ hash)
+    }
+
+    /// The random numbers for one part of the game
+    pub fn stream(&mut self, stream: RngStream) -> &mut ChaCha8Rng {
+        let seed = self.seed;
+        self.streams.entry(stream).or_insert_with(|| {
+            let mut rng = ChaCha8Rng::seed_from_u64(seed);
+            rng.set_stream(stream as u64);
+            rng
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dungeon::{generate_dungeon, DungeonLayout, DungeonParams};
+    use crate::ocean::{generate_ocean, OceanLayout, OceanParams};
+    use rand::Rng;
+
+    fn draw(rng: &mut GameRng, stream: RngStream) -> Vec<u32> {
+        (0..16).map(|_| rng.stream(stream).gen()).collect()
+    }
+
+    /// The ocean a server on `seed` opens a room on, and the first dungeon the
+    /// room with `code` builds, drawn the way the server draws them
+    fn world(seed: u64, code: &str) -> (OceanLayout, DungeonLayout) {
+        let mut rng = GameRng::new(seed);
+        let ocean = OceanParams::new(rng.stream(RngStream::Oceans).gen());
+
+        let mut room = rng.for_room(code);
+        let dungeon = DungeonParams::new(room.stream(RngStream::Dungeons).gen());
+
+        (generate_ocean(&ocean), generate_dungeon(&dungeon))
+    }
+
+    #[test]
+    fn the_same_seed_draws_the_same_numbers() {
+        let mut rng = GameRng::new(1234);
+        let mut again = GameRng::new(1234);
+
+        for stream in [
+            RngStream::Wind,
+            RngStream::OceanEnemies,
+            RngStream::Dungeons,
+        ] {
+            assert_eq!(draw(&mut rng, stream), draw(&mut again, stream));
+        }
+        assert_ne!(
+            draw(&mut rng, RngStream::Wind),
+            draw(&mut GameRng::new(4321), RngStream::Wind)
+        );
+    }
+
+    #[test]
+    fn streams_dont_draw_from_each_other() {
+        let mut rng = GameRng::new(1234);
+        let mut again = GameRng::new(1234);
+
+        //drawing from another stream first changes nothing about this one
+        draw(&mut again, RngStream::Island);
+        assert_eq!(
+            draw(&mut rng, RngStream::Wind),
+            draw(&mut again, RngStream::Wind)
+        );
+        assert_ne!(
+            draw(&mut rng, RngStream::Wind),
+            draw(&mut rng, RngStream::Oceans)
+        );
+    }
+
+    #[test]
+    fn rooms_get_numbers_of_their_own() {
+        let rng = GameRng::new(1234);
+
+        assert_eq!(
+            rng.for_room("ABCDE").seed(),
+            GameRng::new(1234).for_room("ABCDE").seed()
+        );
+        assert_ne!(rng.for_room("ABCDE").seed(), rng.for_room("ABCDF").seed());
+        assert_ne!(
+            rng.for_room("ABCDE").seed(),
+            GameRng::new(4321).for_room("ABCDE").seed()
+        );
+    }
+
+    #[test]
+    fn the_same_seed_plays_back_the_same_world() {
+        assert_eq!(world(42, "ABCDE"), world(42, "ABCDE"));
+
+        let (ocean, dungeon) = world(42, "ABCDE");
+        let (other_ocean, other_dungeon) = world(43, "ABCDE");
+        assert_ne!(ocean, other_ocean);
+        assert_ne!(dungeon, other_dungeon);
+
+        //rooms on the same server draw their dungeons from streams of their own
+        let (_, next_door) = world(42, "ABCDF");
+        assert_ne!(dungeon, next_door);
+    }
+}
@@ -0,0 +1,121 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::gameworld_data::{OCEAN_LEVEL_H, OCEAN_LEVEL_W};
+
+/*   SIMULATION   */
+// The server simulates every boat at a fixed tick and is the authority on where
+// it is. Clients run the exact same step function on their own inputs so their
+// boat responds instantly (prediction), then rewind and replay whenever a
+// snapshot from the server disagrees (reconciliation).
+
+/// How many simulation ticks run per second unless the server is configured
+/// otherwise. Clients use whatever rate the server sends in joined_lobby
+pub const DEFAULT_TICK_RATE: f64 = 30.;
+
+/// How many ticks in the past remote entities are drawn, so there is almost
+/// always a snapshot on either side of the moment being rendered
+pub const INTERPOLATION_TICKS: f32 = 3.;
+
+//boat handling, speeds are the defaults before any shop upgrades
+pub const BOAT_MOVEMENT_SPEED: f32 = 150.;
+pub const BOAT_ROTATION_SPEED: f32 = 100.; //degrees per second
+pub const BOAT_MAX_ACCEL: f32 = 800.;
+pub const BOAT_ACCEL_GAIN: f32 = 180.; //per second while thrusting
+pub const BOAT_ACCEL_DECAY: f32 = 420.; //per second while coasting
+
+/// How fast a pirate walks. The server won't move one further than this
+/// between two of their updates
+pub const PIRATE_SPEED: f32 = 500.;
+
+/// Extra distance a pirate is let go on top of PIRATE_SPEED, for updates the
+/// link bunched up
+pub const PIRATE_MOVE_SLACK: f32 = 64.;
+
+/// Where boats are put in the water, on setting sail and after sinking
+pub const BOAT_SPAWN_POSITION: Vec3 = Vec3::new(0., 0., 900.);
+
+/// One tick worth of boat controls
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct BoatInput {
+    pub seq: u32,
+    /// 1 turns left, -1 turns right
+    pub turn: f32,
+    /// 1 while sailing forward, 0 otherwise
+    pub thrust: f32,
+}
+
+impl BoatInput {
+    /// The same input with the controls held to what a player can actually do.
+    /// Anything that isn't a number counts as letting go of them
+    pub fn clamped(self) -> BoatInput {
+        let held = |v: f32, min: f32| if v.is_nan() { 0. } else { v.clamp(min, 1.) };
+        BoatInput {
+            turn: held(self.turn, -1.),
+            thrust: held(self.thrust, 0.),
+            ..self
+        }
+    }
+}
+
+/// Everything step_boat() needs to know about a boat
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct BoatState {
+    pub pos: Vec3,
+    pub rot: Quat,
+    pub acceleration: f32,
+    pub movement_speed: f32,
+    /// radians per second
+    pub rotation_speed: f32,
+}
+
+impl BoatState {
+    /// A stationary boat with the default speeds
+    pub fn new(pos: Vec3, rot: Quat) -> BoatState {
+        BoatState {
+            pos,
+            rot,
+            acceleration: 0.,
+            movement_speed: BOAT_MOVEMENT_SPEED,
+            rotation_speed: BOAT_ROTATION_SPEED.to_radians(),
+        }
+    }
+
+    /// A fresh boat at BOAT_SPAWN_POSITION
+    pub fn spawn() -> BoatState {
+        BoatState::new(BOAT_SPAWN_POSITION, Quat::IDENTITY)
+    }
+}
+
+/*   STEP_BOAT FUNCTION   */
+/// Advances a boat by one tick. Sailing with the wind is faster than sailing
+/// against it, and acceleration builds up while thrusting and bleeds off while
+/// coasting
+pub fn step_boat(state: &mut BoatState, input: &BoatInput, wind: Vec2, dt: f32) {
+    let input = input.clamped();
+
+    //cosine similarity between the boat and the wind
+    let boat_direction = state.rot * Vec3::Y;
+    let cs = boat_direction.truncate().dot(wind) / (boat_direction.length() * wind.length());
+    let cs = if cs.is_finite() { cs } else { 0. };
+
+    //building up or bleeding off acceleration
+    if state.acceleration <= BOAT_MAX_ACCEL && input.thrust == 1. {
+        state.acceleration += BOAT_ACCEL_GAIN * dt;
+    } else {
+        state.acceleration = (state.acceleration - BOAT_ACCEL_DECAY * dt).max(0.);
+    }
+
+    //turning, left minus right so holding both keeps the boat straight
+    state.rot *= Quat::from_rotation_z(input.turn * state.rotation_speed * dt);
+
+    //moving
+    let movement_dir = state.rot * Vec3::Y;
+    let movement_dis =
+        input.thrust * (state.movement_speed * dt * cs) + (0.5 * state.acceleration * dt);
+    state.pos += movement_dir * movement_dis;
+
+    //staying inside the ocean
+    let extents = Vec3::new(OCEAN_LEVEL_W / 2., OCEAN_LEVEL_H / 2., f32::INFINITY);
+    state.pos = state.pos.min(extents).max(-extents);
+}
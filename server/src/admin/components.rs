@@ -0,0 +1,362 @@
+use bevy::ecs::query::QueryData;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::str::FromStr;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::data::gameworld_data::*;
+use crate::enemies::components::*;
+use crate::level::components::*;
+use crate::network::components::*;
+use crate::rooms::components::*;
+use crate::simulation::components::*;
+
+/// Every command the console takes, shown by `help`
+pub const COMMANDS: &[&str] = &[
+    "help",
+    "list players",
+    "kick <id> [in <room>]",
+    "spawn <kraken|ghostship|whirlpool|storm> <x> <y> [in <room>]",
+    "set seed <seed|random>",
+    "give gold <id> <amount> [in <room>]",
+    "teleport <id> <x> <y> [in <room>]",
+    "broadcast <message>",
+    "shutdown",
+];
+
+/// A line typed into the console or sent to the admin socket, and where the
+/// answer goes
+pub struct AdminRequest {
+    pub line: String,
+    pub reply: Sender<String>,
+}
+
+/// Admin commands waiting to be carried out. They're read on threads of their
+/// own, from stdin and the admin socket, and run by run_admin_commands so they
+/// see the rooms between updates like everything else
+#[derive(Resource)]
+pub struct AdminConsole {
+    sender: Sender<AdminRequest>,
+    requests: Mutex<Receiver<AdminRequest>>,
+}
+
+impl Default for AdminConsole {
+    fn default() -> AdminConsole {
+        let (sender, requests) = mpsc::channel();
+        AdminConsole {
+            sender,
+            requests: Mutex::new(requests),
+        }
+    }
+}
+
+impl AdminConsole {
+    /// Every request that came in since the last call
+    pub fn pending(&self) -> Vec<AdminRequest> {
+        self.requests.lock().unwrap().try_iter().collect()
+    }
+
+    /// Takes commands typed into the server's terminal, until stdin closes
+    pub fn read_stdin(&self) -> io::Result<()> {
+        let sender = self.sender.clone();
+
+        thread::Builder::new()
+            .name("console".to_string())
+            .spawn(move || {
+                for line in io::stdin().lock().lines() {
+                    let Ok(line) = line else {
+                        break;
+                    };
+                    match ask(&sender, line) {
+                        Some(reply) => println!("{}", reply),
+                        None => break,
+                    }
+                }
+            })?;
+        Ok(())
+    }
+
+    /// Takes commands a line at a time from anyone connecting to `addr`, and
+    /// writes the answers back
+    pub fn listen(&self, addr: SocketAddr) -> io::Result<()> {
+        let listener = TcpListener::bind(addr)?;
+        let sender = self.sender.clone();
+
+        thread::Builder::new()
+            .name("admin".to_string())
+            .spawn(move || {
+                for stream in listener.incoming().flatten() {
+                    let sender = sender.clone();
+                    thread::spawn(move || serve(stream, sender));
+                }
+            })?;
+        Ok(())
+    }
+}
+
+/// Longest a shutdown waits for every player to ack being kicked
+pub const SHUTDOWN_GRACE: Duration = Duration::from_secs(3);
+
+/// When a shutdown was asked for, the server exits once every client has acked
+/// everything sent to them or the deadline has passed
+#[derive(Resource, Default)]
+pub struct ShuttingDown {
+    pub deadline: Option<Instant>,
+}
+
+/// Answers one admin socket connection until it's closed
+fn serve(stream: TcpStream, sender: Sender<AdminRequest>) {
+    let Ok(peer) = stream.peer_addr() else {
+        return;
+    };
+    let Ok(mut writer) = stream.try_clone() else {
+        return;
+    };
+    info!("Admin connected from [{}]", peer);
+
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        let Some(reply) = ask(&sender, line) else {
+            break;
+        };
+        if writeln!(writer, "{}", reply).is_err() {
+            break;
+        }
+    }
+
+    info!("Admin [{}] disconnected", peer);
+}
+
+/// Hands a line to the server and waits for the answer. None once the server
+/// has stopped taking commands
+fn ask(sender: &Sender<AdminRequest>, line: String) -> Option<String> {
+    //blank lines get a blank answer rather than a complaint
+    if line.trim().is_empty() {
+        return Some(String::new());
+    }
+
+    let (reply, answer) = mpsc::channel();
+    sender.send(AdminRequest { line, reply }).ok()?;
+    answer.recv().ok()
+}
+
+/// A parsed admin command. Commands about one room take an optional `in
+/// <room>`, which can be left out while only one room is open
+pub enum AdminCommand {
+    Help,
+    ListPlayers,
+    Kick {
+        room: Option<String>,
+        id: i32,
+    },
+    Spawn {
+        room: Option<String>,
+        etype: i32,
+        pos: Vec2,
+    },
+    /// Ocean seed of rooms opened from now on, None for a random one each
+    SetSeed(Option<u64>),
+    GiveGold {
+        room: Option<String>,
+        id: i32,
+        amount: u32,
+    },
+    /// Moves a player's boat, it has to be afloat
+    Teleport {
+        room: Option<String>,
+        id: i32,
+        pos: Vec2,
+    },
+    /// Sent to every player in every room
+    Broadcast(String),
+    Shutdown,
+}
+
+impl AdminCommand {
+    pub fn parse(line: &str) -> Result<AdminCommand, CommandError> {
+        let line = line.trim();
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let keyword = words.first().map(|word| word.to_lowercase());
+
+        match keyword.as_deref() {
+            Some("help") => Ok(AdminCommand::Help),
+            Some("list") => match words[1..] {
+                [] | ["players"] => Ok(AdminCommand::ListPlayers),
+                _ => Err(CommandError::Usage(COMMANDS[1])),
+            },
+            Some("kick") => match in_room(&words[1..]) {
+                ([id], room) => Ok(AdminCommand::Kick {
+                    room,
+                    id: number(id)?,
+                }),
+                _ => Err(CommandError::Usage(COMMANDS[2])),
+            },
+            Some("spawn") => match in_room(&words[1..]) {
+                ([enemy, x, y], room) => {
+                    let etype = enemy_type(enemy)
+                        .ok_or_else(|| CommandError::NoSuchEnemy(enemy.to_string()))?;
+                    let pos = Vec2::new(number(x)?, number(y)?);
+                    if pos.x.abs() > OCEAN_LEVEL_W / 2. || pos.y.abs() > OCEAN_LEVEL_H / 2. {
+                        return Err(CommandError::OffTheMap { pos });
+                    }
+                    Ok(AdminCommand::Spawn { room, etype, pos })
+                }
+                _ => Err(CommandError::Usage(COMMANDS[3])),
+            },
+            Some("set") => match words[1..] {
+                ["seed", seed] if seed.eq_ignore_ascii_case("random") => {
+                    Ok(AdminCommand::SetSeed(None))
+                }
+                ["seed", seed] => Ok(AdminCommand::SetSeed(Some(number(seed)?))),
+                _ => Err(CommandError::Usage(COMMANDS[4])),
+            },
+            Some("give") => match in_room(&words[1..]) {
+                (["gold", id, amount], room) => Ok(AdminCommand::GiveGold {
+                    room,
+                    id: number(id)?,
+                    amount: number(amount)?,
+                }),
+                _ => Err(CommandError::Usage(COMMANDS[5])),
+            },
+            Some("teleport") => match in_room(&words[1..]) {
+                ([id, x, y], room) => {
+                    let pos = Vec2::new(number(x)?, number(y)?);
+                    if pos.x.abs() > OCEAN_LEVEL_W / 2. || pos.y.abs() > OCEAN_LEVEL_H / 2. {
+                        return Err(CommandError::OffTheMap { pos });
+                    }
+                    Ok(AdminCommand::Teleport {
+                        room,
+                        id: number(id)?,
+                        pos,
+                    })
+                }
+                _ => Err(CommandError::Usage(COMMANDS[6])),
+            },
+            Some("broadcast") => {
+                //the message as typed, spaces and all
+                let text = line[words[0].len()..].trim();
+                if text.is_empty() {
+                    return Err(CommandError::Usage(COMMANDS[7]));
+                }
+                Ok(AdminCommand::Broadcast(text.to_string()))
+            }
+            Some("shutdown") => Ok(AdminCommand::Shutdown),
+            Some(_) => Err(CommandError::Unknown(words[0].to_string())),
+            None => Err(CommandError::Usage(COMMANDS[0])),
+        }
+    }
+}
+
+/// Splits a trailing `in <room>` off a command's arguments
+fn in_room<'a>(args: &'a [&'a str]) -> (&'a [&'a str], Option<String>) {
+    match args {
+        [rest @ .., word, code] if word.eq_ignore_ascii_case("in") => {
+            (rest, Some(code.to_uppercase()))
+        }
+        _ => (args, None),
+    }
+}
+
+fn number<T: FromStr>(word: &str) -> Result<T, CommandError> {
+    word.parse()
+        .map_err(|_| CommandError::BadNumber(word.to_string()))
+}
+
+/// The ocean enemy an admin means by `name`
+fn enemy_type(name: &str) -> Option<i32> {
+    match name.to_lowercase().as_str() {
+        "kraken" => Some(KRAKEN),
+        "ghostship" | "ghost_ship" => Some(GHOSTSHIP),
+        "whirlpool" => Some(WHIRLPOOL),
+        "storm" => Some(STORM),
+        _ => None,
+    }
+}
+
+/// Why an admin command couldn't be carried out
+pub enum CommandError {
+    Unknown(String),
+    /// Wrong arguments, with how the command is used
+    Usage(&'static str),
+    BadNumber(String),
+    NoSuchEnemy(String),
+    OffTheMap {
+        pos: Vec2,
+    },
+    NoRooms,
+    /// More than one room is open and the command didn't say which
+    WhichRoom,
+    NoSuchRoom(String),
+    NoSuchPlayer {
+        id: i32,
+        room: String,
+    },
+    /// The player is ashore, or hasn't set sail yet
+    NotAfloat {
+        id: i32,
+        room: String,
+    },
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CommandError::Unknown(word) => {
+                write!(f, "Unknown command {:?}, try `help`", word)
+            }
+            CommandError::Usage(usage) => write!(f, "Usage: {}", usage),
+            CommandError::BadNumber(word) => write!(f, "{:?} isn't a number", word),
+            CommandError::NoSuchEnemy(name) => write!(
+                f,
+                "No enemy called {:?}, try kraken, ghostship, whirlpool or storm",
+                name
+            ),
+            CommandError::OffTheMap { pos } => write!(
+                f,
+                "({}, {}) is off the ocean, which spans {} by {}",
+                pos.x, pos.y, OCEAN_LEVEL_W, OCEAN_LEVEL_H
+            ),
+            CommandError::NoRooms => write!(f, "No rooms are open"),
+            CommandError::WhichRoom => {
+                write!(f, "More than one room is open, say which with `in <room>`")
+            }
+            CommandError::NoSuchRoom(code) => write!(f, "No room {}", code),
+            CommandError::NoSuchPlayer { id, room } => {
+                write!(f, "No player #{} in room {}", id, room)
+            }
+            CommandError::NotAfloat { id, room } => {
+                write!(f, "Player #{} in room {} has no boat afloat", id, room)
+            }
+        }
+    }
+}
+
+/// Sockets and bookkeeping for talking to clients from outside of handle
+#[derive(SystemParam)]
+pub struct Clients<'w> {
+    pub udp: Res<'w, UDP>,
+    pub connections: ResMut<'w, Connections>,
+    pub heartbeats: ResMut<'w, Heartbeats>,
+    pub index: ResMut<'w, RoomIndex>,
+}
+
+/// The parts of a room admin commands touch
+#[derive(QueryData)]
+#[query_data(mutable)]
+pub struct AdminRoom {
+    pub room: &'static Room,
+    pub ocean: &'static OceanMap,
+    pub players: &'static mut Players,
+    pub counter: &'static mut Counter,
+    pub enemies: &'static mut EnemyLists,
+    pub states: &'static mut EnemyStates,
+    pub sims: &'static mut BoatSims,
+}
@@ -0,0 +1,314 @@
+use bevy::prelude::*;
+use protocol::messages::{ServerMessage, MAX_CHAT_LENGTH};
+use protocol::reliable::Channel;
+use std::net::SocketAddr;
+use std::time::Instant;
+
+use crate::admin::components::*;
+use crate::config::ServerConfig;
+use crate::enemies::components::OceanEnemy;
+use crate::enemies::systems::spawn_enemy;
+use crate::network::components::Connections;
+use crate::network::systems::{broadcast, send, send_to_player};
+use crate::rooms::systems::broadcast_roster;
+
+/*   RUN_ADMIN_COMMANDS FUNCTION   */
+/// Carries out the commands that came in from the console and the admin socket
+/// since the last update, and answers each of them
+pub fn run_admin_commands(
+    console: Res<AdminConsole>,
+    mut config: ResMut<ServerConfig>,
+    mut clients: Clients,
+    mut rooms: Query<AdminRoom>,
+    mut shutting_down: ResMut<ShuttingDown>,
+) {
+    for request in console.pending() {
+        let result = AdminCommand::parse(&request.line).and_then(|command| {
+            info!("Admin: {}", request.line.trim());
+
+            match command {
+                AdminCommand::Help => Ok(format!("Commands:\n  {}", COMMANDS.join("\n  "))),
+                AdminCommand::ListPlayers => Ok(list_players(&rooms, &clients.connections)),
+                AdminCommand::Kick { room, id } => {
+                    let mut room = find_room(&mut rooms, &clients, room)?;
+                    kick(&mut clients, &mut room, id, "Kicked by the server admin")
+                }
+                AdminCommand::Spawn { room, etype, pos } => {
+                    let mut room = find_room(&mut rooms, &clients, room)?;
+                    let stats = OceanEnemy::of(etype)
+                        .ok_or_else(|| CommandError::NoSuchEnemy(etype.to_string()))?;
+                    let id = spawn_enemy(
+                        &mut room.counter,
+                        &mut room.enemies,
+                        &mut room.states,
+                        etype,
+                        &stats,
+                        pos,
+                    );
+                    Ok(format!(
+                        "Spawned enemy [{}] at ({}, {}) in room {}",
+                        id, pos.x, pos.y, room.room.code
+                    ))
+                }
+                AdminCommand::SetSeed(seed) => {
+                    config.seed = seed;
+                    Ok(match seed {
+                        Some(seed) => format!("Rooms opened from now on use ocean seed {}", seed),
+                        None => "Rooms opened from now on get a random ocean".to_string(),
+                    })
+                }
+                AdminCommand::GiveGold { room, id, amount } => {
+                    let room = find_room(&mut rooms, &clients, room)?;
+                    let Some(player) = room.players.get(id) else {
+                        return Err(CommandError::NoSuchPlayer {
+                            id,
+                            room: room.room.code.clone(),
+                        });
+                    };
+                    send_to_player(
+                        &clients.udp.socket,
+                        &mut clients.connections,
+                        player,
+                        &ServerMessage::GoldGiven { amount },
+                        Channel::Reliable,
+                    );
+                    Ok(format!(
+                        "Gave {} gold to player #{} ({}) in room {}",
+                        amount, id, player.name, room.room.code
+                    ))
+                }
+                AdminCommand::Teleport { room, id, pos } => {
+                    let mut room = find_room(&mut rooms, &clients, room)?;
+                    let code = room.room.code.clone();
+                    let Some(sim) = room.sims.list.get_mut(&id) else {
+                        return Err(CommandError::NotAfloat { id, room: code });
+                    };
+                    //the boat stays at the height it floats at
+                    sim.state.pos = pos.extend(sim.state.pos.z);
+                    let moved = sim.state.pos;
+                    if let Some(player) = room.players.get_mut(id) {
+                        player.pos = moved;
+                    }
+                    Ok(format!(
+                        "Moved player #{} to ({}, {}) in room {}",
+                        id, pos.x, pos.y, code
+                    ))
+                }
+                AdminCommand::Broadcast(text) => {
+                    let text: String = text.chars().take(MAX_CHAT_LENGTH).collect();
+                    let mut heard_by = 0;
+                    for room in rooms.iter() {
+                        broadcast(
+                            &clients.udp.socket,
+                            &mut clients.connections,
+                            room.players,
+                            -1,
+                            &ServerMessage::Announcement(text.clone()),
+                            Channel::Reliable,
+                        );
+                        heard_by += room.players.len();
+                    }
+                    Ok(format!("Sent to {} players", heard_by))
+                }
+                AdminCommand::Shutdown => {
+                    //resent like anything else until finish_shutdown lets go
+                    for room in rooms.iter() {
+                        broadcast(
+                            &clients.udp.socket,
+                            &mut clients.connections,
+                            room.players,
+                            -1,
+                            &ServerMessage::Kicked {
+                                reason: "The server is shutting down".to_string(),
+                            },
+                            Channel::Reliable,
+                        );
+                    }
+                    let deadline = *shutting_down
+                        .deadline
+                        .get_or_insert(Instant::now() + SHUTDOWN_GRACE);
+                    Ok(format!(
+                        "Shutting down once every player has been told, in {:.1}s at most",
+                        deadline
+                            .saturating_duration_since(Instant::now())
+                            .as_secs_f32()
+                    ))
+                }
+            }
+        });
+
+        let reply = match result {
+            Ok(reply) => reply,
+            Err(e) => e.to_string(),
+        };
+        //the admin may have hung up already
+        let _ = request.reply.send(reply);
+    }
+}
+
+/*   FINISH_SHUTDOWN FUNCTION   */
+/// Exits once a shutdown has been asked for and every client has acked being
+/// kicked, or has had SHUTDOWN_GRACE to do it
+pub fn finish_shutdown(
+    shutting_down: Res<ShuttingDown>,
+    connections: Res<Connections>,
+    mut exit: EventWriter<AppExit>,
+) {
+    let Some(deadline) = shutting_down.deadline else {
+        return;
+    };
+
+    let delivered = connections
+        .list
+        .values()
+        .all(|connection| connection.is_idle());
+    if delivered || Instant::now() >= deadline {
+        exit.send(AppExit::Success);
+    }
+}
+
+/*   FIND_ROOM FUNCTION   */
+/// The room a command is about, by its code or the only one open
+fn find_room<'a>(
+    rooms: &'a mut Query<AdminRoom>,
+    clients: &Clients,
+    code: Option<String>,
+) -> Result<AdminRoomItem<'a>, CommandError> {
+    let index = &clients.index;
+    let entity = match code {
+        Some(code) => *index
+            .by_code
+            .get(&code)
+            .ok_or(CommandError::NoSuchRoom(code))?,
+        None => {
+            let mut open = index.by_code.values();
+            match (open.next(), open.next()) {
+                (Some(entity), None) => *entity,
+                (Some(_), Some(_)) => return Err(CommandError::WhichRoom),
+                (None, _) => return Err(CommandError::NoRooms),
+            }
+        }
+    };
+
+    //only just opened, it isn't spawned until the end of handle
+    rooms.get_mut(entity).map_err(|_| CommandError::NoRooms)
+}
+
+/*   LIST_PLAYERS FUNCTION   */
+/// Every open room and who's in it, with how much has gone to and from each of them
+fn list_players(rooms: &Query<AdminRoom>, connections: &Connections) -> String {
+    let mut lines = Vec::new();
+
+    for room in rooms.iter() {
+        lines.push(format!(
+            "Room {}, ocean seed {}, {}/{} players, {}{}",
+            room.room.code,
+            room.ocean.params.seed,
+            room.players.len(),
+            room.players.capacity(),
+            if room.room.started {
+                "at sea"
+            } else {
+                "in the lobby"
+            },
+            if room.room.pvp.enabled { ", pvp" } else { "" }
+        ));
+
+        for player in room.players.iter() {
+            //dropped players waiting to rejoin have no connection
+            let traffic = player
+                .addr
+                .parse::<SocketAddr>()
+                .ok()
+                .and_then(|addr| connections.list.get(&addr))
+                .map(|connection| {
+                    let traffic = connection.traffic();
+                    let (sent, received) = traffic.rates();
+                    format!(
+                        ", sent {} ({:.1} KB/s), received {} ({:.1} KB/s)",
+                        kilobytes(traffic.sent),
+                        sent / 1024.,
+                        kilobytes(traffic.received),
+                        received / 1024.
+                    )
+                })
+                .unwrap_or_default();
+
+            lines.push(format!(
+                "  #{} {} [{}] {} at ({:.0}, {:.0}){}{}",
+                player.id,
+                player.name,
+                player.addr,
+                player.area,
+                player.pos.x,
+                player.pos.y,
+                if player.ready { ", ready" } else { "" },
+                traffic
+            ));
+        }
+    }
+
+    if lines.is_empty() {
+        "No rooms open".to_string()
+    } else {
+        lines.join("\n")
+    }
+}
+
+fn kilobytes(bytes: u64) -> String {
+    format!("{:.1} KB", bytes as f64 / 1024.)
+}
+
+/*   KICK FUNCTION   */
+/// Takes a player out of their room for good. Unlike a dropped player their
+/// slot isn't held, so they'd have to join again
+fn kick(
+    clients: &mut Clients,
+    room: &mut AdminRoomItem,
+    id: i32,
+    reason: &str,
+) -> Result<String, CommandError> {
+    let Some(player) = room.players.remove(id) else {
+        return Err(CommandError::NoSuchPlayer {
+            id,
+            room: room.room.code.clone(),
+        });
+    };
+    room.sims.list.remove(&id);
+    room.sims.repair(id);
+
+    //their connection stays around until this is acked, see resend_reliable
+    if let Ok(addr) = player.addr.parse::<SocketAddr>() {
+        clients.index.by_addr.remove(&addr);
+        clients.heartbeats.last_seen.remove(&addr);
+        send(
+            &clients.udp.socket,
+            &mut clients.connections,
+            addr,
+            &ServerMessage::Kicked {
+                reason: reason.to_string(),
+            },
+            Channel::Reliable,
+        );
+    }
+
+    broadcast(
+        &clients.udp.socket,
+        &mut clients.connections,
+        &room.players,
+        id,
+        &ServerMessage::PlayerLeft { id },
+        Channel::Reliable,
+    );
+    broadcast_roster(
+        &clients.udp.socket,
+        &mut clients.connections,
+        room.room,
+        &room.players,
+    );
+
+    Ok(format!(
+        "Kicked player #{} ({}) from room {}",
+        id, player.name, room.room.code
+    ))
+}
@@ -0,0 +1,74 @@
+use clap::Parser;
+use protocol::netsim::{LinkConditions, LinkProxy};
+use std::net::SocketAddr;
+use std::thread;
+use std::time::Duration;
+
+/// How often the proxy prints what it has done to the traffic
+const STATS_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Command line flags. Point the game at --listen instead of the server
+#[derive(Parser)]
+#[command(
+    name = "netsim",
+    about = "Forwards Sea of Fortune traffic to a server over a lossy, laggy link"
+)]
+struct Cli {
+    /// Address game clients connect to
+    #[arg(long, default_value = "127.0.0.1:5001")]
+    listen: SocketAddr,
+    /// Address of the real server
+    #[arg(long, default_value = "127.0.0.1:5000")]
+    server: SocketAddr,
+    /// Chance of a datagram being dropped, from 0 to 1
+    #[arg(long, default_value_t = 0.)]
+    loss: f32,
+    /// Milliseconds every datagram is held back
+    #[arg(long, default_value_t = 0)]
+    latency: u64,
+    /// Up to this many extra milliseconds, picked per datagram
+    #[arg(long, default_value_t = 0)]
+    jitter: u64,
+    /// Chance of a datagram being delivered twice, from 0 to 1
+    #[arg(long, default_value_t = 0.)]
+    duplicate: f32,
+    /// Chance of a datagram being overtaken by the ones after it, from 0 to 1
+    #[arg(long, default_value_t = 0.)]
+    reorder: f32,
+    /// Seed for which datagrams get hit [default: random]
+    #[arg(long)]
+    seed: Option<u64>,
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let seed = cli.seed.unwrap_or_else(rand::random);
+    let conditions = LinkConditions {
+        loss: cli.loss,
+        latency: Duration::from_millis(cli.latency),
+        jitter: Duration::from_millis(cli.jitter),
+        duplicate: cli.duplicate,
+        reorder: cli.reorder,
+    };
+
+    let proxy = match LinkProxy::spawn(cli.listen, cli.server, conditions, seed) {
+        Ok(proxy) => proxy,
+        Err(e) => {
+            eprintln!("Could not start the proxy on {}: {}", cli.listen, e);
+            std::process::exit(1);
+        }
+    };
+
+    println!(
+        "Forwarding {} to {} with {:?} (seed {})",
+        proxy.addr(),
+        cli.server,
+        conditions,
+        seed
+    );
+
+    loop {
+        thread::sleep(STATS_INTERVAL);
+        println!("{:?}", proxy.stats());
+    }
+}
@@ -0,0 +1,44 @@
+use bevy::prelude::*;
+use std::collections::HashMap;
+use std::time::Instant;
+
+/// Chat messages a player can send back to back
+pub const CHAT_BURST: f32 = 5.;
+
+/// Chat messages a player gets back per second after using up the burst
+pub const CHAT_REFILL_PER_SECOND: f32 = 0.5;
+
+/// How many chat messages a player can send right now. Refills over time up
+/// to CHAT_BURST, so a few quick messages are fine but a flood isn't
+pub struct ChatAllowance {
+    left: f32,
+    updated: Instant,
+}
+
+impl ChatAllowance {
+    pub fn init(now: Instant) -> ChatAllowance {
+        ChatAllowance {
+            left: CHAT_BURST,
+            updated: now,
+        }
+    }
+
+    /// Uses up one message, or hands back how many seconds until there's one
+    pub fn take(&mut self, now: Instant) -> Result<(), f32> {
+        let refilled = now.duration_since(self.updated).as_secs_f32() * CHAT_REFILL_PER_SECOND;
+        self.left = (self.left + refilled).min(CHAT_BURST);
+        self.updated = now;
+
+        if self.left < 1. {
+            return Err((1. - self.left) / CHAT_REFILL_PER_SECOND);
+        }
+        self.left -= 1.;
+        Ok(())
+    }
+}
+
+/// Chat allowances keyed by player id, reset whenever a slot is given to a new player
+#[derive(Component, Default)]
+pub struct ChatLimits {
+    pub list: HashMap<i32, ChatAllowance>,
+}
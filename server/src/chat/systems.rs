@@ -0,0 +1,74 @@
+use bevy::log::{debug, warn};
+use protocol::messages::{ChatLine, ServerMessage, MAX_CHAT_LENGTH};
+use protocol::reliable::Channel;
+use std::net::{SocketAddr, UdpSocket};
+use std::time::Instant;
+
+use crate::chat::components::*;
+use crate::network::components::*;
+use crate::network::systems::{broadcast, send};
+
+/*   HANDLE_CHAT FUNCTION   */
+/// Passes a player's chat message on to everyone in their room, cut down to
+/// MAX_CHAT_LENGTH. Players going over their ChatAllowance are told to wait
+pub fn handle_chat(
+    socket: &UdpSocket,
+    connections: &mut Connections,
+    src: SocketAddr,
+    sender: &Player,
+    players: &Players,
+    limits: &mut ChatLimits,
+    text: &str,
+) {
+    let text = clean_chat(text);
+    if text.is_empty() {
+        return;
+    }
+
+    let now = Instant::now();
+    let allowance = limits
+        .list
+        .entry(sender.id)
+        .or_insert_with(|| ChatAllowance::init(now));
+
+    if let Err(retry_in) = allowance.take(now) {
+        warn!(
+            "Dropped chat from player #{}: sending too fast, next in {:.1}s",
+            sender.id, retry_in
+        );
+        send(
+            socket,
+            connections,
+            src,
+            &ServerMessage::ChatThrottled { retry_in },
+            Channel::Reliable,
+        );
+        return;
+    }
+
+    debug!(
+        "Chat from player #{} ({}): {}",
+        sender.id, sender.name, text
+    );
+    broadcast(
+        socket,
+        connections,
+        players,
+        -1,
+        &ServerMessage::Chat(ChatLine {
+            from: sender.id,
+            name: sender.name.clone(),
+            text,
+        }),
+        Channel::Reliable,
+    );
+}
+
+/// Trims a chat message down to MAX_CHAT_LENGTH printable characters
+fn clean_chat(text: &str) -> String {
+    text.trim()
+        .chars()
+        .filter(|c| !c.is_control())
+        .take(MAX_CHAT_LENGTH)
+        .collect()
+}
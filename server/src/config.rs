@@ -0,0 +1,272 @@
+use bevy::log::Level;
+use bevy::prelude::*;
+use clap::Parser;
+use protocol::combat::PvpRules;
+use protocol::simulation::DEFAULT_TICK_RATE;
+use serde::Deserialize;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::PathBuf;
+use std::time::Duration;
+use std::{fmt, fs, io};
+
+use crate::network::components::DEFAULT_CLIENT_TIMEOUT;
+
+/// Config file read when --config isn't given, skipped if it doesn't exist
+pub const DEFAULT_CONFIG_PATH: &str = "server.toml";
+
+/// Most players one room can hold, every snapshot has to fit in one packet
+pub const MAX_PLAYERS_LIMIT: usize = 64;
+
+/// Command line flags. Anything given here wins over the config file
+#[derive(Parser)]
+#[command(name = "server", about = "Sea of Fortune game server")]
+pub struct Cli {
+    /// TOML config file [default: server.toml, if there is one]
+    #[arg(short, long)]
+    pub config: Option<PathBuf>,
+    /// Address to listen on
+    #[arg(long)]
+    pub bind: Option<IpAddr>,
+    /// UDP port to listen on
+    #[arg(short, long)]
+    pub port: Option<u16>,
+    /// Players that can be in one room at once
+    #[arg(long)]
+    pub max_players: Option<usize>,
+    /// Rooms that can be open at once
+    #[arg(long)]
+    pub max_rooms: Option<usize>,
+    /// Simulation ticks per second
+    #[arg(long)]
+    pub tick_rate: Option<f64>,
+    /// Ocean seed of rooms that don't ask for one [default: random per room]
+    #[arg(long)]
+    pub seed: Option<u64>,
+    /// Seed of every random number the server picks, to play a session back
+    /// [default: random, printed at startup]
+    #[arg(long)]
+    pub rng_seed: Option<u64>,
+    /// Let players' cannonballs hurt other players' boats
+    #[arg(long)]
+    pub pvp: bool,
+    /// With --pvp, let cannonballs hurt boats of the same crew too
+    #[arg(long)]
+    pub friendly_fire: bool,
+    /// Gold paid for sinking another player's boat
+    #[arg(long)]
+    pub pvp_bounty: Option<u32>,
+    /// How far around their boat or pirate players are sent what's going on
+    #[arg(long)]
+    pub view_radius: Option<f32>,
+    /// Seconds a player can go without sending anything before they're dropped
+    #[arg(long)]
+    pub client_timeout: Option<f32>,
+    /// error, warn, info, debug or trace
+    #[arg(long)]
+    pub log_level: Option<String>,
+    /// Local TCP port taking the same admin commands as the console
+    #[arg(long)]
+    pub admin_port: Option<u16>,
+}
+
+/// Server settings, from the config file and the command line. Keys missing from
+/// the file keep their defaults
+#[derive(Resource, Deserialize, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub bind: IpAddr,
+    pub port: u16,
+    pub max_players: usize,
+    pub max_rooms: usize,
+    pub tick_rate: f64,
+    /// Ocean seed of rooms that don't ask for one, a random one is picked per
+    /// room when this is left out
+    pub seed: Option<u64>,
+    /// Seed of every random number the server picks. A random one is picked
+    /// when this is left out, it's printed at startup for bug reports
+    pub rng_seed: Option<u64>,
+    /// Whether players can sink each other's boats, off unless asked for
+    pub pvp: bool,
+    pub friendly_fire: bool,
+    pub pvp_bounty: u32,
+    /// Players are only sent the enemies, projectiles and other players this
+    /// close to them
+    pub view_radius: f32,
+    /// Seconds a player can go without sending anything before they're dropped
+    pub client_timeout: f32,
+    pub log_level: String,
+    /// Port of the admin socket, only reachable from this machine. Off when left out
+    pub admin_port: Option<u16>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> ServerConfig {
+        ServerConfig {
+            bind: IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            port: 5000,
+            max_players: 4,
+            max_rooms: 8,
+            tick_rate: DEFAULT_TICK_RATE,
+            seed: None,
+            rng_seed: None,
+            pvp: false,
+            friendly_fire: false,
+            pvp_bounty: 250,
+            view_radius: 1200.,
+            client_timeout: DEFAULT_CLIENT_TIMEOUT.as_secs_f32(),
+            log_level: "info".to_string(),
+            admin_port: None,
+        }
+    }
+}
+
+impl ServerConfig {
+    /// Reads the config file, if there is one, and applies the command line on top
+    pub fn load(cli: Cli) -> Result<ServerConfig, ConfigError> {
+        let mut config = match cli.config {
+            Some(path) => ServerConfig::read(path)?,
+            None if fs::metadata(DEFAULT_CONFIG_PATH).is_ok() => {
+                ServerConfig::read(PathBuf::from(DEFAULT_CONFIG_PATH))?
+            }
+            None => ServerConfig::default(),
+        };
+
+        if let Some(bind) = cli.bind {
+            config.bind = bind;
+        }
+        if let Some(port) = cli.port {
+            config.port = port;
+        }
+        if let Some(max_players) = cli.max_players {
+            config.max_players = max_players;
+        }
+        if let Some(max_rooms) = cli.max_rooms {
+            config.max_rooms = max_rooms;
+        }
+        if let Some(tick_rate) = cli.tick_rate {
+            config.tick_rate = tick_rate;
+        }
+        if cli.seed.is_some() {
+            config.seed = cli.seed;
+        }
+        if cli.rng_seed.is_some() {
+            config.rng_seed = cli.rng_seed;
+        }
+        if cli.pvp {
+            config.pvp = true;
+        }
+        if cli.friendly_fire {
+            config.friendly_fire = true;
+        }
+        if let Some(pvp_bounty) = cli.pvp_bounty {
+            config.pvp_bounty = pvp_bounty;
+        }
+        if let Some(view_radius) = cli.view_radius {
+            config.view_radius = view_radius;
+        }
+        if let Some(client_timeout) = cli.client_timeout {
+            config.client_timeout = client_timeout;
+        }
+        if let Some(log_level) = cli.log_level {
+            config.log_level = log_level;
+        }
+        if cli.admin_port.is_some() {
+            config.admin_port = cli.admin_port;
+        }
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn read(path: PathBuf) -> Result<ServerConfig, ConfigError> {
+        let text = fs::read_to_string(&path).map_err(|e| ConfigError::Read(path.clone(), e))?;
+        toml::from_str(&text).map_err(|e| ConfigError::Parse(path, e))
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        if !(1..=MAX_PLAYERS_LIMIT).contains(&self.max_players) {
+            return Err(ConfigError::Invalid(format!(
+                "max_players must be between 1 and {}, got {}",
+                MAX_PLAYERS_LIMIT, self.max_players
+            )));
+        }
+        if self.max_rooms == 0 {
+            return Err(ConfigError::Invalid(
+                "max_rooms must be at least 1".to_string(),
+            ));
+        }
+        //written so NaN fails too
+        if !(self.tick_rate > 0. && self.tick_rate.is_finite()) {
+            return Err(ConfigError::Invalid(format!(
+                "tick_rate must be a positive number, got {}",
+                self.tick_rate
+            )));
+        }
+        if !(self.view_radius > 0. && self.view_radius.is_finite()) {
+            return Err(ConfigError::Invalid(format!(
+                "view_radius must be a positive number, got {}",
+                self.view_radius
+            )));
+        }
+        if !(self.client_timeout > 0. && self.client_timeout.is_finite()) {
+            return Err(ConfigError::Invalid(format!(
+                "client_timeout must be a positive number of seconds, got {}",
+                self.client_timeout
+            )));
+        }
+        if self.log_level.parse::<Level>().is_err() {
+            return Err(ConfigError::Invalid(format!(
+                "log_level must be error, warn, info, debug or trace, got {:?}",
+                self.log_level
+            )));
+        }
+        Ok(())
+    }
+
+    /// Address the socket is bound to
+    pub fn addr(&self) -> SocketAddr {
+        SocketAddr::new(self.bind, self.port)
+    }
+
+    /// Address of the admin socket, if there is one
+    pub fn admin_addr(&self) -> Option<SocketAddr> {
+        self.admin_port
+            .map(|port| SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), port))
+    }
+
+    /// PvP rules every room is opened with
+    pub fn pvp_rules(&self) -> PvpRules {
+        PvpRules {
+            enabled: self.pvp,
+            friendly_fire: self.friendly_fire,
+            bounty: self.pvp_bounty,
+        }
+    }
+
+    /// How long a client can go quiet, already checked by load()
+    pub fn client_timeout(&self) -> Duration {
+        Duration::from_secs_f32(self.client_timeout)
+    }
+
+    /// Level for bevy's LogPlugin, already checked by load()
+    pub fn level(&self) -> Level {
+        self.log_level.parse().unwrap_or(Level::INFO)
+    }
+}
+
+/// Why the config couldn't be loaded
+pub enum ConfigError {
+    Read(PathBuf, io::Error),
+    Parse(PathBuf, toml::de::Error),
+    Invalid(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::Read(path, e) => write!(f, "Could not read {}: {}", path.display(), e),
+            ConfigError::Parse(path, e) => write!(f, "Could not parse {}: {}", path.display(), e),
+            ConfigError::Invalid(reason) => write!(f, "Invalid config: {}", reason),
+        }
+    }
+}
@@ -1,45 +1,8 @@
-use bevy::prelude::*;
-
-//setting window constants
-pub const WIN_W: f32 = 1280.;
-pub const WIN_H: f32 = 720.;
-
-pub const WIN_W_CENTER: f32 = WIN_W / 2.;
-pub const WIN_H_CENTER: f32 = WIN_H / 2.;
-
-//setting level constants
-pub const TILE_SIZE: u32 = 32;
-
-//REMEMBER TO CHANGE THIS WHEN WE CHANGE MAP SIZE
-pub const OCEAN_LEVEL_H: f32 = 4000.;
-pub const OCEAN_LEVEL_W: f32 = 4000.;
-pub const OCEAN_H_CENTER: f32 = OCEAN_LEVEL_H / 2.;
-pub const OCEAN_W_CENTER: f32 = OCEAN_LEVEL_W / 2.;
-
-pub const SAND_LEVEL_H: f32 = 3000.;
-pub const SAND_LEVEL_W: f32 = 3000.;
-pub const SAND_H_CENTER: f32 = SAND_LEVEL_H / 2.;
-pub const SAND_W_CENTER: f32 = SAND_LEVEL_W / 2.;
-
-pub const DUNGEON_LEVEL_H: f32 = 16000.;
-pub const DUNGEON_LEVEL_W: f32 = 16000.;
-pub const DUNGEON_H_CENTER: f32 = DUNGEON_LEVEL_H / 2.;
-pub const DUNGEON_W_CENTER: f32 = DUNGEON_LEVEL_W / 2.;
-
-//for boat (change later, should not have bounds determined
-//in different ways for different entities)
-pub const BOUNDS: Vec2 = Vec2::new(OCEAN_LEVEL_W, OCEAN_LEVEL_H);
-
-//Enemy Codes
-pub const PLAYER: i32 = 0;
-pub const BOAT: i32 = 1;
-pub const BAT: i32 = 2;
-pub const KRAKEN: i32 = 3;
-pub const GHOSTSHIP: i32 = 4;
-pub const ROCK: i32 = 5;
-pub const RSKELETON: i32 = 6;
-pub const MSKELETON: i32 = 7;
-pub const WHIRLPOOL: i32 = 8;
+//level and ocean constants are shared with the client
+pub use protocol::gameworld_data::{OCEAN_LEVEL_H, OCEAN_LEVEL_W};
+
+//Enemy Codes (shared with the client)
+pub use protocol::gameworld_data::{GHOSTSHIP, KRAKEN, STORM, WHIRLPOOL};
 
 //All Enemy constants
 pub const GHOSTSHIP_PROJECTILE_LIFETIME: f32 = 5.;
@@ -59,3 +22,20 @@ pub const KRAKEN_ATTACK_DIST: f32 = 800.;
 pub const KRAKEN_MOVEMENT_SPEED: f32 = 150.;
 pub const KRAKEN_AGRO_STOP: f32 = 300.;
 pub const KRAKEN_AGRO_RANGE: f32 = 1000.;
+pub const KRAKEN_LIFETIME: f32 = 120.;
+pub const KRAKEN_ATTACK_COOLDOWN: f32 = 2.5;
+pub const KRAKEN_SPAWN_TIME: (f32, f32) = (30., 60.);
+
+pub const GHOSTSHIP_LIFETIME: f32 = 120.;
+pub const GHOSTSHIP_ATTACK_COOLDOWN: f32 = 2.5;
+pub const GHOSTSHIP_SPAWN_TIME: (f32, f32) = (30., 70.);
+
+pub const WHIRLPOOL_HP: f32 = 2.;
+pub const WHIRLPOOL_LIFETIME: f32 = 120.;
+pub const WHIRLPOOL_MIN_DISTANCE: f32 = 300.;
+pub const WHIRLPOOL_SPAWN_TIME: (f32, f32) = (25., 35.);
+
+pub const STORM_HP: f32 = 1.;
+pub const STORM_LIFETIME: f32 = 120.;
+pub const STORM_MIN_DISTANCE: f32 = 500.;
+pub const STORM_SPAWN_TIME: (f32, f32) = (30., 35.);
@@ -0,0 +1,40 @@
+use bevy::prelude::*;
+use protocol::components::Area;
+use protocol::dungeon::DungeonInstance;
+use std::collections::HashMap;
+use std::fmt;
+
+/// A room's dungeons keyed by the zone of their island. One is made the first
+/// time anyone goes into it and kept, cleared or not, until the room closes
+#[derive(Component, Default)]
+pub struct Dungeons {
+    pub list: HashMap<u32, DungeonInstance>,
+}
+
+/// Why a player's claim to have gone into a dungeon, or killed something in
+/// one, was thrown out
+#[derive(Debug, PartialEq)]
+pub enum DungeonRejection {
+    /// The room's ocean has no island with that zone
+    NoIsland { island: u32 },
+    /// The player isn't down in that island's dungeon
+    NotInside { island: u32, area: Area },
+    /// Nobody has been in the dungeon yet, so there's nothing in it to kill
+    NotOpened { island: u32 },
+}
+
+impl fmt::Display for DungeonRejection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DungeonRejection::NoIsland { island } => write!(f, "there's no island {}", island),
+            DungeonRejection::NotInside { island, area } => write!(
+                f,
+                "not in the dungeon of island {}, the player is {}",
+                island, area
+            ),
+            DungeonRejection::NotOpened { island } => {
+                write!(f, "nobody has been in the dungeon of island {}", island)
+            }
+        }
+    }
+}
@@ -0,0 +1,206 @@
+use bevy::log::{debug, info, warn};
+use protocol::dungeon::{DungeonInstance, DungeonParams};
+use protocol::messages::{DungeonKill, ServerMessage};
+use protocol::ocean::OceanParams;
+use protocol::reliable::Channel;
+use protocol::rng::RngStream;
+use rand::Rng;
+use std::net::{SocketAddr, UdpSocket};
+
+use crate::dungeons::components::*;
+use crate::network::components::*;
+use crate::network::systems::{send, send_to_player};
+use crate::rooms::components::RoomQueryItem;
+
+/*   VALIDATE_DUNGEON FUNCTION   */
+/// Checks that a player going into or killing something in an island's
+/// dungeon is actually down there. Their area always arrives before either,
+/// both go over the same reliable channel
+pub fn validate_dungeon(
+    player: &Player,
+    island: u32,
+    ocean: &OceanParams,
+) -> Result<(), DungeonRejection> {
+    if island > ocean.start_island() {
+        return Err(DungeonRejection::NoIsland { island });
+    }
+
+    match player.area {
+        Area::Dungeon { island: inside, .. } if inside == island => Ok(()),
+        area => Err(DungeonRejection::NotInside { island, area }),
+    }
+}
+
+/*   REJECT_DUNGEON FUNCTION   */
+/// Logs a thrown out dungeon claim, counted with the player's rejected hits
+fn reject_dungeon(room: &mut RoomQueryItem, player_id: i32, what: &str, reason: DungeonRejection) {
+    let record = room.attacks.list.entry(player_id).or_default();
+    record.rejected += 1;
+    warn!(
+        "Rejected {} from player #{} in room {} ({} rejected so far): {}",
+        what, player_id, room.room.code, record.rejected, reason
+    );
+}
+
+/*   HANDLE_ENTER_DUNGEON FUNCTION   */
+/// Sends a player going down into an island's dungeon everything they need to
+/// build it, making the dungeon first if they're the first one in
+pub fn handle_enter_dungeon(
+    socket: &UdpSocket,
+    connections: &mut Connections,
+    src: SocketAddr,
+    room: &mut RoomQueryItem,
+    player_id: i32,
+    island: u32,
+) {
+    let Some(player) = room.players.get(player_id) else {
+        return;
+    };
+    if let Err(reason) = validate_dungeon(player, island, &room.ocean.params) {
+        reject_dungeon(room, player_id, "dungeon", reason);
+        return;
+    }
+
+    let instance = room.dungeons.list.entry(island).or_insert_with(|| {
+        let instance = DungeonInstance::new(
+            island,
+            DungeonParams::new(room.rng.stream(RngStream::Dungeons).gen()),
+        );
+        info!(
+            "Room {} opened the dungeon of island {} on seed {} with {} enemies",
+            room.room.code,
+            island,
+            instance.params.seed,
+            instance.enemies.len()
+        );
+        instance
+    });
+
+    info!(
+        "Player #{} went into the dungeon of island {}, {} enemies left",
+        player_id,
+        island,
+        instance.enemies.len()
+    );
+    send(
+        socket,
+        connections,
+        src,
+        &ServerMessage::DungeonEntered(instance.clone()),
+        Channel::Reliable,
+    );
+}
+
+/*   HANDLE_DUNGEON_KILL FUNCTION   */
+/// Takes a killed enemy out of its dungeon and tells everyone in it. When
+/// two players kill the same one, the first to be heard gets the loot and the
+/// other's claim is dropped
+pub fn handle_dungeon_kill(
+    socket: &UdpSocket,
+    connections: &mut Connections,
+    room: &mut RoomQueryItem,
+    player_id: i32,
+    island: u32,
+    id: i32,
+) {
+    let Some(player) = room.players.get(player_id) else {
+        return;
+    };
+    let checked = match validate_dungeon(player, island, &room.ocean.params) {
+        Ok(()) if !room.dungeons.list.contains_key(&island) => {
+            Err(DungeonRejection::NotOpened { island })
+        }
+        checked => checked,
+    };
+    if let Err(reason) = checked {
+        reject_dungeon(room, player_id, "kill", reason);
+        return;
+    }
+    let Some(instance) = room.dungeons.list.get_mut(&island) else {
+        return;
+    };
+
+    //already dead, the kill crossed paths with dungeon_enemy_died
+    let Some(index) = instance.enemies.iter().position(|enemy| enemy.id == id) else {
+        return;
+    };
+    let enemy = instance.enemies.remove(index);
+
+    debug!(
+        "Player #{} killed enemy [{}] in the dungeon of island {}",
+        player_id, id, island
+    );
+    if instance.cleared() {
+        info!(
+            "Room {} cleared the dungeon of island {}",
+            room.room.code, island
+        );
+    }
+
+    //only the players down there have the enemy
+    let kill = ServerMessage::DungeonEnemyDied(DungeonKill {
+        island,
+        enemy,
+        by: player_id,
+    });
+    for player in room.players.iter() {
+        let inside = matches!(player.area, Area::Dungeon { island: under, .. } if under == island);
+        if inside {
+            send_to_player(socket, connections, player, &kill, Channel::Reliable);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn player_in(area: Area) -> Player {
+        Player {
+            id: 1,
+            area,
+            ..Default::default()
+        }
+    }
+
+    fn dungeon(island: u32) -> Area {
+        Area::Dungeon {
+            island,
+            island_type: IslandType::Level1,
+        }
+    }
+
+    #[test]
+    fn players_down_in_the_dungeon_get_in() {
+        let ocean = OceanParams::new(7);
+
+        assert_eq!(validate_dungeon(&player_in(dungeon(2)), 2, &ocean), Ok(()));
+    }
+
+    #[test]
+    fn players_anywhere_else_are_turned_away() {
+        let ocean = OceanParams::new(7);
+        let ashore = Area::Island {
+            island: 2,
+            island_type: IslandType::Level1,
+        };
+
+        for area in [Area::MainMenu, Area::Ocean, ashore, dungeon(3)] {
+            assert_eq!(
+                validate_dungeon(&player_in(area), 2, &ocean),
+                Err(DungeonRejection::NotInside { island: 2, area })
+            );
+        }
+    }
+
+    #[test]
+    fn islands_the_ocean_doesnt_have_are_turned_away() {
+        let ocean = OceanParams::new(7);
+        let island = ocean.start_island() + 1;
+
+        assert_eq!(
+            validate_dungeon(&player_in(dungeon(island)), island, &ocean),
+            Err(DungeonRejection::NoIsland { island })
+        );
+    }
+}
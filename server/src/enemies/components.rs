@@ -0,0 +1,213 @@
+use bevy::prelude::*;
+use protocol::combat::Weapon;
+use protocol::rng::{GameRng, RngStream};
+use rand::Rng;
+use std::collections::HashMap;
+use std::fmt;
+use std::time::Instant;
+
+use crate::data::gameworld_data::*;
+use crate::simulation::components::secs_to_ticks;
+
+/// Counts the ticks down to the next spawn of one enemy type. The next wait is
+/// picked at random from `range`, in seconds, every time the timer goes off
+pub struct SpawnTimer {
+    pub etype: i32,
+    pub range: (f32, f32),
+    pub ticks_left: u32,
+}
+
+impl SpawnTimer {
+    pub fn new(etype: i32, range: (f32, f32), tick_rate: f64, rng: &mut GameRng) -> SpawnTimer {
+        SpawnTimer {
+            etype,
+            range,
+            ticks_left: random_ticks(range, tick_rate, rng),
+        }
+    }
+
+    /// Counts down one tick, true once it's time to spawn
+    pub fn tick(&mut self) -> bool {
+        self.ticks_left = self.ticks_left.saturating_sub(1);
+        self.ticks_left == 0
+    }
+
+    /// Starts waiting for the next spawn
+    pub fn restart(&mut self, tick_rate: f64, rng: &mut GameRng) {
+        self.ticks_left = random_ticks(self.range, tick_rate, rng);
+    }
+}
+
+fn random_ticks(range: (f32, f32), tick_rate: f64, rng: &mut GameRng) -> u32 {
+    let secs = rng
+        .stream(RngStream::OceanEnemies)
+        .gen_range(range.0..range.1);
+    secs_to_ticks(secs, tick_rate)
+}
+
+/// Spawn timers for every enemy that shows up in a room's ocean
+#[derive(Component)]
+pub struct SpawnTimers {
+    pub list: Vec<SpawnTimer>,
+}
+
+impl SpawnTimers {
+    pub fn init(tick_rate: f64, rng: &mut GameRng) -> SpawnTimers {
+        SpawnTimers {
+            list: vec![
+                SpawnTimer::new(KRAKEN, KRAKEN_SPAWN_TIME, tick_rate, rng),
+                SpawnTimer::new(GHOSTSHIP, GHOSTSHIP_SPAWN_TIME, tick_rate, rng),
+                SpawnTimer::new(WHIRLPOOL, WHIRLPOOL_SPAWN_TIME, tick_rate, rng),
+                SpawnTimer::new(STORM, STORM_SPAWN_TIME, tick_rate, rng),
+            ],
+        }
+    }
+}
+
+/// What an ocean enemy of one type spawns with
+pub struct OceanEnemy {
+    pub hp: f32,
+    /// Seconds until it leaves the ocean on its own
+    pub lifetime: f32,
+    /// Seconds between attacks, None for enemies that don't attack
+    pub cooldown: Option<f32>,
+    /// Closest it can spawn to a boat
+    pub min_distance: f32,
+    pub z: f32,
+}
+
+impl OceanEnemy {
+    /// Stats of the enemy type, None for enemies that don't show up at sea
+    pub fn of(etype: i32) -> Option<OceanEnemy> {
+        let (hp, lifetime, cooldown, min_distance, z) = match etype {
+            KRAKEN => (
+                KRAKEN_MAX_HP,
+                KRAKEN_LIFETIME,
+                Some(KRAKEN_ATTACK_COOLDOWN),
+                0.,
+                900.,
+            ),
+            GHOSTSHIP => (
+                GHOSTSHIP_MAX_HP,
+                GHOSTSHIP_LIFETIME,
+                Some(GHOSTSHIP_ATTACK_COOLDOWN),
+                0.,
+                900.,
+            ),
+            WHIRLPOOL => (
+                WHIRLPOOL_HP,
+                WHIRLPOOL_LIFETIME,
+                None,
+                WHIRLPOOL_MIN_DISTANCE,
+                0.,
+            ),
+            STORM => (STORM_HP, STORM_LIFETIME, None, STORM_MIN_DISTANCE, 0.),
+            _ => return None,
+        };
+
+        Some(OceanEnemy {
+            hp,
+            lifetime,
+            cooldown,
+            min_distance,
+            z,
+        })
+    }
+}
+
+/// Server only state of a live enemy that never goes over the wire
+pub struct EnemyState {
+    /// Time until the enemy can fire again, None for enemies that don't attack
+    pub cooldown: Option<Timer>,
+    /// Seconds until the enemy leaves the ocean on its own
+    pub lifetime: f32,
+}
+
+/// State of every live enemy in a room, keyed by enemy id
+#[derive(Component, Default)]
+pub struct EnemyStates {
+    pub list: HashMap<i32, EnemyState>,
+}
+
+/// Why a player's claim to have hit an enemy or another player's boat was thrown out
+#[derive(Debug, PartialEq)]
+pub enum HitRejection {
+    /// The room doesn't allow players to hurt each other
+    PvpOff,
+    /// A player claimed to have hit their own boat
+    OwnBoat,
+    /// The target sails with the attacker and friendly fire is off
+    SameCrew { crew: u8 },
+    /// The attacker or the target has no boat afloat
+    NotAfloat { id: i32 },
+    /// The hit was claimed with another weapon than the one the server knows
+    /// the player has, a boat's cannon or the pirate weapon they swapped to
+    WrongWeapon { claimed: Weapon, held: Weapon },
+    /// More damage than the weapon does, or none at all
+    BadDamage { dmg: f32, max: f32 },
+    /// The enemy was further away than the weapon reaches
+    OutOfRange { distance: f32, range: f32 },
+    /// Hits from the same weapon came faster than it can attack
+    TooSoon { since: f32, cooldown: f32 },
+}
+
+impl fmt::Display for HitRejection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HitRejection::PvpOff => write!(f, "pvp is off in this room"),
+            HitRejection::OwnBoat => write!(f, "players can't hit their own boat"),
+            HitRejection::SameCrew { crew } => {
+                write!(f, "both are on crew {} and friendly fire is off", crew + 1)
+            }
+            HitRejection::NotAfloat { id } => write!(f, "player #{} has no boat afloat", id),
+            HitRejection::WrongWeapon { claimed, held } => {
+                write!(f, "claimed a {:?} hit holding a {:?}", claimed, held)
+            }
+            HitRejection::BadDamage { dmg, max } => {
+                write!(f, "claimed {} damage, weapon does at most {}", dmg, max)
+            }
+            HitRejection::OutOfRange { distance, range } => {
+                write!(
+                    f,
+                    "target was {:.0} away, weapon reaches {:.0}",
+                    distance, range
+                )
+            }
+            HitRejection::TooSoon { since, cooldown } => write!(
+                f,
+                "hit {:.2}s after the last one, weapon cooldown is {:.2}s",
+                since, cooldown
+            ),
+        }
+    }
+}
+
+/// Recent attacks of one player, used to check their next damage claim
+pub struct AttackRecord {
+    /// When the player last landed an accepted hit, with any weapon
+    pub last_hit: Option<Instant>,
+    /// How many of this player's claims have been rejected
+    pub rejected: u32,
+    /// Pirate weapon the player last swapped to, pirates start with a sword
+    pub weapon: Weapon,
+    /// When the pirate's position was last taken, None until their first
+    /// update in an area. Bounds how far the next one can move them
+    pub moved_at: Option<Instant>,
+}
+
+impl Default for AttackRecord {
+    fn default() -> AttackRecord {
+        AttackRecord {
+            last_hit: None,
+            rejected: 0,
+            weapon: Weapon::Sword,
+            moved_at: None,
+        }
+    }
+}
+
+/// Attack records keyed by player id, reset whenever a slot is given to a new player
+#[derive(Component, Default)]
+pub struct AttackRecords {
+    pub list: HashMap<i32, AttackRecord>,
+}
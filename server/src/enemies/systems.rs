@@ -0,0 +1,490 @@
+use bevy::prelude::*;
+use protocol::combat::{Weapon, HIT_COOLDOWN_TOLERANCE, HIT_RANGE_SLACK};
+use protocol::rng::{GameRng, RngStream};
+use rand::Rng;
+use std::time::Instant;
+
+use crate::config::ServerConfig;
+use crate::data::gameworld_data::*;
+use crate::enemies::components::*;
+use crate::network::components::*;
+
+/*   SPAWN_ENEMIES FUNCTION   */
+/// Spawns ocean enemies in every room when its timers go off. Timers count
+/// ticks and only run while somebody in the room is at sea, so nothing piles
+/// up while everyone is on an island
+pub fn spawn_enemies(
+    config: Res<ServerConfig>,
+    mut rooms: Query<(
+        &mut SpawnTimers,
+        &Players,
+        &mut Counter,
+        &mut EnemyLists,
+        &mut EnemyStates,
+        &mut GameRng,
+    )>,
+) {
+    for (mut timers, players, mut counter, mut enemies, mut states, mut rng) in rooms.iter_mut() {
+        let boats: Vec<Vec2> = players
+            .iter()
+            .filter(|player| player.boat)
+            .map(|player| player.pos.xy())
+            .collect();
+
+        if boats.is_empty() {
+            continue;
+        }
+
+        for spawn_timer in timers.list.iter_mut() {
+            if !spawn_timer.tick() {
+                continue;
+            }
+            spawn_timer.restart(config.tick_rate, &mut rng);
+
+            let etype = spawn_timer.etype;
+            let Some(stats) = OceanEnemy::of(etype) else {
+                warn!("Undefined enemy type for spawn_enemies(): {}", etype);
+                continue;
+            };
+
+            // Generate random coordinates within the ocean bounds
+            let rng = rng.stream(RngStream::OceanEnemies);
+            let mut spawn_pos = Vec2::new(
+                rng.gen_range(-(OCEAN_LEVEL_W / 2.0)..(OCEAN_LEVEL_W / 2.0)),
+                rng.gen_range(-(OCEAN_LEVEL_H / 2.0)..(OCEAN_LEVEL_H / 2.0)),
+            );
+
+            // If too close to a boat, push it away
+            for boat_pos in boats.iter() {
+                let offset = spawn_pos - *boat_pos;
+                if offset.length() < stats.min_distance {
+                    spawn_pos = *boat_pos + offset.normalize_or(Vec2::Y) * stats.min_distance;
+                }
+            }
+
+            spawn_enemy(
+                &mut counter,
+                &mut enemies,
+                &mut states,
+                etype,
+                &stats,
+                spawn_pos,
+            );
+        }
+    }
+}
+
+/*   SPAWN_ENEMY FUNCTION   */
+/// Puts a new enemy in a room's ocean at `pos`, it goes out to the players who
+/// can see it with the next snapshot. Returns the id it was given
+pub fn spawn_enemy(
+    counter: &mut Counter,
+    enemies: &mut EnemyLists,
+    states: &mut EnemyStates,
+    etype: i32,
+    stats: &OceanEnemy,
+    pos: Vec2,
+) -> i32 {
+    let enemy = Enemy {
+        id: counter.next_id(),
+        etype,
+        pos: pos.extend(stats.z),
+        animation_index: 0,
+        hp: stats.hp,
+        alive: true,
+        target_id: -1,
+    };
+
+    debug!("Spawned enemy [{}] of type {}", enemy.id, etype);
+
+    states.list.insert(
+        enemy.id,
+        EnemyState {
+            cooldown: stats
+                .cooldown
+                .map(|secs| Timer::from_seconds(secs, TimerMode::Once)),
+            lifetime: stats.lifetime,
+        },
+    );
+    let id = enemy.id;
+    enemies.update.list.push(enemy);
+    id
+}
+
+/*   ENEMY_LIFETIMES FUNCTION   */
+/// Removes enemies that have been around for too long, and forgets the state of
+/// enemies that have died
+pub fn enemy_lifetimes(time: Res<Time>, mut rooms: Query<(&mut EnemyLists, &mut EnemyStates)>) {
+    for (mut enemies, mut states) in rooms.iter_mut() {
+        let live: Vec<i32> = enemies.update.list.iter().map(|enemy| enemy.id).collect();
+        states.list.retain(|id, _| live.contains(id));
+
+        let mut index = 0;
+        while index < enemies.update.list.len() {
+            let id = enemies.update.list[index].id;
+
+            let expired = match states.list.get_mut(&id) {
+                Some(state) => {
+                    state.lifetime -= time.delta_seconds();
+                    state.lifetime <= 0.
+                }
+                None => false,
+            };
+
+            if expired {
+                debug!("Enemy [{}] left the ocean", id);
+                states.list.remove(&id);
+                let dead = enemies.update.list.remove(index);
+                enemies.dead.list.push(dead);
+            } else {
+                index += 1;
+            }
+        }
+    }
+}
+
+/*   NEAREST_BOAT FUNCTION   */
+/// The closest player at sea to `pos` and how far away they are
+fn nearest_boat(players: &Players, pos: Vec3) -> Option<(&Player, f32)> {
+    players
+        .iter()
+        .filter(|player| player.boat)
+        .map(|player| (player, pos.xy().distance(player.pos.xy())))
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+}
+
+/*   ENEMY_PROJ_HANDLE FUNCTION   */
+/// Fires a projectile from every enemy whose cooldown is up at the nearest
+/// boat in range. Fired projectiles are sent to the clients with the next snapshot
+pub fn enemy_proj_handle(
+    time: Res<Time>,
+    mut rooms: Query<(
+        &mut EnemyLists,
+        &mut EnemyStates,
+        &mut Projectiles,
+        &Players,
+    )>,
+) {
+    for (mut enemies, mut states, mut projectiles, players) in rooms.iter_mut() {
+        for enemy in enemies.update.list.iter_mut() {
+            let Some(cooldown) = states
+                .list
+                .get_mut(&enemy.id)
+                .and_then(|state| state.cooldown.as_mut())
+            else {
+                continue;
+            };
+
+            cooldown.tick(time.delta());
+            if !cooldown.finished() {
+                continue;
+            }
+
+            let (attack_dist, lifetime, speed, attack_cooldown) = match enemy.etype {
+                KRAKEN => (
+                    KRAKEN_ATTACK_DIST,
+                    KRAKEN_PROJECTILE_LIFETIME,
+                    KRAKEN_PROJECTILE_SPEED,
+                    KRAKEN_ATTACK_COOLDOWN,
+                ),
+                GHOSTSHIP => (
+                    GHOSTSHIP_ATTACK_DIST,
+                    GHOSTSHIP_PROJECTILE_LIFETIME,
+                    GHOSTSHIP_PROJECTILE_SPEED,
+                    GHOSTSHIP_ATTACK_COOLDOWN,
+                ),
+                _ => {
+                    warn!("Undefined enemy type for enemy_proj_handle()");
+                    continue;
+                }
+            };
+
+            let Some((player, distance_to_player)) = nearest_boat(players, enemy.pos) else {
+                continue;
+            };
+
+            if distance_to_player > attack_dist {
+                continue;
+            }
+
+            *cooldown = Timer::from_seconds(attack_cooldown, TimerMode::Once);
+            enemy.target_id = player.id;
+
+            let original_direction = (player.pos - enemy.pos).normalize();
+            let angle = original_direction.x.atan2(original_direction.y);
+            let angle_direction = Vec3::new(angle.sin(), angle.cos(), 0.0).normalize();
+
+            let projectile_start_pos = enemy.pos + angle_direction * 10.0;
+
+            debug!("Player #{} is in range of entity [{}]", player.id, enemy.id);
+            projectiles.list.push(Projectile {
+                owner_id: enemy.id,
+                etype: enemy.etype,
+                velocity: Velocity {
+                    v: angle_direction.truncate() * speed,
+                },
+                translation: projectile_start_pos,
+                lifetime,
+            });
+        }
+    }
+}
+
+/*   ENEMY_MOVEMENT FUNCTION   */
+/// Moves krakens and ghost ships towards the nearest boat in agro range.
+/// Whirlpools and storms stay where they spawned
+pub fn enemy_movement(time: Res<Time>, mut rooms: Query<(&mut EnemyLists, &Players)>) {
+    for (mut enemies, players) in rooms.iter_mut() {
+        for enemy in enemies.update.list.iter_mut() {
+            let (agro_range, agro_stop, speed) = match enemy.etype {
+                KRAKEN => (KRAKEN_AGRO_RANGE, KRAKEN_AGRO_STOP, KRAKEN_MOVEMENT_SPEED),
+                GHOSTSHIP => (
+                    GHOSTSHIP_AGRO_RANGE,
+                    GHOSTSHIP_AGRO_STOP,
+                    GHOSTSHIP_MOVEMENT_SPEED,
+                ),
+                WHIRLPOOL | STORM => continue,
+                _ => {
+                    warn!("Undefined enemy type: {}", enemy.etype);
+                    continue;
+                }
+            };
+
+            let Some((player, distance_to_player)) = nearest_boat(players, enemy.pos) else {
+                continue;
+            };
+
+            //Check
+            if distance_to_player > agro_range || distance_to_player <= agro_stop {
+                continue;
+            }
+
+            //Gets direction the enemy will be going, staying at its own height
+            let mut direction = player.pos - enemy.pos;
+            direction.z = 0.;
+            let velocity = direction.normalize() * speed;
+
+            //Moves enemy
+            enemy.pos += velocity * time.delta_seconds();
+        }
+    }
+}
+
+/*   VALIDATE_HIT FUNCTION   */
+/// Checks a player's claim to have hit something at `target` against where the
+/// server has them, the weapon the server knows they have and when they last
+/// landed a hit. Every check uses that weapon, never the one that was claimed
+pub fn validate_hit(
+    attacker: &Player,
+    target: Vec3,
+    attack: &Damage,
+    record: &AttackRecord,
+    now: Instant,
+) -> Result<(), HitRejection> {
+    //pirates don't fight at sea, and a sunk boat has nothing left to fire
+    if attacker.area == Area::Ocean && !attacker.boat {
+        return Err(HitRejection::NotAfloat { id: attacker.id });
+    }
+
+    let weapon = if attacker.boat {
+        Weapon::Cannon
+    } else {
+        record.weapon
+    };
+    if attack.weapon != weapon {
+        return Err(HitRejection::WrongWeapon {
+            claimed: attack.weapon,
+            held: weapon,
+        });
+    }
+
+    //written so NaN fails too
+    if !(attack.dmg > 0. && attack.dmg <= weapon.damage()) {
+        return Err(HitRejection::BadDamage {
+            dmg: attack.dmg,
+            max: weapon.damage(),
+        });
+    }
+
+    //boats are where simulate_boats last put them, pirates no further than
+    //they could have walked from there, see move_pirate
+    let distance = attacker.pos.xy().distance(target.xy());
+    let range = weapon.range() + HIT_RANGE_SLACK;
+    if distance > range {
+        return Err(HitRejection::OutOfRange { distance, range });
+    }
+
+    //one weapon at a time, switching doesn't buy another attack
+    if let Some(last) = record.last_hit {
+        let since = now.duration_since(last).as_secs_f32();
+        if since < weapon.cooldown() * HIT_COOLDOWN_TOLERANCE {
+            return Err(HitRejection::TooSoon {
+                since,
+                cooldown: weapon.cooldown(),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use protocol::combat::CANNON_COOLDOWN;
+    use std::time::Duration;
+
+    fn boat() -> Player {
+        Player {
+            id: 1,
+            boat: true,
+            area: Area::Ocean,
+            ..default()
+        }
+    }
+
+    fn pirate() -> Player {
+        Player {
+            id: 1,
+            boat: false,
+            area: Area::Island {
+                island: 0,
+                island_type: IslandType::Level1,
+            },
+            ..default()
+        }
+    }
+
+    fn hit(weapon: Weapon) -> Damage {
+        Damage {
+            target_id: 9,
+            dmg: weapon.damage(),
+            weapon,
+        }
+    }
+
+    fn check(
+        attacker: &Player,
+        target: Vec3,
+        attack: &Damage,
+        record: &AttackRecord,
+    ) -> Result<(), HitRejection> {
+        validate_hit(attacker, target, attack, record, Instant::now())
+    }
+
+    #[test]
+    fn hits_in_reach_with_the_weapon_in_hand_count() {
+        let record = AttackRecord::default();
+
+        assert_eq!(
+            check(&boat(), Vec3::X * 300., &hit(Weapon::Cannon), &record),
+            Ok(())
+        );
+        assert_eq!(
+            check(&pirate(), Vec3::X * 60., &hit(Weapon::Sword), &record),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn pirates_at_sea_have_nothing_to_fire() {
+        let stranded = Player {
+            boat: false,
+            ..boat()
+        };
+
+        assert_eq!(
+            check(
+                &stranded,
+                Vec3::ZERO,
+                &hit(Weapon::Cannon),
+                &AttackRecord::default()
+            ),
+            Err(HitRejection::NotAfloat { id: 1 })
+        );
+    }
+
+    #[test]
+    fn hits_are_checked_against_the_weapon_the_server_knows_about() {
+        let mut record = AttackRecord::default();
+
+        //the musket carries much further than the sword they actually hold
+        assert_eq!(
+            check(&pirate(), Vec3::X * 1000., &hit(Weapon::Musket), &record),
+            Err(HitRejection::WrongWeapon {
+                claimed: Weapon::Musket,
+                held: Weapon::Sword
+            })
+        );
+        assert_eq!(
+            check(&boat(), Vec3::ZERO, &hit(Weapon::Sword), &record),
+            Err(HitRejection::WrongWeapon {
+                claimed: Weapon::Sword,
+                held: Weapon::Cannon
+            })
+        );
+
+        record.weapon = Weapon::Musket;
+        assert_eq!(
+            check(&pirate(), Vec3::X * 1000., &hit(Weapon::Musket), &record),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn damage_past_what_the_weapon_does_is_thrown_out() {
+        let record = AttackRecord::default();
+        let max = Weapon::Cannon.damage();
+
+        for dmg in [max + 1., 0., -1., f32::NAN] {
+            let attack = Damage {
+                dmg,
+                ..hit(Weapon::Cannon)
+            };
+            assert!(matches!(
+                check(&boat(), Vec3::ZERO, &attack, &record),
+                Err(HitRejection::BadDamage { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn targets_out_of_reach_are_thrown_out() {
+        let record = AttackRecord::default();
+        let range = Weapon::Sword.range() + HIT_RANGE_SLACK;
+
+        assert_eq!(
+            check(
+                &pirate(),
+                Vec3::X * (range + 1.),
+                &hit(Weapon::Sword),
+                &record
+            ),
+            Err(HitRejection::OutOfRange {
+                distance: range + 1.,
+                range
+            })
+        );
+    }
+
+    #[test]
+    fn hits_faster_than_the_cooldown_are_thrown_out() {
+        let now = Instant::now();
+        let record = AttackRecord {
+            last_hit: Some(now),
+            ..default()
+        };
+        let attack = hit(Weapon::Cannon);
+
+        let soon = now + Duration::from_secs_f32(CANNON_COOLDOWN / 2.);
+        assert!(matches!(
+            validate_hit(&boat(), Vec3::ZERO, &attack, &record, soon),
+            Err(HitRejection::TooSoon { .. })
+        ));
+        let later = now + Duration::from_secs_f32(CANNON_COOLDOWN);
+        assert_eq!(
+            validate_hit(&boat(), Vec3::ZERO, &attack, &record, later),
+            Ok(())
+        );
+    }
+}
@@ -0,0 +1,107 @@
+use bevy::ecs::query::QueryData;
+use bevy::prelude::*;
+use protocol::delta::DeltaEncoder;
+use std::collections::{HashMap, HashSet};
+
+use crate::network::components::*;
+use crate::simulation::components::*;
+
+/// Side of a cell of the spatial grid. About a view radius across, so finding
+/// what's in view only looks at a handful of cells
+pub const GRID_CELL_SIZE: f32 = 1000.;
+
+/// Positions bucketed into square cells, so the ones near a point can be found
+/// without going through all of them
+pub struct SpatialGrid {
+    cells: HashMap<(i32, i32), Vec<(usize, Vec2)>>,
+    //corners of the cells anything is in, so a huge radius doesn't walk empty sea
+    min: (i32, i32),
+    max: (i32, i32),
+}
+
+impl SpatialGrid {
+    /// A grid of `positions`, each of which is found again by its index
+    pub fn build(positions: impl Iterator<Item = Vec2>) -> SpatialGrid {
+        let mut grid = SpatialGrid {
+            cells: HashMap::new(),
+            min: (i32::MAX, i32::MAX),
+            max: (i32::MIN, i32::MIN),
+        };
+
+        for (index, pos) in positions.enumerate() {
+            let cell = cell_of(pos);
+            grid.min = (grid.min.0.min(cell.0), grid.min.1.min(cell.1));
+            grid.max = (grid.max.0.max(cell.0), grid.max.1.max(cell.1));
+            grid.cells.entry(cell).or_default().push((index, pos));
+        }
+        grid
+    }
+
+    /// Indexes of every position within `radius` of `center`, lowest first
+    pub fn within(&self, center: Vec2, radius: f32) -> Vec<usize> {
+        let low = cell_of(center - Vec2::splat(radius));
+        let high = cell_of(center + Vec2::splat(radius));
+        let mut found = Vec::new();
+
+        for x in low.0.max(self.min.0)..=high.0.min(self.max.0) {
+            for y in low.1.max(self.min.1)..=high.1.min(self.max.1) {
+                let Some(cell) = self.cells.get(&(x, y)) else {
+                    continue;
+                };
+                found.extend(
+                    cell.iter()
+                        .filter(|(_, pos)| pos.distance(center) <= radius)
+                        .map(|(index, _)| *index),
+                );
+            }
+        }
+
+        found.sort_unstable();
+        found
+    }
+}
+
+fn cell_of(pos: Vec2) -> (i32, i32) {
+    (
+        (pos.x / GRID_CELL_SIZE).floor() as i32,
+        (pos.y / GRID_CELL_SIZE).floor() as i32,
+    )
+}
+
+/// What each player in a room has been sent, so the enemies coming into their
+/// view and dying in it can be picked out
+#[derive(Component, Default)]
+pub struct Interest {
+    /// The enemies each player at sea knows about, by player id
+    pub known: HashMap<i32, KnownEnemies>,
+}
+
+/// The enemies one player at sea has been sent
+#[derive(Default)]
+pub struct KnownEnemies {
+    /// Ids of the enemies in their view as of the last tick
+    pub ids: HashSet<i32>,
+    /// Turns their view into update_enemies deltas, from the last one they acked
+    pub deltas: DeltaEncoder,
+}
+
+impl KnownEnemies {
+    /// Sends them everything in view again from the next tick on
+    pub fn forget(&mut self) {
+        self.ids.clear();
+        self.deltas.forget();
+    }
+}
+
+/// The parts of a room that go out with every snapshot
+#[derive(QueryData)]
+#[query_data(mutable)]
+pub struct SnapshotRoom {
+    pub players: &'static Players,
+    pub sims: &'static BoatSims,
+    pub tick: &'static ServerTick,
+    pub wind: &'static Wind,
+    pub enemies: &'static mut EnemyLists,
+    pub projectiles: &'static mut Projectiles,
+    pub interest: &'static mut Interest,
+}
@@ -0,0 +1,103 @@
+use protocol::messages::ServerMessage;
+use protocol::reliable::Channel;
+use std::net::UdpSocket;
+
+use crate::interest::components::*;
+use crate::network::components::*;
+use crate::network::systems::send_to_player;
+
+/*   SEND_ENEMIES_IN_VIEW FUNCTION   */
+/// Sends a player at sea every enemy within `radius` of their boat, as a delta
+/// from the last update they acked. The ones that came into view or died in it
+/// since the last tick are sent reliably as well, `known` keeps track of which
+/// those are
+pub fn send_enemies_in_view(
+    socket: &UdpSocket,
+    connections: &mut Connections,
+    player: &Player,
+    known: &mut KnownEnemies,
+    grid: &SpatialGrid,
+    enemies: &EnemyLists,
+    radius: f32,
+) {
+    let in_view: Vec<Enemy> = grid
+        .within(player.pos.truncate(), radius)
+        .into_iter()
+        .map(|index| enemies.update.list[index].clone())
+        .collect();
+
+    let came_into_view: Vec<Enemy> = in_view
+        .iter()
+        .filter(|enemy| !known.ids.contains(&enemy.id))
+        .cloned()
+        .collect();
+    //the ones that died out of view were dropped when they left it
+    let died: Vec<Enemy> = enemies
+        .dead
+        .list
+        .iter()
+        .filter(|enemy| known.ids.contains(&enemy.id))
+        .cloned()
+        .collect();
+    known.ids = in_view.iter().map(|enemy| enemy.id).collect();
+
+    //the whole view every time, the client drops anything that's left it
+    send_to_player(
+        socket,
+        connections,
+        player,
+        &ServerMessage::UpdateEnemies(known.deltas.encode(&in_view)),
+        Channel::Unreliable,
+    );
+
+    if !came_into_view.is_empty() {
+        send_to_player(
+            socket,
+            connections,
+            player,
+            &ServerMessage::NewEnemies(Enemies {
+                list: came_into_view,
+            }),
+            Channel::Reliable,
+        );
+    }
+
+    if !died.is_empty() {
+        send_to_player(
+            socket,
+            connections,
+            player,
+            &ServerMessage::DeadEnemies(Enemies { list: died }),
+            Channel::Reliable,
+        );
+    }
+}
+
+/*   SEND_PROJECTILES_IN_VIEW FUNCTION   */
+/// Sends a player at sea the projectiles fired within `radius` of their boat
+/// since the last tick
+pub fn send_projectiles_in_view(
+    socket: &UdpSocket,
+    connections: &mut Connections,
+    player: &Player,
+    projectiles: &Projectiles,
+    radius: f32,
+) {
+    let center = player.pos.truncate();
+    let list: Vec<Projectile> = projectiles
+        .list
+        .iter()
+        .filter(|projectile| projectile.translation.truncate().distance(center) <= radius)
+        .cloned()
+        .collect();
+
+    if !list.is_empty() {
+        send_to_player(
+            socket,
+            connections,
+            player,
+            &ServerMessage::UpdateProjectiles(Projectiles { list }),
+            Channel::Reliable,
+        );
+    }
+}
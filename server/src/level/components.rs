@@ -1,26 +1,9 @@
 use bevy::prelude::*;
-use serde::Serialize;
+use protocol::ocean::{OceanLayout, OceanParams};
 
-//struct that holds all of the information for an ocean tile
-#[derive(Component, Serialize, Clone)]
-pub struct OceanTile {
-    translation: Vec3,
-    tile_index: usize,
-}
-
-/// implementation for ocean tile
-impl OceanTile {
-    // constructor for ocean tile
-    pub fn new(t: Vec3, ti: usize) -> Self {
-        OceanTile {
-            translation: t,
-            tile_index: ti,
-        }
-    }
-}
-
-///struct that holds the ocean map as a resource
-#[derive(Resource)]
+///struct that holds a room's ocean map
+#[derive(Component)]
 pub struct OceanMap {
-    pub map: Vec<OceanTile>,
+    pub params: OceanParams,
+    pub layout: OceanLayout,
 }
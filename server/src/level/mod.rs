@@ -1,6 +1,2 @@
 pub mod components;
 pub mod systems;
-
-use bevy::prelude::*;
-use systems::*;
-use components::*;
\ No newline at end of file
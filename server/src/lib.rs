@@ -0,0 +1,840 @@
+//! The Sea of Fortune game server. main.rs reads the config and runs the app
+//! built here, integration tests build the same app on a socket of their own.
+
+pub mod admin;
+mod chat;
+pub mod config;
+mod data;
+mod dungeons;
+mod enemies;
+mod interest;
+mod level;
+pub mod network;
+mod pvp;
+mod rooms;
+mod simulation;
+
+use bevy::app::ScheduleRunnerPlugin;
+use bevy::prelude::*;
+use std::collections::HashMap;
+use std::net::{SocketAddr, UdpSocket};
+use std::time::*;
+
+use crate::admin::components::{AdminConsole, Clients, ShuttingDown};
+use crate::admin::systems::*;
+use crate::chat::systems::*;
+use crate::config::ServerConfig;
+use crate::dungeons::systems::*;
+use crate::enemies::systems::*;
+use crate::network::components::*;
+use crate::network::systems::*;
+use crate::pvp::systems::*;
+use crate::rooms::components::*;
+use crate::rooms::systems::*;
+use crate::simulation::components::*;
+use crate::simulation::systems::*;
+use protocol::codec::{CodecError, MAX_PACKET_SIZE, PROTOCOL_VERSION};
+use protocol::combat::MAX_CREWS;
+use protocol::messages::*;
+use protocol::reliable::{Channel, Connection};
+use protocol::rng::{GameRng, RngStream};
+use protocol::simulation::BoatState;
+use rand::Rng;
+
+/*   BUILD_APP FUNCTION   */
+/// The whole server, answering on `socket`, which has to be non-blocking. main()
+/// adds logging and runs it, tests drive it with App::update() instead
+pub fn build_app(config: ServerConfig, socket: UdpSocket) -> App {
+    let mut app = App::new();
+    app.insert_resource(UDP { socket })
+        .init_resource::<Connections>()
+        .insert_resource(Heartbeats::new(config.client_timeout()))
+        .init_resource::<RoomIndex>()
+        .init_resource::<AdminConsole>()
+        .init_resource::<ShuttingDown>()
+        .insert_resource(GameRng::from_seed(config.rng_seed))
+        .insert_resource(Time::<Fixed>::from_hz(config.tick_rate))
+        .add_systems(Update, handle)
+        .add_systems(Update, run_admin_commands.after(handle))
+        .add_systems(Update, resend_reliable.after(run_admin_commands))
+        .add_systems(Update, finish_shutdown.after(resend_reliable))
+        .add_systems(Update, evict_timed_out.after(handle))
+        .add_systems(Update, release_held_slots.after(evict_timed_out))
+        .add_systems(Update, close_empty_rooms.after(release_held_slots))
+        .add_systems(
+            FixedUpdate,
+            (
+                change_wind,
+                simulate_boats,
+                spawn_enemies,
+                enemy_lifetimes,
+                enemy_movement,
+                enemy_proj_handle,
+                broadcast_snapshot,
+            )
+                .chain(),
+        )
+        //headless, the runner wakes up about twice per tick to drain the socket
+        .add_plugins(
+            MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(Duration::from_secs_f64(
+                0.5 / config.tick_rate,
+            ))),
+        )
+        .insert_resource(config);
+    app
+}
+
+pub fn handle(
+    mut commands: Commands,
+    config: Res<ServerConfig>,
+    clients: Clients,
+    mut rooms: Query<RoomQuery>,
+    mut rng: ResMut<GameRng>,
+    mut held: Local<Vec<(SocketAddr, ClientMessage)>>,
+    mut buf: Local<Vec<u8>>,
+) {
+    let Clients {
+        udp,
+        mut connections,
+        mut heartbeats,
+        mut index,
+    } = clients;
+    //allocated once and reused for every packet after
+    buf.resize(MAX_PACKET_SIZE, 0);
+
+    //messages for rooms opened last tick, held back until they were spawned
+    let mut batch: Vec<(SocketAddr, ClientMessage)> = std::mem::take(&mut *held);
+
+    loop {
+        let result = udp.socket.recv_from(&mut buf);
+
+        match result {
+            Ok((bytes, src)) => {
+                let messages = match connections.list.entry(src).or_default().receive(
+                    &udp.socket,
+                    src,
+                    &buf[..bytes],
+                ) {
+                    Ok(messages) => messages,
+                    //the frame header says so before anything has to be decoded, so
+                    //clients on another version get told why instead of silence
+                    Err(CodecError::VersionMismatch { version }) => {
+                        warn!(
+                            "Rejected [{}]: client protocol v{}, server protocol v{}",
+                            src, version, PROTOCOL_VERSION
+                        );
+                        send(
+                            &udp.socket,
+                            &mut connections,
+                            src,
+                            &ServerMessage::JoinRejected(RejectReason::VersionMismatch {
+                                server: PROTOCOL_VERSION,
+                                client: version,
+                            }),
+                            Channel::Unreliable,
+                        );
+                        continue;
+                    }
+                    Err(e) => {
+                        warn!("Recieved invalid packet from [{}]: {}", src.ip(), e);
+                        continue;
+                    }
+                };
+
+                //anything at all from a player counts as a heartbeat
+                if index.by_addr.contains_key(&src) {
+                    heartbeats.last_seen.insert(src, Instant::now());
+                }
+
+                batch.extend(messages.into_iter().map(|message| (src, message)));
+            }
+            //nothing left to read until next tick
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => break,
+            Err(e) => {
+                warn!("Failed to read from the socket: {}", e);
+                break;
+            }
+        }
+    }
+
+    //rooms opened while handling this batch of packets, they're only spawned
+    //once it's done so players joining them in the meantime go in here
+    let mut opened: HashMap<Entity, RoomBundle> = HashMap::new();
+
+    for (src, message) in batch {
+        let addr = src.to_string();
+
+        match message {
+            //the query can't reach a room opened in this batch, so anything
+            //else its players send waits until it's been spawned
+            message
+                if index
+                    .by_addr
+                    .get(&src)
+                    .is_some_and(|entity| opened.contains_key(entity)) =>
+            {
+                held.push((src, message));
+            }
+            ClientMessage::ListRooms => {
+                let list = rooms
+                    .iter()
+                    .map(|room| (room.room, room.players))
+                    .chain(opened.values().map(|room| (&room.room, &room.players)))
+                    .map(|(room, players)| RoomInfo {
+                        code: room.code.clone(),
+                        players: players.len(),
+                        capacity: players.capacity(),
+                    })
+                    .collect();
+
+                send(
+                    &udp.socket,
+                    &mut connections,
+                    src,
+                    &ServerMessage::RoomList(list),
+                    Channel::Unreliable,
+                );
+            }
+            ClientMessage::NewPlayer {
+                player: mut new_player,
+                room: choice,
+            } => {
+                info!("Player join request from [{}]", new_player.addr);
+
+                // reply to where the packet actually came from rather than the
+                // address the client thinks it has
+                new_player.addr = addr.clone();
+
+                // the client is asking again before our joined_lobby got
+                // through, it's already being resent so there's nothing to do
+                if index.by_addr.contains_key(&src) {
+                    continue;
+                }
+
+                //the room they're going in, None opens a new one
+                let (target, seed) = match choice {
+                    RoomChoice::Any => {
+                        let open = opened
+                            .iter()
+                            .find(|(_, room)| !room.players.is_full())
+                            .map(|(entity, _)| *entity)
+                            .or_else(|| {
+                                rooms
+                                    .iter()
+                                    .find(|room| !room.players.is_full())
+                                    .map(|room| room.entity)
+                            });
+                        (open, None)
+                    }
+                    RoomChoice::Create { seed } => (None, seed),
+                    RoomChoice::Join { code } => {
+                        let code = code.trim().to_uppercase();
+                        match index.by_code.get(&code) {
+                            Some(entity) => (Some(*entity), None),
+                            None => {
+                                warn!("Rejected [{}]: no room {}", src, code);
+                                send(
+                                    &udp.socket,
+                                    &mut connections,
+                                    src,
+                                    &ServerMessage::JoinRejected(RejectReason::NoSuchRoom { code }),
+                                    Channel::Unreliable,
+                                );
+                                continue;
+                            }
+                        }
+                    }
+                };
+
+                let entity = match target {
+                    Some(entity) => entity,
+                    None if index.by_code.len() >= config.max_rooms => {
+                        warn!(
+                            "Rejected [{}]: already {} rooms open",
+                            src, config.max_rooms
+                        );
+                        send(
+                            &udp.socket,
+                            &mut connections,
+                            src,
+                            &ServerMessage::JoinRejected(RejectReason::TooManyRooms {
+                                max: config.max_rooms,
+                            }),
+                            Channel::Unreliable,
+                        );
+                        continue;
+                    }
+                    None => {
+                        let code = index.new_code(&mut rng);
+                        let seed = seed
+                            .or(config.seed)
+                            .unwrap_or_else(|| rng.stream(RngStream::Oceans).gen());
+                        let room = RoomBundle::new(
+                            code.clone(),
+                            seed,
+                            config.max_players,
+                            config.pvp_rules(),
+                            config.tick_rate,
+                            &rng,
+                        );
+                        info!(
+                            "Opened room {} on ocean seed {} ({} tiles, {} islands)",
+                            code,
+                            room.ocean.params.seed,
+                            room.ocean.layout.tiles.len(),
+                            room.ocean.layout.islands.len()
+                        );
+
+                        let entity = commands.spawn_empty().id();
+                        index.by_code.insert(code, entity);
+                        opened.insert(entity, room);
+                        entity
+                    }
+                };
+
+                let mut found = rooms.get_mut(entity).ok();
+                let (room, players, attacks, chat, ocean) =
+                    match (opened.get_mut(&entity), found.as_mut()) {
+                        (Some(room), _) => (
+                            &room.room,
+                            &mut room.players,
+                            &mut room.attacks,
+                            &mut room.chat,
+                            &room.ocean,
+                        ),
+                        (None, Some(room)) => (
+                            &*room.room,
+                            &mut *room.players,
+                            &mut *room.attacks,
+                            &mut *room.chat,
+                            room.ocean,
+                        ),
+                        (None, None) => continue,
+                    };
+
+                match players.insert(new_player) {
+                    Ok(id) => {
+                        index.by_addr.insert(src, entity);
+                        heartbeats.last_seen.insert(src, Instant::now());
+                        attacks.list.remove(&id);
+                        chat.list.remove(&id);
+
+                        //send the player their id and everything they
+                        //need to build the ocean
+                        send(
+                            &udp.socket,
+                            &mut connections,
+                            src,
+                            &ServerMessage::JoinedLobby {
+                                id,
+                                room: room.code.clone(),
+                                ocean: ocean.params,
+                                tick_rate: config.tick_rate,
+                                token: players.token(id).unwrap_or_default(),
+                            },
+                            Channel::Reliable,
+                        );
+
+                        //late joiners skip the lobby
+                        if room.started {
+                            send(
+                                &udp.socket,
+                                &mut connections,
+                                src,
+                                &ServerMessage::SessionStarted,
+                                Channel::Reliable,
+                            );
+                        }
+                        broadcast_roster(&udp.socket, &mut connections, room, players);
+
+                        info!(
+                            "Player #{} joined room {} ({}/{}), sent ocean seed {}",
+                            id,
+                            room.code,
+                            players.len(),
+                            players.capacity(),
+                            ocean.params.seed
+                        );
+                    }
+                    Err(LobbyFull { capacity, current }) => {
+                        warn!(
+                            "Rejected [{}]: room {} is full ({}/{})",
+                            src, room.code, current, capacity
+                        );
+                        send(
+                            &udp.socket,
+                            &mut connections,
+                            src,
+                            &ServerMessage::JoinRejected(RejectReason::LobbyFull {
+                                capacity,
+                                current,
+                            }),
+                            Channel::Unreliable,
+                        );
+                    }
+                }
+            }
+            ClientMessage::Rejoin {
+                room: code,
+                id,
+                token,
+            } => {
+                let code = code.trim().to_uppercase();
+                info!(
+                    "Rejoin request for player #{} of room {} from [{}]",
+                    id, code, src
+                );
+
+                let Some(mut room) = index
+                    .by_code
+                    .get(&code)
+                    .and_then(|entity| rooms.get_mut(*entity).ok())
+                else {
+                    warn!("Rejected rejoin from [{}]: no room {}", src, code);
+                    send(
+                        &udp.socket,
+                        &mut connections,
+                        src,
+                        &ServerMessage::JoinRejected(RejectReason::NoSuchRoom { code }),
+                        Channel::Unreliable,
+                    );
+                    continue;
+                };
+
+                //an address is only ever one player's, one still in a room
+                //can't take over another slot without leaving first
+                let theirs = room
+                    .players
+                    .get(id)
+                    .is_some_and(|player| player.addr == addr);
+                if index.by_addr.contains_key(&src) && !theirs {
+                    warn!(
+                        "Rejected rejoin from [{}]: they're already someone else in a room",
+                        src
+                    );
+                    send(
+                        &udp.socket,
+                        &mut connections,
+                        src,
+                        &ServerMessage::JoinRejected(RejectReason::SlotLost),
+                        Channel::Unreliable,
+                    );
+                    continue;
+                }
+
+                let Ok(previous) = room.players.rejoin(id, token, addr.clone()) else {
+                    warn!(
+                        "Rejected rejoin from [{}]: slot #{} of room {} isn't theirs",
+                        src, id, code
+                    );
+                    send(
+                        &udp.socket,
+                        &mut connections,
+                        src,
+                        &ServerMessage::JoinRejected(RejectReason::SlotLost),
+                        Channel::Unreliable,
+                    );
+                    continue;
+                };
+
+                //they may be sending from somewhere new, and either way
+                //their client starts counting reliable packets from scratch
+                if let Ok(previous) = previous.parse::<SocketAddr>() {
+                    if previous != src {
+                        index.by_addr.remove(&previous);
+                        heartbeats.last_seen.remove(&previous);
+                        connections.list.remove(&previous);
+                    }
+                }
+                connections.list.insert(src, Connection::default());
+                index.by_addr.insert(src, room.entity);
+                heartbeats.last_seen.insert(src, Instant::now());
+                //and decoding enemy updates from scratch
+                if let Some(known) = room.interest.known.get_mut(&id) {
+                    known.forget();
+                }
+
+                send(
+                    &udp.socket,
+                    &mut connections,
+                    src,
+                    &ServerMessage::JoinedLobby {
+                        id,
+                        room: room.room.code.clone(),
+                        ocean: room.ocean.params,
+                        tick_rate: config.tick_rate,
+                        token,
+                    },
+                    Channel::Reliable,
+                );
+                if room.room.started {
+                    send(
+                        &udp.socket,
+                        &mut connections,
+                        src,
+                        &ServerMessage::SessionStarted,
+                        Channel::Reliable,
+                    );
+                }
+                broadcast_roster(&udp.socket, &mut connections, &room.room, &room.players);
+
+                info!(
+                    "Player #{} rejoined room {} ({}/{})",
+                    id,
+                    room.room.code,
+                    room.players.len(),
+                    room.players.capacity()
+                );
+            }
+            ClientMessage::PlayerLeave(_) => {
+                //always confirm, even if they had already been evicted
+                send(
+                    &udp.socket,
+                    &mut connections,
+                    src,
+                    &ServerMessage::LeaveSuccess,
+                    Channel::Reliable,
+                );
+
+                heartbeats.last_seen.remove(&src);
+                let Some(mut room) = index
+                    .by_addr
+                    .remove(&src)
+                    .and_then(|entity| rooms.get_mut(entity).ok())
+                else {
+                    continue;
+                };
+                let Some(id) = room.players.find_by_addr(&addr).map(|player| player.id) else {
+                    continue;
+                };
+
+                room.players.remove(id);
+                room.sims.list.remove(&id);
+                room.sims.repair(id);
+
+                broadcast(
+                    &udp.socket,
+                    &mut connections,
+                    &room.players,
+                    id,
+                    &ServerMessage::PlayerLeft { id },
+                    Channel::Reliable,
+                );
+                broadcast_roster(&udp.socket, &mut connections, &room.room, &room.players);
+
+                info!("Logged out player #{} from room {}", id, room.room.code);
+            }
+            ClientMessage::Heartbeat => {
+                //already recorded above
+            }
+            message => {
+                //everything else is about the sender's room
+                let Some(mut room) = index
+                    .by_addr
+                    .get(&src)
+                    .and_then(|entity| rooms.get_mut(*entity).ok())
+                else {
+                    continue;
+                };
+                let sender_id = room.players.find_by_addr(&addr).map(|player| player.id);
+
+                handle_in_room(
+                    &udp.socket,
+                    &mut connections,
+                    src,
+                    sender_id,
+                    &mut room,
+                    message,
+                );
+            }
+        }
+    }
+
+    for (entity, room) in opened {
+        commands.entity(entity).insert(room);
+    }
+}
+
+/// Handles a message from a player about the room they're in
+fn handle_in_room(
+    socket: &UdpSocket,
+    connections: &mut Connections,
+    src: SocketAddr,
+    sender_id: Option<i32>,
+    room: &mut RoomQueryItem,
+    message: ClientMessage,
+) {
+    match message {
+        ClientMessage::PlayerUpdate(update) => {
+            //boats are moved by the server, see simulate_boats
+            if update.boat || sender_id.is_some_and(|id| room.sims.list.contains_key(&id)) {
+                return;
+            }
+
+            if let Some(player) = sender_id.and_then(|id| room.players.get_mut(id)) {
+                let now = Instant::now();
+                let record = room.attacks.list.entry(player.id).or_default();
+
+                player.pos = match record.moved_at {
+                    Some(then) => move_pirate(player.pos, update.pos, (now - then).as_secs_f32()),
+                    //first update since coming ashore or going underground
+                    None => update.pos,
+                };
+                record.moved_at = Some(now);
+                player.rot = update.rot;
+                player.boat = false;
+            }
+        }
+        ClientMessage::SetReady(ready) => {
+            if let Some(player) = sender_id.and_then(|id| room.players.get_mut(id)) {
+                player.ready = ready;
+                broadcast_roster(socket, connections, &room.room, &room.players);
+            }
+        }
+        ClientMessage::SetCrew(crew) => {
+            //crews are picked in the lobby, nobody switches sides mid fight
+            if room.room.started || crew.is_some_and(|crew| crew >= MAX_CREWS) {
+                return;
+            }
+
+            if let Some(player) = sender_id.and_then(|id| room.players.get_mut(id)) {
+                player.crew = crew;
+                broadcast_roster(socket, connections, &room.room, &room.players);
+            }
+        }
+        ClientMessage::StartSession => {
+            let roster = room.room.roster(&room.players);
+
+            if room.room.started {
+                return;
+            }
+            if sender_id != Some(roster.host) {
+                warn!(
+                    "Ignored start from [{}]: not the host of room {}",
+                    src, room.room.code
+                );
+                return;
+            }
+            if !roster.all_ready() {
+                warn!(
+                    "Ignored start of room {}: not everyone is ready",
+                    room.room.code
+                );
+                return;
+            }
+
+            room.room.started = true;
+            info!(
+                "Room {} set sail with {} players",
+                room.room.code,
+                room.players.len()
+            );
+
+            broadcast(
+                socket,
+                connections,
+                &room.players,
+                -1,
+                &ServerMessage::SessionStarted,
+                Channel::Reliable,
+            );
+            broadcast_roster(socket, connections, &room.room, &room.players);
+        }
+        ClientMessage::BoatSpawned => {
+            let Some(player) = sender_id.and_then(|id| room.players.get_mut(id)) else {
+                return;
+            };
+            //a second boat_spawned would put a live boat back at full speed at the spawn
+            if room.sims.list.contains_key(&player.id) {
+                warn!(
+                    "Ignored boat_spawned from player #{}: their boat is still afloat",
+                    player.id
+                );
+                return;
+            }
+
+            let state = BoatState::spawn();
+            room.sims.list.insert(player.id, BoatSim::new(state));
+            player.pos = state.pos;
+            player.rot = state.rot;
+            player.boat = true;
+        }
+        ClientMessage::BoatSank => {
+            let Some(player) = sender_id.and_then(|id| room.players.get_mut(id)) else {
+                return;
+            };
+            if room.sims.list.remove(&player.id).is_some() {
+                debug!("Player #{}'s boat sank", player.id);
+            }
+            room.sims.repair(player.id);
+            player.boat = false;
+        }
+        ClientMessage::BoatInputs(inputs) => {
+            if let Some(sim) = sender_id.and_then(|id| room.sims.list.get_mut(&id)) {
+                sim.queue(inputs);
+            }
+        }
+        ClientMessage::EnemyDamaged(attack) => {
+            let Some(attacker) = sender_id.and_then(|id| room.players.get(id)) else {
+                warn!("Rejected hit from [{}]: not in the room", src);
+                return;
+            };
+            let id = attacker.id;
+
+            //already dead, the hit just crossed paths with dead_enemies
+            let Some(index) = room
+                .enemies
+                .update
+                .list
+                .iter()
+                .position(|x| x.id == attack.target_id)
+            else {
+                return;
+            };
+
+            let now = Instant::now();
+            let record = room.attacks.list.entry(id).or_default();
+
+            if let Err(reason) = validate_hit(
+                attacker,
+                room.enemies.update.list[index].pos,
+                &attack,
+                record,
+                now,
+            ) {
+                record.rejected += 1;
+                warn!(
+                    "Rejected hit from player #{} on enemy [{}] ({} rejected so far): {}",
+                    id, attack.target_id, record.rejected, reason
+                );
+                return;
+            }
+            record.last_hit = Some(now);
+
+            let enemies = &mut room.enemies;
+            enemies.update.list[index].hp -= attack.dmg;
+
+            debug!(
+                "Enemy [{}] hp: [{}]",
+                enemies.update.list[index].id, enemies.update.list[index].hp
+            );
+
+            if enemies.update.list[index].hp <= 0. {
+                debug!("Enemy [{}] died", enemies.update.list[index].id);
+
+                //sent to every player with the next update
+                let dead = enemies.update.list.remove(index);
+                enemies.dead.list.push(dead);
+            }
+        }
+        ClientMessage::EquipWeapon(weapon) => {
+            let Some(id) = sender_id else {
+                return;
+            };
+            let record = room.attacks.list.entry(id).or_default();
+
+            //cannons come with the boat, they aren't carried around
+            if weapon.is_boat_weapon() {
+                record.rejected += 1;
+                warn!(
+                    "Rejected weapon from player #{} ({} rejected so far): pirates can't carry a {:?}",
+                    id, record.rejected, weapon
+                );
+                return;
+            }
+            debug!("Player #{} swapped to a {:?}", id, weapon);
+            record.weapon = weapon;
+        }
+        ClientMessage::PlayerDamaged(attack) => {
+            let Some(id) = sender_id else {
+                warn!("Rejected hit from [{}]: not in the room", src);
+                return;
+            };
+
+            handle_boat_hit(socket, connections, room, id, attack);
+        }
+        ClientMessage::GotHereLate(_) => {
+            let Some(id) = sender_id else {
+                return;
+            };
+
+            //forgetting what they were sent makes the next snapshot send
+            //everything in view again
+            debug!("Player #{} is back at sea, catching them up", id);
+            if let Some(known) = room.interest.known.get_mut(&id) {
+                known.forget();
+            }
+        }
+        ClientMessage::AckEnemies(seq) => {
+            let Some(known) = sender_id.and_then(|id| room.interest.known.get_mut(&id)) else {
+                return;
+            };
+            known.deltas.ack(seq);
+        }
+        ClientMessage::AreaChanged(area) => {
+            let Some(player) = sender_id.and_then(|id| room.players.get_mut(id)) else {
+                return;
+            };
+            if player.area == area {
+                return;
+            }
+
+            debug!("Player #{} is now {}", player.id, area);
+            player.area = area;
+            if let Some(record) = room.attacks.list.get_mut(&player.id) {
+                record.moved_at = None;
+            }
+
+            //a boat doesn't follow its player ashore
+            if area != Area::Ocean {
+                room.sims.list.remove(&player.id);
+                player.boat = false;
+            }
+            //boats are mended back at port
+            if let Area::Island {
+                island_type: IslandType::Start,
+                ..
+            } = area
+            {
+                room.sims.repair(player.id);
+            }
+            broadcast_roster(socket, connections, &room.room, &room.players);
+        }
+        ClientMessage::Chat(text) => {
+            let Some(sender) = sender_id.and_then(|id| room.players.get(id)) else {
+                return;
+            };
+
+            handle_chat(
+                socket,
+                connections,
+                src,
+                sender,
+                &room.players,
+                &mut room.chat,
+                &text,
+            );
+        }
+        ClientMessage::EnterDungeon { island } => {
+            let Some(id) = sender_id else {
+                return;
+            };
+
+            handle_enter_dungeon(socket, connections, src, room, id, island);
+        }
+        ClientMessage::DungeonEnemyKilled { island, id: enemy } => {
+            let Some(id) = sender_id else {
+                warn!("Rejected kill from [{}]: not in the room", src);
+                return;
+            };
+
+            handle_dungeon_kill(socket, connections, room, id, island, enemy);
+        }
+        //joining, leaving and heartbeats don't need a room, see handle
+        ClientMessage::NewPlayer { .. }
+        | ClientMessage::Rejoin { .. }
+        | ClientMessage::ListRooms
+        | ClientMessage::PlayerLeave(_)
+        | ClientMessage::Heartbeat => {}
+    }
+}
@@ -1,144 +1,357 @@
 use bevy::prelude::*;
+use protocol::messages::{ClientMessage, MAX_NAME_LENGTH};
+use protocol::reliable::Connection;
 use serde::{Deserialize, Serialize};
-use std::io::*;
+use std::collections::HashMap;
 use std::net::*;
-use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+pub use protocol::components::*;
 
 #[derive(Resource)]
 pub struct UDP {
     pub socket: UdpSocket,
 }
 
-#[derive(Serialize, Deserialize)]
-pub struct Envelope {
-    pub message: String,
-    pub packet: String,
+/// Reliability state for every client the server is talking to, keyed by the
+/// address packets arrive from
+#[derive(Resource, Default)]
+pub struct Connections {
+    pub list: HashMap<SocketAddr, Connection<ClientMessage>>,
 }
 
-#[derive(Serialize, Deserialize)]
-pub struct Packet<T> {
-    pub payload: T,
-}
+/// How long a client can go without sending anything before it's dropped
+pub const DEFAULT_CLIENT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long a dropped player's slot is kept for them to rejoin before it's freed
+pub const REJOIN_GRACE: Duration = Duration::from_secs(30);
 
+/// When each player (by the address they joined from) was last heard from.
+/// Player ids are only unique within a room, addresses are unique on the server
 #[derive(Resource)]
-pub struct Counter {
-    pub count: i32,
+pub struct Heartbeats {
+    pub timeout: Duration,
+    pub last_seen: HashMap<SocketAddr, Instant>,
 }
 
-impl Counter {
-    pub fn init() -> Counter {
-        Counter { count: 5 }
+impl Heartbeats {
+    pub fn new(timeout: Duration) -> Heartbeats {
+        Heartbeats {
+            timeout,
+            last_seen: HashMap::new(),
+        }
     }
+}
 
-    pub fn next(&mut self) -> i32 {
-        self.count += 1;
-        self.count
-    }
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct EnemyLists {
+    pub update: Enemies,
+    pub dead: Enemies,
 }
 
-#[derive(Clone, Serialize, Deserialize)]
-pub struct Player {
-    pub id: i32,
-    pub addr: String,
-    pub pos: Vec3,
-    pub rot: Quat,
-    pub boat: bool,
-    pub used: bool,
+/// Returned when someone tries to join a lobby with no free slots
+pub struct LobbyFull {
+    pub capacity: usize,
+    pub current: usize,
 }
 
-impl Player {
-    pub fn default() -> Player {
-        Player {
-            id: -1,
-            addr: "null".to_string(),
-            pos: Vec3::splat(0.),
-            rot: Quat::from_rotation_x((90.0_f32).to_radians()),
-            boat: true,
-            used: false,
-        }
-    }
+/// Returned when a rejoin doesn't match a slot that's held or taken
+pub struct SlotLost;
+
+/// A dropped player waiting to rejoin, their slot isn't handed out until `until`
+struct HeldSlot {
+    player: Player,
+    until: Instant,
 }
 
-#[derive(Resource, Serialize, Deserialize, Clone)]
+/// Everyone in a room. A player's id is the index of their slot, so it stays
+/// the same for as long as they're connected and is handed to the next player
+/// once they leave. Slots are only allocated as players join, up to `capacity`.
+/// A dropped player's slot is held for them for a while, and they can get it
+/// back with the token they were given when they joined
+#[derive(Component)]
 pub struct Players {
-    pub player_array: [Player; 4],
+    slots: Vec<Option<Player>>,
+    held: HashMap<i32, HeldSlot>,
+    tokens: HashMap<i32, u64>,
+    capacity: usize,
 }
 
 impl Players {
-    pub fn init() -> Players {
+    /// An empty lobby with room for `capacity` players
+    pub fn init(capacity: usize) -> Players {
         Players {
-            player_array: [
-                Player::default(),
-                Player::default(),
-                Player::default(),
-                Player::default(),
-            ],
+            slots: Vec::new(),
+            held: HashMap::new(),
+            tokens: HashMap::new(),
+            capacity,
         }
     }
-}
 
-#[derive(Resource, Clone, Serialize, Deserialize)]
-pub struct Projectiles {
-    pub list: Vec<Projectile>,
-}
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
 
-#[derive(Clone, Serialize, Deserialize)]
-pub struct Projectile {
-    pub owner_id: i32,
-    pub velocity: Velocity,
-    pub translation: Vec3,
-    pub lifetime: f32,
-}
+    /// How many players are in the lobby, not counting held slots
+    pub fn len(&self) -> usize {
+        self.slots.iter().filter(|slot| slot.is_some()).count()
+    }
 
-#[derive(Clone, Serialize, Deserialize)]
-pub struct Velocity {
-    pub v: Vec2,
-}
+    /// Whether nobody is in the lobby or waiting to rejoin it
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0 && self.held.is_empty()
+    }
 
-#[derive(Resource, Serialize, Deserialize, Clone)]
-pub struct EnemyLists {
-    pub new: Enemies,
-    pub update: Enemies,
-    pub dead: Enemies,
-}
+    /// Whether a new player would be turned away
+    pub fn is_full(&self) -> bool {
+        self.len() + self.held.len() >= self.capacity
+    }
 
-#[derive(Serialize, Deserialize, Clone)]
-pub struct Enemies {
-    pub list: Vec<Enemy>,
-}
+    /// Adds a player to the lowest free slot and returns the id it was given.
+    /// Only their name and address are taken from the request, the server
+    /// decides everything else
+    pub fn insert(&mut self, player: Player) -> Result<i32, LobbyFull> {
+        let free =
+            self.slots.iter().enumerate().position(|(index, slot)| {
+                slot.is_none() && !self.held.contains_key(&(index as i32))
+            });
+        let index = match free {
+            Some(index) => index,
+            None if self.slots.len() < self.capacity => {
+                self.slots.push(None);
+                self.slots.len() - 1
+            }
+            None => {
+                return Err(LobbyFull {
+                    capacity: self.capacity,
+                    current: self.len() + self.held.len(),
+                })
+            }
+        };
 
-impl Enemies {
-    pub fn init() -> Enemies {
-        Enemies { list: Vec::new() }
+        let id = index as i32;
+        self.slots[index] = Some(Player {
+            id,
+            addr: player.addr,
+            name: clean_name(&player.name, id),
+            used: true,
+            ..Player::default()
+        });
+        //not from GameRng, a token anyone with the seed can work out proves nothing
+        self.tokens.insert(index as i32, rand::random());
+        Ok(index as i32)
     }
-}
 
-#[derive(Serialize, Deserialize, Clone)]
+    /// Frees a player's slot, handing back whoever was in it
+    pub fn remove(&mut self, id: i32) -> Option<Player> {
+        self.tokens.remove(&id);
+        self.held.remove(&id);
+        self.remove_slot(id)
+    }
 
-pub struct Enemy {
-    pub id: i32,
-    pub etype: i32,
-    pub pos: Vec3,
-    pub animation_index: usize,
-    pub hp: f32,
-    pub alive: bool,
-    pub target_id: i32,
-}
+    fn remove_slot(&mut self, id: i32) -> Option<Player> {
+        self.slots.get_mut(usize::try_from(id).ok()?)?.take()
+    }
 
-#[derive(Serialize, Deserialize, Clone)]
-pub struct Damage {
-    pub target_id: i32,
-    pub dmg: f32,
+    /// Token the player in a slot has to show to get it back after dropping
+    pub fn token(&self, id: i32) -> Option<u64> {
+        self.tokens.get(&id).copied()
+    }
+
+    /// Takes a dropped player out of the lobby but keeps their slot until `until`
+    pub fn hold(&mut self, id: i32, until: Instant) {
+        let Some(player) = self.remove_slot(id) else {
+            return;
+        };
+        self.held.insert(id, HeldSlot { player, until });
+    }
+
+    /// Puts a player back in their slot, whether it was held or they hadn't
+    /// been dropped yet, now sending from `addr`. Hands back the address they
+    /// were sending from before
+    pub fn rejoin(&mut self, id: i32, token: u64, addr: String) -> Result<String, SlotLost> {
+        if self.tokens.get(&id) != Some(&token) {
+            return Err(SlotLost);
+        }
+
+        if let Some(held) = self.held.remove(&id) {
+            self.slots[id as usize] = Some(held.player);
+        }
+
+        let player = self.get_mut(id).ok_or(SlotLost)?;
+        Ok(std::mem::replace(&mut player.addr, addr))
+    }
+
+    /// Frees every held slot whose player didn't make it back in time, handing
+    /// back their ids
+    pub fn release_held(&mut self, now: Instant) -> Vec<i32> {
+        let expired: Vec<i32> = self
+            .held
+            .iter()
+            .filter(|(_, held)| held.until <= now)
+            .map(|(id, _)| *id)
+            .collect();
+
+        for id in expired.iter() {
+            self.held.remove(id);
+            self.tokens.remove(id);
+        }
+        expired
+    }
+
+    pub fn get(&self, id: i32) -> Option<&Player> {
+        self.slots.get(usize::try_from(id).ok()?)?.as_ref()
+    }
+
+    pub fn get_mut(&mut self, id: i32) -> Option<&mut Player> {
+        self.slots.get_mut(usize::try_from(id).ok()?)?.as_mut()
+    }
+
+    /// The player packets from `addr` belong to, if any
+    pub fn find_by_addr(&self, addr: &str) -> Option<&Player> {
+        self.iter().find(|player| player.addr == addr)
+    }
+
+    /// Every player in the lobby, in id order
+    pub fn iter(&self) -> impl Iterator<Item = &Player> {
+        self.slots.iter().flatten()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Player> {
+        self.slots.iter_mut().flatten()
+    }
 }
 
-#[derive(Resource, Clone)]
-pub struct Cooldowns {
-    pub list: Vec<CD>,
+/// Trims a player's name down to MAX_NAME_LENGTH printable characters, players
+/// who didn't pick one are named after their id
+fn clean_name(name: &str, id: i32) -> String {
+    let name: String = name
+        .trim()
+        .chars()
+        .filter(|c| !c.is_control())
+        .take(MAX_NAME_LENGTH)
+        .collect();
+
+    if name.is_empty() {
+        format!("Pirate #{}", id)
+    } else {
+        name
+    }
 }
 
-#[derive(Clone)]
-pub struct CD {
-    pub enemy_id: i32,
-    pub og: f32,
-    pub timer: Timer,
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn join(players: &mut Players, addr: &str) -> Result<i32, LobbyFull> {
+        players.insert(Player {
+            addr: addr.to_string(),
+            ..default()
+        })
+    }
+
+    #[test]
+    fn players_get_the_lowest_free_slot() {
+        let mut players = Players::init(3);
+
+        assert_eq!(join(&mut players, "a").ok(), Some(0));
+        assert_eq!(join(&mut players, "b").ok(), Some(1));
+        players.remove(0);
+        assert_eq!(join(&mut players, "c").ok(), Some(0));
+        assert_eq!(join(&mut players, "d").ok(), Some(2));
+
+        let Err(LobbyFull { capacity, current }) = join(&mut players, "e") else {
+            panic!("a fourth player got into a lobby of three");
+        };
+        assert_eq!((capacity, current), (3, 3));
+        let ids: Vec<i32> = players.iter().map(|player| player.id).collect();
+        assert_eq!(ids, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn held_slots_are_kept_for_the_player_who_dropped() {
+        let mut players = Players::init(2);
+        let id = join(&mut players, "a").ok().unwrap();
+        let token = players.token(id).unwrap();
+        let now = Instant::now();
+
+        players.hold(id, now + REJOIN_GRACE);
+        assert!(players.get(id).is_none());
+        assert_eq!(players.len(), 0);
+        assert!(!players.is_empty());
+
+        //nobody else gets it, and it still counts towards the capacity
+        assert_eq!(join(&mut players, "b").ok(), Some(1));
+        assert!(players.is_full());
+        assert!(join(&mut players, "c").is_err());
+
+        assert!(players.rejoin(id, token ^ 1, "d".to_string()).is_err());
+        assert_eq!(
+            players.rejoin(id, token, "d".to_string()).ok().as_deref(),
+            Some("a")
+        );
+        assert_eq!(
+            players.get(id).map(|player| player.addr.as_str()),
+            Some("d")
+        );
+        assert_eq!(players.find_by_addr("d").map(|player| player.id), Some(id));
+    }
+
+    #[test]
+    fn held_slots_are_freed_once_the_grace_runs_out() {
+        let mut players = Players::init(1);
+        let id = join(&mut players, "a").ok().unwrap();
+        let token = players.token(id).unwrap();
+        let now = Instant::now();
+        players.hold(id, now + REJOIN_GRACE);
+
+        assert!(players.release_held(now).is_empty());
+        assert_eq!(players.release_held(now + REJOIN_GRACE), vec![id]);
+        assert!(players.is_empty());
+        assert!(players.rejoin(id, token, "a".to_string()).is_err());
+
+        //and the next player in gets a token of their own
+        assert_eq!(join(&mut players, "b").ok(), Some(id));
+        assert_ne!(players.token(id), Some(token));
+    }
+
+    #[test]
+    fn joining_players_only_pick_their_name() {
+        let mut players = Players::init(2);
+        let id = players
+            .insert(Player {
+                id: 7,
+                addr: "a".to_string(),
+                name: "Anne".to_string(),
+                ready: true,
+                crew: Some(1),
+                pos: Vec3::new(100., 100., 0.),
+                boat: false,
+                area: Area::Dungeon {
+                    island: 0,
+                    island_type: IslandType::Level1,
+                },
+                ..default()
+            })
+            .ok()
+            .unwrap();
+
+        let player = players.get(id).unwrap();
+        assert_eq!((player.id, player.addr.as_str()), (0, "a"));
+        assert_eq!(player.name, "Anne");
+        assert!(player.used);
+
+        let fresh = Player::default();
+        assert!(!player.ready);
+        assert_eq!(player.crew, None);
+        assert_eq!((player.pos, player.boat), (fresh.pos, fresh.boat));
+        assert_eq!(player.area, fresh.area);
+    }
+
+    #[test]
+    fn names_are_cleaned_up() {
+        assert_eq!(clean_name("  Anne\tBonny \n", 0), "AnneBonny");
+        assert_eq!(clean_name(&"x".repeat(40), 0).len(), MAX_NAME_LENGTH);
+        assert_eq!(clean_name("   ", 3), "Pirate #3");
+    }
 }
@@ -1,7 +1,258 @@
 use bevy::prelude::*;
-use std::io::{Read, Write};
+use protocol::messages::{BoatSnapshot, PlayerPose, ServerMessage, Snapshot};
+use protocol::reliable::Channel;
 use std::net::*;
-use std::sync::{Arc, Mutex};
-use std::thread;
+use std::time::Instant;
 
+use crate::config::ServerConfig;
+use crate::interest::components::*;
+use crate::interest::systems::*;
 use crate::network::components::*;
+use crate::rooms::components::*;
+use crate::rooms::systems::broadcast_roster;
+
+/*   SEND FUNCTION   */
+/// Encodes a message and sends it to a client on the given channel. Failures are
+/// logged rather than unwrapped so a single bad send can't take the whole server down
+pub fn send(
+    socket: &UdpSocket,
+    connections: &mut Connections,
+    addr: SocketAddr,
+    message: &ServerMessage,
+    channel: Channel,
+) {
+    let connection = connections.list.entry(addr).or_default();
+
+    if let Err(e) = connection.send(socket, addr, message, channel) {
+        warn!("Failed to send packet to [{}]: {}", addr, e);
+    }
+}
+
+/*   SEND_TO_PLAYER FUNCTION   */
+/// Sends a message to the address a player joined from
+pub fn send_to_player(
+    socket: &UdpSocket,
+    connections: &mut Connections,
+    player: &Player,
+    message: &ServerMessage,
+    channel: Channel,
+) {
+    match player.addr.parse::<SocketAddr>() {
+        Ok(addr) => send(socket, connections, addr, message, channel),
+        Err(e) => warn!("Player #{} has a bad address [{}]: {}", player.id, player.addr, e),
+    }
+}
+
+/*   RESEND_RELIABLE FUNCTION   */
+/// Resends reliable packets that haven't been acked yet and forgets about
+/// connections that have nothing left to deliver and no player behind them.
+/// A player whose connection was given up on is dropped by evict_timed_out
+pub fn resend_reliable(udp: Res<UDP>, mut connections: ResMut<Connections>, index: Res<RoomIndex>) {
+    let now = Instant::now();
+
+    for (addr, connection) in connections.list.iter_mut() {
+        let dropped = connection.resend(&udp.socket, *addr, now);
+        if dropped > 0 {
+            warn!(
+                "Gave up on [{}] with {} reliable packet(s) unacked",
+                addr, dropped
+            );
+        }
+    }
+
+    connections.list.retain(|addr, connection| {
+        index.by_addr.contains_key(addr) || !(connection.is_idle() || connection.gave_up())
+    });
+}
+
+/*   BROADCAST FUNCTION   */
+/// Sends a message to every player in a room except `skip_id`
+pub fn broadcast(
+    socket: &UdpSocket,
+    connections: &mut Connections,
+    players: &Players,
+    skip_id: i32,
+    message: &ServerMessage,
+    channel: Channel,
+) {
+    for player in players.iter() {
+        if player.id != skip_id {
+            send_to_player(socket, connections, player, message, channel);
+        }
+    }
+}
+
+/*   EVICT_TIMED_OUT FUNCTION   */
+/// Drops every player that hasn't sent anything within the heartbeat timeout,
+/// or stopped acking what we send them, and tells the rest of their room they're gone. Their slot is held for
+/// REJOIN_GRACE in case they come back, see release_held_slots
+pub fn evict_timed_out(
+    udp: Res<UDP>,
+    mut connections: ResMut<Connections>,
+    mut rooms: Query<(&Room, &mut Players)>,
+    mut heartbeats: ResMut<Heartbeats>,
+    mut index: ResMut<RoomIndex>,
+) {
+    let now = Instant::now();
+    let timeout = heartbeats.timeout;
+
+    //a client we have no record of counts as just seen
+    let timed_out: Vec<(SocketAddr, Entity)> = index
+        .by_addr
+        .iter()
+        .filter(|(addr, _)| {
+            let last_seen = *heartbeats.last_seen.entry(**addr).or_insert(now);
+            let gave_up = connections
+                .list
+                .get(*addr)
+                .is_some_and(|connection| connection.gave_up());
+            gave_up || now.duration_since(last_seen) >= timeout
+        })
+        .map(|(addr, room)| (*addr, *room))
+        .collect();
+
+    for (addr, room) in timed_out {
+        let silent_for = now.duration_since(heartbeats.last_seen[&addr]);
+        let why = if silent_for >= timeout {
+            format!("nothing heard for {:.1}s", silent_for.as_secs_f32())
+        } else {
+            "reliable packets went unacked".to_string()
+        };
+        index.by_addr.remove(&addr);
+        heartbeats.last_seen.remove(&addr);
+        connections.list.remove(&addr);
+
+        let Ok((room, mut players)) = rooms.get_mut(room) else {
+            continue;
+        };
+        let Some(id) = players
+            .find_by_addr(&addr.to_string())
+            .map(|player| player.id)
+        else {
+            continue;
+        };
+        //they might only have lost their connection for a moment
+        players.hold(id, now + REJOIN_GRACE);
+
+        info!(
+            "Dropping player #{} [{}]: {}, holding their slot for {}s",
+            id,
+            addr,
+            why,
+            REJOIN_GRACE.as_secs()
+        );
+
+        broadcast(
+            &udp.socket,
+            &mut connections,
+            &players,
+            id,
+            &ServerMessage::PlayerLeft { id },
+            Channel::Reliable,
+        );
+        broadcast_roster(&udp.socket, &mut connections, room, &players);
+    }
+}
+
+/*   BROADCAST_SNAPSHOT FUNCTION   */
+/// Sends every player the state of the part of their room they're in at the
+/// end of this tick, as far as they can see from their boat or pirate. Players
+/// at sea also get the enemies around them and projectiles fired nearby
+pub fn broadcast_snapshot(
+    udp: Res<UDP>,
+    mut connections: ResMut<Connections>,
+    config: Res<ServerConfig>,
+    mut rooms: Query<SnapshotRoom>,
+) {
+    let radius = config.view_radius;
+
+    for mut room in rooms.iter_mut() {
+        let players = room.players;
+        let grid = SpatialGrid::build(
+            room.enemies
+                .update
+                .list
+                .iter()
+                .map(|enemy| enemy.pos.truncate()),
+        );
+
+        //players who went ashore start over when they're back at sea
+        room.interest
+            .known
+            .retain(|id, _| players.get(*id).is_some());
+        for (id, known) in room.interest.known.iter_mut() {
+            if players
+                .get(*id)
+                .is_some_and(|player| player.area != Area::Ocean)
+            {
+                known.forget();
+            }
+        }
+
+        for player in players.iter() {
+            //everyone sees themselves, and the others only when they're nearby
+            let nearby = |id: i32| {
+                id == player.id
+                    || players.get(id).is_some_and(|other| {
+                        other.area == player.area
+                            && other.pos.truncate().distance(player.pos.truncate()) <= radius
+                    })
+            };
+
+            let snapshot = ServerMessage::Snapshot(Box::new(Snapshot {
+                tick: room.tick.tick,
+                wind: room.wind.direction,
+                players: players
+                    .iter()
+                    .filter(|other| nearby(other.id))
+                    .map(PlayerPose::from)
+                    .collect(),
+                boats: room
+                    .sims
+                    .list
+                    .iter()
+                    .filter(|(id, _)| nearby(**id))
+                    .map(|(id, sim)| BoatSnapshot {
+                        id: *id,
+                        state: sim.state,
+                        last_input: sim.last_input.seq,
+                    })
+                    .collect(),
+            }));
+
+            send_to_player(
+                &udp.socket,
+                &mut connections,
+                player,
+                &snapshot,
+                Channel::Unreliable,
+            );
+
+            //the ocean's enemies are only any use at sea
+            if player.area != Area::Ocean {
+                continue;
+            }
+
+            send_enemies_in_view(
+                &udp.socket,
+                &mut connections,
+                player,
+                room.interest.known.entry(player.id).or_default(),
+                &grid,
+                &room.enemies,
+                radius,
+            );
+            send_projectiles_in_view(
+                &udp.socket,
+                &mut connections,
+                player,
+                &room.projectiles,
+                radius,
+            );
+        }
+
+        //every player has been sent these now
+        room.enemies.dead.list.clear();
+        room.projectiles.list.clear();
+    }
+}
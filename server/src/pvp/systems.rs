@@ -0,0 +1,120 @@
+use bevy::log::{debug, info, warn};
+use protocol::components::Damage;
+use protocol::messages::{ServerMessage, Sinking};
+use protocol::reliable::Channel;
+use std::net::UdpSocket;
+use std::time::Instant;
+
+use crate::enemies::components::HitRejection;
+use crate::enemies::systems::validate_hit;
+use crate::network::components::*;
+use crate::network::systems::broadcast;
+use crate::rooms::components::RoomQueryItem;
+
+/*   HANDLE_BOAT_HIT FUNCTION   */
+/// Checks a player's claim to have hit another player's boat against the room's
+/// PvP rules and the same checks as a hit on an enemy. Believed hits come off
+/// the target's hp and are sent to everyone, and the attacker is paid the
+/// room's bounty once the boat sinks
+pub fn handle_boat_hit(
+    socket: &UdpSocket,
+    connections: &mut Connections,
+    room: &mut RoomQueryItem,
+    attacker_id: i32,
+    attack: Damage,
+) {
+    let Some(attacker) = room.players.get(attacker_id) else {
+        return;
+    };
+    let target_id = attack.target_id;
+
+    //already sunk or gone ashore, the hit just crossed paths with the news
+    let Some(target) = room
+        .players
+        .get(target_id)
+        .filter(|_| room.sims.list.contains_key(&target_id))
+    else {
+        return;
+    };
+
+    let pvp = room.room.pvp;
+    let now = Instant::now();
+    let record = room.attacks.list.entry(attacker_id).or_default();
+
+    let checked = if !pvp.enabled {
+        Err(HitRejection::PvpOff)
+    } else if target_id == attacker_id {
+        Err(HitRejection::OwnBoat)
+    } else if !room.sims.list.contains_key(&attacker_id) {
+        Err(HitRejection::NotAfloat { id: attacker_id })
+    } else if !pvp.can_hurt(attacker.crew, target.crew) {
+        Err(HitRejection::SameCrew {
+            crew: attacker.crew.unwrap_or_default(),
+        })
+    } else {
+        validate_hit(attacker, target.pos, &attack, record, now)
+    };
+
+    if let Err(reason) = checked {
+        record.rejected += 1;
+        warn!(
+            "Rejected hit from player #{} on player #{} ({} rejected so far): {}",
+            attacker_id, target_id, record.rejected, reason
+        );
+        return;
+    }
+    record.last_hit = Some(now);
+
+    let sinking = Sinking {
+        id: target_id,
+        name: target.name.clone(),
+        by: attacker_id,
+        by_name: attacker.name.clone(),
+        bounty: pvp.bounty,
+    };
+
+    let hp = room.sims.hp(target_id) - attack.dmg;
+    room.sims.hp.insert(target_id, hp);
+
+    debug!(
+        "Player #{} hit player #{}'s boat, hp: [{}]",
+        attacker_id, target_id, hp
+    );
+    broadcast(
+        socket,
+        connections,
+        &room.players,
+        -1,
+        &ServerMessage::BoatDamaged {
+            id: target_id,
+            by: attacker_id,
+            dmg: attack.dmg,
+            hp,
+        },
+        Channel::Reliable,
+    );
+
+    if hp > 0. {
+        return;
+    }
+
+    //the boat is out of the water until its player sets sail again
+    room.sims.list.remove(&target_id);
+    room.sims.repair(target_id);
+    if let Some(target) = room.players.get_mut(target_id) {
+        target.boat = false;
+    }
+
+    info!(
+        "Player #{} sank player #{}'s boat in room {}, paying {} gold",
+        attacker_id, target_id, room.room.code, pvp.bounty
+    );
+    broadcast(
+        socket,
+        connections,
+        &room.players,
+        -1,
+        &ServerMessage::BoatSunk(sinking),
+        Channel::Reliable,
+    );
+}
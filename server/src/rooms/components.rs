@@ -0,0 +1,163 @@
+use bevy::ecs::query::QueryData;
+use bevy::prelude::*;
+use protocol::combat::PvpRules;
+use protocol::messages::{Roster, RosterEntry};
+use protocol::rng::{GameRng, RngStream};
+use rand::Rng;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+
+use crate::chat::components::*;
+use crate::dungeons::components::*;
+use crate::enemies::components::*;
+use crate::interest::components::*;
+use crate::level::components::*;
+use crate::level::systems::build_ocean;
+use crate::network::components::*;
+use crate::simulation::components::*;
+use crate::simulation::systems::init_wind;
+
+/// Length of the code players share to get into the same room
+pub const ROOM_CODE_LENGTH: usize = 5;
+
+//no 0/O or 1/I so codes can be read out to a friend
+const ROOM_CODE_CHARS: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+
+/// A world players can join. Every room is an entity holding its own ocean,
+/// players, enemies and tick, and is closed once the last player leaves
+#[derive(Component)]
+pub struct Room {
+    pub code: String,
+    /// Whether the host has started the session, players joining after that
+    /// skip the lobby
+    pub started: bool,
+    pub pvp: PvpRules,
+}
+
+impl Room {
+    /// The lobby view of the room. Whoever has the lowest id is the host, so
+    /// the next player in line takes over when the host leaves
+    pub fn roster(&self, players: &Players) -> Roster {
+        Roster {
+            host: players.iter().next().map_or(-1, |player| player.id),
+            started: self.started,
+            pvp: self.pvp,
+            players: players
+                .iter()
+                .map(|player| RosterEntry {
+                    id: player.id,
+                    name: player.name.clone(),
+                    ready: player.ready,
+                    crew: player.crew,
+                    area: player.area,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Everything a room needs to run
+#[derive(Bundle)]
+pub struct RoomBundle {
+    pub room: Room,
+    pub ocean: OceanMap,
+    pub players: Players,
+    pub counter: Counter,
+    pub enemies: EnemyLists,
+    pub projectiles: Projectiles,
+    pub tick: ServerTick,
+    pub wind: Wind,
+    pub sims: BoatSims,
+    pub spawn_timers: SpawnTimers,
+    pub enemy_states: EnemyStates,
+    pub attacks: AttackRecords,
+    pub chat: ChatLimits,
+    pub dungeons: Dungeons,
+    pub interest: Interest,
+    /// Random numbers of this room alone, so what happens in one room doesn't
+    /// change what the others draw
+    pub rng: GameRng,
+}
+
+impl RoomBundle {
+    /// An empty room for `capacity` players on the ocean built from `seed`,
+    /// with random numbers of its own from the server's `rng` and its code
+    pub fn new(
+        code: String,
+        seed: u64,
+        capacity: usize,
+        pvp: PvpRules,
+        tick_rate: f64,
+        rng: &GameRng,
+    ) -> RoomBundle {
+        let mut rng = rng.for_room(&code);
+
+        RoomBundle {
+            room: Room {
+                code,
+                started: false,
+                pvp,
+            },
+            ocean: build_ocean(seed),
+            players: Players::init(capacity),
+            counter: Counter::init(),
+            enemies: EnemyLists {
+                update: Enemies { list: Vec::new() },
+                dead: Enemies { list: Vec::new() },
+            },
+            projectiles: Projectiles { list: Vec::new() },
+            tick: ServerTick::default(),
+            wind: init_wind(&mut rng, tick_rate),
+            sims: BoatSims::default(),
+            spawn_timers: SpawnTimers::init(tick_rate, &mut rng),
+            enemy_states: EnemyStates::default(),
+            attacks: AttackRecords::default(),
+            chat: ChatLimits::default(),
+            dungeons: Dungeons::default(),
+            interest: Interest::default(),
+            rng,
+        }
+    }
+}
+
+/// The parts of a room that incoming messages touch
+#[derive(QueryData)]
+#[query_data(mutable)]
+pub struct RoomQuery {
+    pub entity: Entity,
+    pub room: &'static mut Room,
+    pub ocean: &'static OceanMap,
+    pub players: &'static mut Players,
+    pub enemies: &'static mut EnemyLists,
+    pub sims: &'static mut BoatSims,
+    pub attacks: &'static mut AttackRecords,
+    pub chat: &'static mut ChatLimits,
+    pub dungeons: &'static mut Dungeons,
+    pub interest: &'static mut Interest,
+    pub rng: &'static mut GameRng,
+}
+
+/// Finds rooms by their code, and the room each client is in by the address
+/// they joined from
+#[derive(Resource, Default)]
+pub struct RoomIndex {
+    pub by_code: HashMap<String, Entity>,
+    pub by_addr: HashMap<SocketAddr, Entity>,
+}
+
+impl RoomIndex {
+    /// A random code no open room is using
+    pub fn new_code(&self, rng: &mut GameRng) -> String {
+        let rng = rng.stream(RngStream::RoomCodes);
+
+        loop {
+            let code: String = (0..ROOM_CODE_LENGTH)
+                .map(|_| ROOM_CODE_CHARS[rng.gen_range(0..ROOM_CODE_CHARS.len())] as char)
+                .collect();
+
+            if !self.by_code.contains_key(&code) {
+                return code;
+            }
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use bevy::prelude::*;
+use protocol::messages::ServerMessage;
+use protocol::reliable::Channel;
+use std::net::UdpSocket;
+use std::time::Instant;
+
+use crate::network::components::*;
+use crate::network::systems::broadcast;
+use crate::rooms::components::*;
+use crate::simulation::components::BoatSims;
+
+/*   BROADCAST_ROSTER FUNCTION   */
+/// Sends everyone in a room the room's roster for their lobby screen
+pub fn broadcast_roster(
+    socket: &UdpSocket,
+    connections: &mut Connections,
+    room: &Room,
+    players: &Players,
+) {
+    broadcast(
+        socket,
+        connections,
+        players,
+        -1,
+        &ServerMessage::Roster(room.roster(players)),
+        Channel::Reliable,
+    );
+}
+
+/*   RELEASE_HELD_SLOTS FUNCTION   */
+/// Frees the slots of dropped players who didn't rejoin within REJOIN_GRACE,
+/// along with what was left of their boat
+pub fn release_held_slots(mut rooms: Query<(&Room, &mut Players, &mut BoatSims)>) {
+    let now = Instant::now();
+
+    for (room, mut players, mut sims) in rooms.iter_mut() {
+        for id in players.release_held(now) {
+            sims.repair(id);
+            info!(
+                "Gave up on player #{} rejoining room {}, their slot is free",
+                id, room.code
+            );
+        }
+    }
+}
+
+/*   CLOSE_EMPTY_ROOMS FUNCTION   */
+/// Closes every room the last player has left, once nobody is waiting to
+/// rejoin it. Its code can be handed out again
+pub fn close_empty_rooms(
+    mut commands: Commands,
+    rooms: Query<(Entity, &Room, &Players)>,
+    mut index: ResMut<RoomIndex>,
+) {
+    for (entity, room, players) in rooms.iter() {
+        if !players.is_empty() {
+            continue;
+        }
+
+        info!("Closing room {}, everyone has left", room.code);
+        index.by_code.remove(&room.code);
+        index.by_addr.retain(|_, in_room| *in_room != entity);
+        commands.entity(entity).despawn();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+    use protocol::combat::{PvpRules, BOAT_MAX_HP};
+
+    #[test]
+    fn boats_are_mended_once_their_held_slot_is_given_up_on() {
+        let mut players = Players::init(2);
+        let id = players.insert(Player::default()).ok().unwrap();
+        let mut sims = BoatSims::default();
+        sims.hp.insert(id, 3.);
+        players.hold(id, Instant::now());
+
+        let mut world = World::new();
+        let room = Room {
+            code: "ABCDE".into(),
+            started: true,
+            pvp: PvpRules::default(),
+        };
+        let room = world.spawn((room, players, sims)).id();
+        world.run_system_once(release_held_slots);
+
+        assert_eq!(world.get::<BoatSims>(room).unwrap().hp(id), BOAT_MAX_HP);
+    }
+}
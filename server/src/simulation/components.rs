@@ -0,0 +1,89 @@
+use bevy::prelude::*;
+use protocol::combat::BOAT_MAX_HP;
+use protocol::simulation::{BoatInput, BoatState};
+use std::collections::{HashMap, VecDeque};
+
+/// How many inputs a client can get ahead of the server before the oldest
+/// ones are thrown away
+pub const MAX_QUEUED_INPUTS: usize = 8;
+
+/// How often the wind changes direction, in seconds
+pub const WIND_CHANGE_TIME: f32 = 30.;
+
+/// How many ticks at `tick_rate` make up `secs` seconds, never less than one
+pub fn secs_to_ticks(secs: f32, tick_rate: f64) -> u32 {
+    ((secs as f64 * tick_rate).round() as u32).max(1)
+}
+
+/// Counts the fixed simulation ticks since the room was opened
+#[derive(Component, Default)]
+pub struct ServerTick {
+    pub tick: u32,
+}
+
+/// The wind every boat in a room sails in. Owned by the server so every client agrees
+#[derive(Component)]
+pub struct Wind {
+    pub direction: Vec2,
+    /// Ticks until it changes direction
+    pub ticks_left: u32,
+}
+
+/// The authoritative boat of one player and the inputs waiting to be applied to it
+pub struct BoatSim {
+    pub state: BoatState,
+    pub inputs: VecDeque<BoatInput>,
+    pub last_input: BoatInput,
+    pub last_received: u32,
+}
+
+impl BoatSim {
+    pub fn new(state: BoatState) -> BoatSim {
+        BoatSim {
+            state,
+            inputs: VecDeque::new(),
+            last_input: BoatInput::default(),
+            last_received: 0,
+        }
+    }
+
+    /// Queues any inputs that haven't been seen before. Clients send their last
+    /// few inputs every tick so one lost packet doesn't lose an input
+    pub fn queue(&mut self, inputs: Vec<BoatInput>) {
+        for input in inputs {
+            if input.seq <= self.last_received {
+                continue;
+            }
+
+            self.last_received = input.seq;
+            self.inputs.push_back(input.clamped());
+        }
+
+        while self.inputs.len() > MAX_QUEUED_INPUTS {
+            self.inputs.pop_front();
+        }
+    }
+}
+
+/// Every boat the server is simulating in a room, keyed by player id. Sunk
+/// boats are taken out until their player sets sail again
+#[derive(Component, Default)]
+pub struct BoatSims {
+    pub list: HashMap<i32, BoatSim>,
+    /// What each player's boat has left, only other players' cannonballs count
+    /// against it, see handle_boat_hit. Kept apart from the sim so going ashore
+    /// and setting sail again doesn't mend the boat
+    pub hp: HashMap<i32, f32>,
+}
+
+impl BoatSims {
+    /// What's left of a player's boat, boats nobody has hit yet are whole
+    pub fn hp(&self, id: i32) -> f32 {
+        self.hp.get(&id).copied().unwrap_or(BOAT_MAX_HP)
+    }
+
+    /// Mends a player's boat, after it sank or once they're back at port
+    pub fn repair(&mut self, id: i32) {
+        self.hp.remove(&id);
+    }
+}
@@ -0,0 +1,131 @@
+use bevy::prelude::*;
+use protocol::rng::{GameRng, RngStream};
+use protocol::simulation::{step_boat, PIRATE_MOVE_SLACK, PIRATE_SPEED};
+use rand::Rng;
+
+use crate::config::ServerConfig;
+use crate::network::components::Players;
+use crate::simulation::components::*;
+
+/*   INIT_WIND FUNCTION   */
+/// Picks a random starting wind
+pub fn init_wind(rng: &mut GameRng, tick_rate: f64) -> Wind {
+    Wind {
+        direction: random_wind(rng),
+        ticks_left: secs_to_ticks(WIND_CHANGE_TIME, tick_rate),
+    }
+}
+
+fn random_wind(rng: &mut GameRng) -> Vec2 {
+    let rng = rng.stream(RngStream::Wind);
+    Vec2::new(rng.gen_range(0.0..=360.0), rng.gen_range(0.0..=360.0))
+}
+
+/*   CHANGE_WIND FUNCTION   */
+/// Changes the wind direction of every room every WIND_CHANGE_TIME seconds
+/// worth of ticks
+pub fn change_wind(config: Res<ServerConfig>, mut rooms: Query<(&mut Wind, &mut GameRng)>) {
+    for (mut wind, mut rng) in rooms.iter_mut() {
+        wind.ticks_left -= 1;
+
+        if wind.ticks_left == 0 {
+            wind.direction = random_wind(&mut rng);
+            wind.ticks_left = secs_to_ticks(WIND_CHANGE_TIME, config.tick_rate);
+            debug!("Changing wind {}", wind.direction);
+        }
+    }
+}
+
+/*   MOVE_PIRATE FUNCTION   */
+/// Where a pirate at `from` who says they're at `to` is put, no further than
+/// they could have walked in `elapsed` seconds. Pirates aren't simulated, but
+/// this keeps their position close enough to trust for hit ranges
+pub fn move_pirate(from: Vec3, to: Vec3, elapsed: f32) -> Vec3 {
+    let reach = PIRATE_SPEED * elapsed + PIRATE_MOVE_SLACK;
+    let step = (to - from).truncate().clamp_length_max(reach);
+    (from.truncate() + step).extend(to.z)
+}
+
+/*   SIMULATE_BOATS FUNCTION   */
+/// Runs one tick of every player's boat in every room. Each tick uses up one
+/// queued input, and if the client's input hasn't arrived yet its last one is repeated
+pub fn simulate_boats(
+    mut rooms: Query<(&mut ServerTick, &mut BoatSims, &mut Players, &Wind)>,
+    time: Res<Time<Fixed>>,
+) {
+    let dt = time.timestep().as_secs_f32();
+
+    for (mut tick, mut sims, mut players, wind) in rooms.iter_mut() {
+        tick.tick = tick.tick.wrapping_add(1);
+
+        //boats of players that left or went ashore aren't simulated anymore.
+        //what's left of their hull is kept until the slot is freed, dropping
+        //and rejoining doesn't mend it
+        sims.list
+            .retain(|id, _| players.get(*id).is_some_and(|player| player.boat));
+
+        for (id, sim) in sims.list.iter_mut() {
+            if let Some(input) = sim.inputs.pop_front() {
+                sim.last_input = input;
+            }
+
+            step_boat(&mut sim.state, &sim.last_input, wind.direction, dt);
+
+            if let Some(player) = players.get_mut(*id) {
+                player.pos = sim.state.pos;
+                player.rot = sim.state.rot;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+    use protocol::components::Player;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn pirates_only_get_as_far_as_they_could_have_walked() {
+        let from = Vec3::new(0., 0., 10.);
+
+        let near = Vec3::new(30., 40., 10.);
+        assert_eq!(move_pirate(from, near, 0.5), near);
+
+        //a second of walking is all they get, however far they claim to be
+        let far = Vec3::new(5000., 0., 10.);
+        let reach = PIRATE_SPEED + PIRATE_MOVE_SLACK;
+        assert_eq!(move_pirate(from, far, 1.), Vec3::new(reach, 0., 10.));
+    }
+
+    #[test]
+    fn dropped_players_keep_their_damage_when_they_rejoin() {
+        let mut players = Players::init(2);
+        let id = players.insert(Player::default()).ok().unwrap();
+        let token = players.token(id).unwrap();
+        let mut sims = BoatSims::default();
+        sims.hp.insert(id, 3.);
+        players.hold(id, Instant::now() + Duration::from_secs(60));
+
+        let mut world = World::new();
+        world.init_resource::<Time<Fixed>>();
+        let wind = Wind {
+            direction: Vec2::X,
+            ticks_left: 100,
+        };
+        let room = world
+            .spawn((ServerTick::default(), sims, players, wind))
+            .id();
+
+        //a few ticks go by while they're gone
+        for _ in 0..3 {
+            world.run_system_once(simulate_boats);
+        }
+        let mut players = world.get_mut::<Players>(room).unwrap();
+        assert!(players.rejoin(id, token, "127.0.0.1:9".into()).is_ok());
+        world.run_system_once(simulate_boats);
+
+        assert_eq!(world.get::<BoatSims>(room).unwrap().hp(id), 3.);
+    }
+}
@@ -0,0 +1,366 @@
+//! Drives a headless server through scripted clients that talk to it through a
+//! netsim proxy, for behaviour that has to hold up over a link that drops,
+//! delays, duplicates and reorders datagrams in both directions.
+
+mod support;
+
+use bevy::prelude::*;
+use protocol::codec;
+use protocol::combat::Weapon;
+use protocol::components::{Area, Damage, IslandType, Player};
+use protocol::delta::DeltaDecoder;
+use protocol::messages::*;
+use protocol::netsim::LinkConditions;
+use protocol::reliable::Channel;
+use protocol::simulation::{BoatInput, BOAT_SPAWN_POSITION};
+use server::config::ServerConfig;
+use std::thread;
+use std::time::Duration;
+
+use support::*;
+
+#[test]
+fn players_join_sail_and_leave_over_a_bad_link() {
+    let server = TestServer::start(ServerConfig::default());
+    let proxy = server.proxy(bad_link(), 1);
+
+    let mut sailor = TestClient::new("sailor", proxy.addr());
+    let mut watcher = TestClient::new("watcher", proxy.addr());
+    set_sail(&mut [&mut sailor, &mut watcher]);
+
+    sailor.send(&ClientMessage::BoatSpawned, Channel::Reliable);
+
+    //full sail for a second, sending the last few inputs every tick like the game
+    let mut history: Vec<BoatInput> = Vec::new();
+    for seq in 1..=30 {
+        history.push(BoatInput {
+            seq,
+            turn: 0.,
+            thrust: 1.,
+        });
+        let recent = history[history.len().saturating_sub(3)..].to_vec();
+        sailor.send(&ClientMessage::BoatInputs(recent), Channel::Unreliable);
+        thread::sleep(Duration::from_millis(33));
+    }
+
+    let id = sailor.id;
+    let distance = watcher.wait_for("the sailor's boat to move", |message| {
+        let ServerMessage::Snapshot(snapshot) = message else {
+            return None;
+        };
+        let boat = snapshot.boats.iter().find(|boat| boat.id == id)?;
+        let distance = boat.state.pos.distance(BOAT_SPAWN_POSITION);
+        (distance > 50.).then_some(distance)
+    });
+    assert!(distance.is_finite());
+
+    sailor.send(
+        &ClientMessage::PlayerLeave(Player::default()),
+        Channel::Reliable,
+    );
+    sailor.wait_for("leave_success", |message| match message {
+        ServerMessage::LeaveSuccess => Some(()),
+        _ => None,
+    });
+    watcher.wait_for("the sailor to leave", |message| match message {
+        ServerMessage::PlayerLeft { id: left } if left == id => Some(()),
+        _ => None,
+    });
+    watcher.wait_for("a roster without the sailor", |message| match message {
+        ServerMessage::Roster(roster) if roster.players.len() == 1 => Some(()),
+        _ => None,
+    });
+
+    let stats = proxy.stats();
+    assert!(stats.dropped > 0, "nothing was dropped: {:?}", stats);
+    assert!(stats.duplicated > 0, "nothing was duplicated: {:?}", stats);
+}
+
+#[test]
+fn messages_right_behind_opening_a_room_still_reach_it() {
+    let server = TestServer::start(ServerConfig::default());
+    //slow but steady, so both messages get to the server in the same batch
+    let slow = LinkConditions {
+        loss: 0.,
+        latency: Duration::from_millis(40),
+        jitter: Duration::ZERO,
+        duplicate: 0.,
+        reorder: 0.,
+    };
+    let proxy = server.proxy(slow, 9);
+
+    let host = TestClient::new("host", proxy.addr());
+    host.send(
+        &ClientMessage::NewPlayer {
+            player: Player {
+                name: host.name.to_string(),
+                ..default()
+            },
+            room: RoomChoice::Create { seed: Some(7) },
+        },
+        Channel::Reliable,
+    );
+    host.send(&ClientMessage::SetReady(true), Channel::Reliable);
+
+    host.wait_for_ready(1);
+}
+
+#[test]
+fn pvp_hits_count_once_over_a_bad_link() {
+    let config = ServerConfig {
+        pvp: true,
+        ..Default::default()
+    };
+    let server = TestServer::start(config);
+    //half of everything arrives twice, which reliable delivery has to see through
+    let link = LinkConditions {
+        duplicate: 0.5,
+        ..bad_link()
+    };
+    let proxy = server.proxy(link, 2);
+
+    let mut attacker = TestClient::new("attacker", proxy.addr());
+    let mut target = TestClient::new("target", proxy.addr());
+    set_sail(&mut [&mut attacker, &mut target]);
+
+    for (client, x) in [(&attacker, 0.), (&target, 200.)] {
+        launch(&server, client, x, 0.);
+    }
+    let ids = [attacker.id, target.id];
+    attacker.wait_for("both boats to be afloat", |message| {
+        boats_afloat(&message, &ids).then_some(())
+    });
+
+    let hit = ClientMessage::PlayerDamaged(Damage {
+        target_id: target.id,
+        dmg: Weapon::Cannon.damage(),
+        weapon: Weapon::Cannon,
+    });
+
+    //a cannon's cooldown apart, with room for the jitter
+    let mut hp_seen = Vec::new();
+    for _ in 0..2 {
+        attacker.send(&hit, Channel::Reliable);
+        let hp = target.wait_for("boat_damaged", |message| match message {
+            ServerMessage::BoatDamaged { id, hp, .. } if id == ids[1] => Some(hp),
+            _ => None,
+        });
+        hp_seen.push(hp);
+        thread::sleep(Duration::from_secs_f32(Weapon::Cannon.cooldown()));
+    }
+    assert_eq!(hp_seen, vec![4., 3.]);
+
+    //duplicates of either hit would show up as more damage by now
+    let late = target.listen(Duration::from_secs(1));
+    assert!(!late
+        .iter()
+        .any(|message| matches!(message, ServerMessage::BoatDamaged { .. })));
+    assert!(proxy.stats().duplicated > 0);
+
+    //going ashore and setting sail again doesn't mend the boat
+    let ashore = Area::Island {
+        island: 0,
+        island_type: IslandType::Level1,
+    };
+    target.send(&ClientMessage::AreaChanged(ashore), Channel::Reliable);
+    attacker.wait_for("the target to go ashore", |message| match message {
+        ServerMessage::Roster(roster) => roster
+            .players
+            .iter()
+            .any(|player| player.id == ids[1] && player.area == ashore)
+            .then_some(()),
+        _ => None,
+    });
+    target.send(&ClientMessage::AreaChanged(Area::Ocean), Channel::Reliable);
+    launch(&server, &target, 200., 0.);
+
+    attacker.send(&hit, Channel::Reliable);
+    let hp = target.wait_for("boat_damaged", |message| match message {
+        ServerMessage::BoatDamaged { id, hp, .. } if id == ids[1] => Some(hp),
+        _ => None,
+    });
+    assert_eq!(hp, 2.);
+}
+
+#[test]
+fn everyone_hears_about_a_shutdown_over_a_bad_link() {
+    let server = TestServer::start(ServerConfig::default());
+    //worse than usual, the kick rarely gets through on the first try
+    let lossy = LinkConditions {
+        loss: 0.5,
+        ..bad_link()
+    };
+    let proxy = server.proxy(lossy, 10);
+
+    let mut host = TestClient::new("host", proxy.addr());
+    host.join(RoomChoice::Any);
+
+    let reply = server.admin("shutdown");
+    assert!(reply.starts_with("Shutting down"), "{}", reply);
+    host.wait_for("being kicked", |message| match message {
+        ServerMessage::Kicked { .. } => Some(()),
+        _ => None,
+    });
+}
+
+#[test]
+fn chat_arrives_once_and_in_order_over_a_bad_link() {
+    let server = TestServer::start(ServerConfig::default());
+    let link = LinkConditions {
+        reorder: 0.5,
+        ..bad_link()
+    };
+    let proxy = server.proxy(link, 3);
+
+    let mut talker = TestClient::new("talker", proxy.addr());
+    let mut listener = TestClient::new("listener", proxy.addr());
+    let code = talker.join(RoomChoice::Create { seed: Some(7) });
+    listener.join(RoomChoice::Join { code });
+
+    //the server's chat burst, all at once
+    let sent: Vec<String> = (1..=5).map(|n| format!("message {}", n)).collect();
+    for text in sent.iter() {
+        talker.send(&ClientMessage::Chat(text.clone()), Channel::Reliable);
+    }
+
+    let mut heard = Vec::new();
+    listener.wait_for("the whole conversation", |message| {
+        if let ServerMessage::Chat(line) = message {
+            heard.push(line.text);
+        }
+        (heard.len() == sent.len()).then_some(())
+    });
+    //anything heard twice would have caught up by now
+    heard.extend(
+        listener
+            .listen(Duration::from_millis(500))
+            .into_iter()
+            .filter_map(|message| match message {
+                ServerMessage::Chat(line) => Some(line.text),
+                _ => None,
+            }),
+    );
+
+    assert_eq!(heard, sent);
+    assert!(proxy.stats().reordered > 0);
+}
+
+#[test]
+fn dungeon_kills_count_once_for_the_whole_crew_over_a_bad_link() {
+    let server = TestServer::start(ServerConfig::default());
+    let link = LinkConditions {
+        duplicate: 0.5,
+        ..bad_link()
+    };
+    let proxy = server.proxy(link, 4);
+
+    let mut first = TestClient::new("first", proxy.addr());
+    let mut second = TestClient::new("second", proxy.addr());
+    set_sail(&mut [&mut first, &mut second]);
+
+    let enter = |client: &TestClient, island: u32| {
+        let area = Area::Dungeon {
+            island,
+            island_type: IslandType::Level1,
+        };
+        client.send(&ClientMessage::AreaChanged(area), Channel::Reliable);
+        client.send(&ClientMessage::EnterDungeon { island }, Channel::Reliable);
+        client.wait_for("dungeon_entered", |message| match message {
+            ServerMessage::DungeonEntered(instance) if instance.island == island => Some(instance),
+            _ => None,
+        })
+    };
+
+    //a dungeon can come out without enemies, but not every one of them
+    let instance = (0..=4)
+        .map(|island| enter(&first, island))
+        .find(|instance| !instance.enemies.is_empty())
+        .expect("every dungeon came out empty");
+    let island = instance.island;
+
+    let seen = enter(&second, island);
+    assert_eq!(seen.params, instance.params);
+    assert_eq!(seen.enemies, instance.enemies);
+
+    //both of them get the same enemy at about the same time
+    let id = instance.enemies[0].id;
+    for client in [&first, &second] {
+        client.send(
+            &ClientMessage::DungeonEnemyKilled { island, id },
+            Channel::Reliable,
+        );
+    }
+
+    let died = |client: &TestClient| {
+        client.wait_for("dungeon_enemy_died", |message| match message {
+            ServerMessage::DungeonEnemyDied(kill) => Some(kill),
+            _ => None,
+        })
+    };
+    let kill = died(&first);
+    assert_eq!(kill.enemy.id, id);
+    assert!(kill.by == first.id || kill.by == second.id);
+    assert_eq!(died(&second).by, kill.by);
+
+    //the losing claim and any duplicates would have shown up by now
+    for client in [&first, &second] {
+        let late = client.listen(Duration::from_secs(1));
+        assert!(!late
+            .iter()
+            .any(|message| matches!(message, ServerMessage::DungeonEnemyDied(_))));
+    }
+
+    let again = enter(&second, island);
+    assert_eq!(again.enemies.len(), instance.enemies.len() - 1);
+    assert!(!again.enemies.iter().any(|enemy| enemy.id == id));
+}
+
+#[test]
+fn enemy_updates_only_carry_what_changed_over_a_bad_link() {
+    let server = TestServer::start(ServerConfig::default());
+    let proxy = server.proxy(bad_link(), 7);
+
+    let mut client = TestClient::new("lookout", proxy.addr());
+    set_sail(&mut [&mut client]);
+    client.send(&ClientMessage::BoatSpawned, Channel::Reliable);
+
+    let kraken = server.spawn("kraken", 200., 0.);
+
+    //acks every update like the game does, until one builds on an earlier one
+    let mut decoder = DeltaDecoder::default();
+    let mut full = None;
+    let (full, delta) = client.wait_for("an update built on an acked one", |message| {
+        let ServerMessage::UpdateEnemies(delta) = &message else {
+            return None;
+        };
+        let enemies = decoder.decode(delta)?;
+        client.send(&ClientMessage::AckEnemies(delta.seq), Channel::Unreliable);
+
+        //the kraken decodes the same either way, but only comes in full once
+        if !enemies.iter().any(|enemy| enemy.id == kraken) {
+            return None;
+        }
+        let size = codec::encode(&message).unwrap().len();
+        match delta.baseline {
+            None => {
+                assert!(delta.changed.iter().all(|enemy| enemy.etype.is_some()));
+                full = Some(size);
+                None
+            }
+            Some(_) => {
+                assert!(delta.changed.iter().all(|enemy| enemy.etype.is_none()));
+                full.map(|full| (full, size))
+            }
+        }
+    });
+    assert!(
+        delta < full,
+        "a delta of {} bytes against {} in full",
+        delta,
+        full
+    );
+
+    //and the admin can see what that came to
+    let players = server.admin("list players");
+    assert!(players.contains("KB/s"), "no traffic in: {}", players);
+}
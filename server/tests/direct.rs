@@ -0,0 +1,205 @@
+//! Drives a headless server through scripted clients that talk to it straight
+//! over loopback, for behaviour that has nothing to do with the link.
+
+mod support;
+
+use protocol::codec::{self, MAX_PACKET_SIZE, PROTOCOL_VERSION};
+use protocol::components::{Area, Enemies, IslandType, Player};
+use protocol::messages::*;
+use protocol::reliable::{Channel, Packet};
+use server::config::ServerConfig;
+use std::net::UdpSocket;
+use std::time::Duration;
+
+use support::*;
+
+#[test]
+fn clients_on_another_version_are_told_so() {
+    let server = TestServer::start(ServerConfig::default());
+
+    //a join from the version before, whatever shape its messages had
+    let old = PROTOCOL_VERSION - 1;
+    let mut frame = codec::encode(&Packet::Unreliable(ClientMessage::NewPlayer {
+        player: Player::default(),
+        room: RoomChoice::Any,
+    }))
+    .unwrap();
+    frame[2..4].copy_from_slice(&old.to_le_bytes());
+
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    socket.set_read_timeout(Some(PATIENCE)).unwrap();
+    socket.send_to(&frame, server.addr).unwrap();
+
+    let mut buf = vec![0; MAX_PACKET_SIZE];
+    let (size, _) = socket
+        .recv_from(&mut buf)
+        .expect("never heard back from the server");
+    let Ok(Packet::Unreliable(ServerMessage::JoinRejected(reason))) =
+        codec::decode::<Packet<ServerMessage>>(&buf[..size])
+    else {
+        panic!("the join wasn't rejected");
+    };
+
+    assert!(matches!(
+        reason,
+        RejectReason::VersionMismatch { server, client }
+            if server == PROTOCOL_VERSION && client == old
+    ));
+}
+
+#[test]
+fn players_only_hear_about_their_own_area() {
+    let server = TestServer::start(ServerConfig::default());
+
+    let mut sailor = TestClient::new("sailor", server.addr);
+    let mut lander = TestClient::new("lander", server.addr);
+    set_sail(&mut [&mut sailor, &mut lander]);
+
+    sailor.send(&ClientMessage::BoatSpawned, Channel::Reliable);
+
+    let ashore = Area::Island {
+        island: 0,
+        island_type: IslandType::Level1,
+    };
+    lander.send(&ClientMessage::AreaChanged(ashore), Channel::Reliable);
+
+    //the crew can see where the lander went
+    let id = lander.id;
+    for client in [&sailor, &lander] {
+        client.wait_for("the lander to go ashore", |message| match message {
+            ServerMessage::Roster(roster) => roster
+                .players
+                .iter()
+                .any(|player| player.id == id && player.area == ashore)
+                .then_some(()),
+            _ => None,
+        });
+    }
+
+    //the sailor still gets the ocean, without the lander in it
+    sailor.wait_for("a snapshot without the lander", |message| match message {
+        ServerMessage::Snapshot(snapshot) => {
+            (!snapshot.players.iter().any(|player| player.id == id)).then_some(())
+        }
+        _ => None,
+    });
+    sailor.wait_for("update_enemies", |message| match message {
+        ServerMessage::UpdateEnemies(_) => Some(()),
+        _ => None,
+    });
+
+    //anything sent before the server knew has arrived by now
+    lander.listen(Duration::from_millis(500));
+    let heard = lander.listen(Duration::from_secs(1));
+    assert!(heard
+        .iter()
+        .any(|message| matches!(message, ServerMessage::Snapshot(_))));
+    for message in heard.iter() {
+        match message {
+            ServerMessage::Snapshot(snapshot) => {
+                assert!(snapshot.players.iter().all(|player| player.id == id));
+                assert!(snapshot.boats.iter().all(|boat| boat.id == id));
+            }
+            ServerMessage::UpdateEnemies(_)
+            | ServerMessage::NewEnemies(_)
+            | ServerMessage::DeadEnemies(_)
+            | ServerMessage::UpdateProjectiles(_) => panic!("the lander heard about the ocean"),
+            _ => {}
+        }
+    }
+}
+
+#[test]
+fn players_only_see_what_is_near_them() {
+    let config = ServerConfig {
+        view_radius: 600.,
+        ..Default::default()
+    };
+    let server = TestServer::start(config);
+
+    let mut near = TestClient::new("near", server.addr);
+    let mut far = TestClient::new("far", server.addr);
+    set_sail(&mut [&mut near, &mut far]);
+
+    //three view radii apart
+    for (client, x) in [(&near, 0.), (&far, 1800.)] {
+        launch(&server, client, x, 0.);
+    }
+
+    let kraken = server.spawn("kraken", 200., 0.);
+
+    let has_kraken = |enemies: &Enemies| enemies.list.iter().any(|enemy| enemy.id == kraken);
+    near.wait_for("the kraken to come into view", |message| match message {
+        ServerMessage::NewEnemies(enemies) => has_kraken(&enemies).then_some(()),
+        _ => None,
+    });
+
+    //neither of them sees the other, and only one of them sees the kraken
+    let (near_id, far_id) = (near.id, far.id);
+    for (client, other) in [(&near, far_id), (&far, near_id)] {
+        for message in client.listen(Duration::from_secs(1)) {
+            match message {
+                ServerMessage::Snapshot(snapshot) => {
+                    assert!(!snapshot.players.iter().any(|player| player.id == other));
+                    assert!(!snapshot.boats.iter().any(|boat| boat.id == other));
+                }
+                ServerMessage::UpdateEnemies(delta) => {
+                    let seen = delta.changed.iter().any(|enemy| enemy.id == kraken);
+                    assert!(client.id == near_id || !seen);
+                }
+                ServerMessage::NewEnemies(enemies) => {
+                    assert!(client.id == near_id || !has_kraken(&enemies));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    //sailing over brings both of them into view
+    let reply = server.admin(&format!("teleport {} 100 100", far_id));
+    assert!(
+        reply.starts_with("Moved"),
+        "couldn't move the boat: {}",
+        reply
+    );
+    far.wait_for("the kraken to come into view", |message| match message {
+        ServerMessage::NewEnemies(enemies) => has_kraken(&enemies).then_some(()),
+        _ => None,
+    });
+    far.wait_for("the other boat", |message| {
+        boats_afloat(&message, &[near_id, far_id]).then_some(())
+    });
+}
+
+#[test]
+fn players_in_a_room_cannot_rejoin_as_someone_else() {
+    let server = TestServer::start(ServerConfig::default());
+
+    let mut anne = TestClient::new("anne", server.addr);
+    let mut mary = TestClient::new("mary", server.addr);
+    anne.join(RoomChoice::Create { seed: None });
+    let code = mary.join(RoomChoice::Create { seed: None });
+
+    //anne knows mary's slot and token, but is still in a room of her own
+    anne.send(
+        &ClientMessage::Rejoin {
+            room: code,
+            id: mary.id,
+            token: mary.token,
+        },
+        Channel::Unreliable,
+    );
+    anne.wait_for("the rejoin to be turned down", |message| match message {
+        ServerMessage::JoinRejected(RejectReason::SlotLost) => Some(()),
+        ServerMessage::JoinedLobby { .. } => panic!("anne took over mary's slot"),
+        _ => None,
+    });
+
+    //mary's slot still answers to her
+    let listing = server.admin("list");
+    assert!(
+        listing.contains(&format!("#{} mary [{}]", mary.id, mary.addr())),
+        "mary lost her slot: {}",
+        listing
+    );
+}
@@ -0,0 +1,386 @@
+//! Scripted clients, a headless server to run them against and the netsim
+//! links in between, shared by the integration tests
+
+//every test file only uses its own share of these
+#![allow(dead_code)]
+
+use bevy::prelude::*;
+use protocol::codec::MAX_PACKET_SIZE;
+use protocol::components::{Area, Player};
+use protocol::messages::*;
+use protocol::netsim::{LinkConditions, LinkProxy};
+use protocol::reliable::{Channel, Connection};
+use server::admin::components::AdminConsole;
+use server::build_app;
+use server::config::ServerConfig;
+use std::collections::VecDeque;
+use std::io::{Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, UdpSocket};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// How long a client waits for something before the test fails
+pub const PATIENCE: Duration = Duration::from_secs(15);
+
+/// How often a client that hasn't heard back asks to join again
+pub const JOIN_RETRY: Duration = Duration::from_millis(500);
+
+/// A link nobody would want to play on
+pub fn bad_link() -> LinkConditions {
+    LinkConditions {
+        loss: 0.2,
+        latency: Duration::from_millis(40),
+        jitter: Duration::from_millis(40),
+        duplicate: 0.1,
+        reorder: 0.1,
+    }
+}
+
+/*   TEST_SERVER   */
+/// The server app, updated on a thread of its own until dropped
+pub struct TestServer {
+    pub addr: SocketAddr,
+    /// Admin socket, for setting things up the players can't
+    pub admin: SocketAddr,
+    pub stop: Arc<AtomicBool>,
+    pub thread: Option<JoinHandle<()>>,
+}
+
+impl TestServer {
+    pub fn start(config: ServerConfig) -> TestServer {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        socket.set_nonblocking(true).unwrap();
+        let addr = socket.local_addr().unwrap();
+        //a free port, given back for the admin socket to take
+        let admin = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = stop.clone();
+        let (listening, ready) = mpsc::channel();
+        let thread = thread::spawn(move || {
+            let mut app = build_app(config, socket);
+            app.finish();
+            app.cleanup();
+            app.world()
+                .resource::<AdminConsole>()
+                .listen(admin)
+                .unwrap();
+            listening.send(()).unwrap();
+
+            //like app.run(), until it's told to stop or exits by itself
+            while !stopped.load(Ordering::Relaxed) && app.should_exit().is_none() {
+                app.update();
+                thread::sleep(Duration::from_millis(2));
+            }
+        });
+
+        ready.recv().unwrap();
+        TestServer {
+            addr,
+            admin,
+            stop,
+            thread: Some(thread),
+        }
+    }
+
+    /// Runs an admin command like the console would and hands back the answer,
+    /// every line of it once the server hangs up
+    pub fn admin(&self, line: &str) -> String {
+        let mut stream = TcpStream::connect(self.admin).unwrap();
+        writeln!(stream, "{}", line).unwrap();
+        stream.shutdown(Shutdown::Write).unwrap();
+        let mut reply = String::new();
+        stream.read_to_string(&mut reply).unwrap();
+        reply.trim().to_string()
+    }
+}
+
+impl TestServer {
+    /// A netsim proxy in front of the server, clients talk to it instead
+    pub fn proxy(&self, link: LinkConditions, seed: u64) -> LinkProxy {
+        LinkProxy::spawn("127.0.0.1:0".parse().unwrap(), self.addr, link, seed).unwrap()
+    }
+
+    /// Has the admin spawn an ocean enemy at (`x`, `y`) and hands back its id
+    pub fn spawn(&self, etype: &str, x: f32, y: f32) -> i32 {
+        let reply = self.admin(&format!("spawn {} {} {}", etype, x, y));
+        reply
+            .split(['[', ']'])
+            .nth(1)
+            .and_then(|id| id.parse().ok())
+            .unwrap_or_else(|| panic!("couldn't spawn a {}: {}", etype, reply))
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/*   TEST_CLIENT   */
+/// A scripted player. Sends whatever the test tells it to and keeps everything
+/// the server sends in an inbox, in the order the game would have seen it.
+/// Like the game it never stops listening, resending and sending heartbeats,
+/// whichever client the test happens to be waiting on
+pub struct TestClient {
+    pub name: &'static str,
+    pub id: i32,
+    /// What the server handed out for getting back into the slot after dropping
+    pub token: u64,
+    pub wire: Arc<Mutex<Wire>>,
+    pub stop: Arc<AtomicBool>,
+    pub thread: Option<JoinHandle<()>>,
+}
+
+/// The client's end of the connection, kept going by the client's thread
+pub struct Wire {
+    socket: UdpSocket,
+    server: SocketAddr,
+    connection: Connection<ServerMessage>,
+    inbox: VecDeque<ServerMessage>,
+    last_heartbeat: Instant,
+}
+
+impl Wire {
+    pub fn send(&mut self, message: &ClientMessage, channel: Channel) {
+        self.connection
+            .send(&self.socket, self.server, message, channel)
+            .unwrap();
+    }
+
+    /// Reads everything waiting on the socket into the inbox, resends what
+    /// hasn't been acked and keeps the heartbeat going
+    pub fn poll(&mut self) {
+        let mut buf = vec![0; MAX_PACKET_SIZE];
+        while let Ok((bytes, _)) = self.socket.recv_from(&mut buf) {
+            if let Ok(messages) = self
+                .connection
+                .receive(&self.socket, self.server, &buf[..bytes])
+            {
+                self.inbox.extend(messages);
+            }
+        }
+
+        let now = Instant::now();
+        self.connection.resend(&self.socket, self.server, now);
+        if now.duration_since(self.last_heartbeat) >= HEARTBEAT_INTERVAL {
+            self.last_heartbeat = now;
+            self.send(&ClientMessage::Heartbeat, Channel::Unreliable);
+        }
+    }
+}
+
+impl TestClient {
+    pub fn new(name: &'static str, server: SocketAddr) -> TestClient {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        socket.set_nonblocking(true).unwrap();
+
+        let wire = Arc::new(Mutex::new(Wire {
+            socket,
+            server,
+            connection: Connection::new(),
+            inbox: VecDeque::new(),
+            last_heartbeat: Instant::now(),
+        }));
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let polled = wire.clone();
+        let stopped = stop.clone();
+        let thread = thread::spawn(move || {
+            while !stopped.load(Ordering::Relaxed) {
+                polled.lock().unwrap().poll();
+                thread::sleep(Duration::from_millis(1));
+            }
+        });
+
+        TestClient {
+            name,
+            id: -1,
+            token: 0,
+            wire,
+            stop,
+            thread: Some(thread),
+        }
+    }
+
+    pub fn send(&self, message: &ClientMessage, channel: Channel) {
+        self.wire.lock().unwrap().send(message, channel);
+    }
+
+    /// Where the server sees this client's packets come from
+    pub fn addr(&self) -> SocketAddr {
+        self.wire.lock().unwrap().socket.local_addr().unwrap()
+    }
+
+    /// Hands every message to `found` until it picks one out, skipping the ones
+    /// it doesn't want. Gives up at `end`
+    pub fn find<T>(
+        &self,
+        end: Instant,
+        found: &mut impl FnMut(ServerMessage) -> Option<T>,
+    ) -> Option<T> {
+        while Instant::now() < end {
+            let next = self.wire.lock().unwrap().inbox.pop_front();
+            match next {
+                Some(message) => {
+                    if let Some(result) = found(message) {
+                        return Some(result);
+                    }
+                }
+                None => thread::sleep(Duration::from_millis(1)),
+            }
+        }
+        None
+    }
+
+    /// Like find, but fails the test after PATIENCE
+    pub fn wait_for<T>(&self, what: &str, mut found: impl FnMut(ServerMessage) -> Option<T>) -> T {
+        match self.find(Instant::now() + PATIENCE, &mut found) {
+            Some(result) => result,
+            None => panic!("{} gave up waiting for {}", self.name, what),
+        }
+    }
+
+    /// Everything that arrives over the next while
+    pub fn listen(&self, time: Duration) -> Vec<ServerMessage> {
+        thread::sleep(time);
+        self.wire.lock().unwrap().inbox.drain(..).collect()
+    }
+
+    /// Asks to join like the game does, again and again until the server answers.
+    /// Hands back the code of the room it was put in
+    pub fn join(&mut self, room: RoomChoice) -> String {
+        let end = Instant::now() + PATIENCE;
+        let player = Player {
+            name: self.name.to_string(),
+            ..default()
+        };
+
+        while Instant::now() < end {
+            self.send(
+                &ClientMessage::NewPlayer {
+                    player: player.clone(),
+                    room: room.clone(),
+                },
+                Channel::Unreliable,
+            );
+
+            let answer = self.find(Instant::now() + JOIN_RETRY, &mut |message| match message {
+                ServerMessage::JoinedLobby {
+                    id, room, token, ..
+                } => Some((id, room, token)),
+                ServerMessage::JoinRejected(reason) => {
+                    panic!("{} was turned away: {}", self.name, reason)
+                }
+                _ => None,
+            });
+            if let Some((id, room, token)) = answer {
+                self.id = id;
+                self.token = token;
+                return room;
+            }
+        }
+
+        panic!("{} never got into a room", self.name);
+    }
+
+    /// Waits for the room's roster to have `players` players in it, all ready
+    pub fn wait_for_ready(&self, players: usize) {
+        self.wait_for("everyone to be ready", |message| match message {
+            ServerMessage::Roster(roster)
+                if roster.players.len() == players && roster.all_ready() =>
+            {
+                Some(())
+            }
+            _ => None,
+        });
+    }
+
+    pub fn wait_for_session(&self) {
+        self.wait_for("the session to start", |message| match message {
+            ServerMessage::SessionStarted => Some(()),
+            _ => None,
+        });
+    }
+}
+
+impl Drop for TestClient {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Gets every client into the same room and out to sea. The first client hosts
+pub fn set_sail(clients: &mut [&mut TestClient]) {
+    let code = clients[0].join(RoomChoice::Create { seed: Some(7) });
+    for client in clients.iter_mut().skip(1) {
+        let joined = client.join(RoomChoice::Join { code: code.clone() });
+        assert_eq!(joined, code);
+    }
+
+    for client in clients.iter_mut() {
+        client.send(&ClientMessage::SetReady(true), Channel::Reliable);
+    }
+    clients[0].wait_for_ready(clients.len());
+    clients[0].send(&ClientMessage::StartSession, Channel::Reliable);
+
+    //straight out to sea, like the game once the crew leaves the start island
+    for client in clients.iter_mut() {
+        client.wait_for_session();
+        client.send(&ClientMessage::AreaChanged(Area::Ocean), Channel::Reliable);
+    }
+}
+
+/// Puts a client's boat in the water, and has the admin move it to (`x`, `y`)
+/// once it's there. Boats always go in at the spawn, the server doesn't take a
+/// client's word for where its boat is
+pub fn launch(server: &TestServer, client: &TestClient, x: f32, y: f32) {
+    client.send(&ClientMessage::BoatSpawned, Channel::Reliable);
+    let id = client.id;
+
+    //until boat_spawned gets through there's no boat to move
+    let deadline = Instant::now() + PATIENCE;
+    loop {
+        let reply = server.admin(&format!("teleport {} {} {}", id, x, y));
+        if reply.starts_with("Moved") {
+            break;
+        }
+        assert!(
+            Instant::now() < deadline,
+            "couldn't move the boat: {}",
+            reply
+        );
+        thread::sleep(Duration::from_millis(20));
+    }
+
+    client.wait_for("our boat where the admin put it", |message| match message {
+        ServerMessage::Snapshot(snapshot) => snapshot
+            .boats
+            .iter()
+            .any(|boat| boat.id == id && boat.state.pos.truncate().distance(Vec2::new(x, y)) < 1.)
+            .then_some(()),
+        _ => None,
+    });
+}
+
+/// Whether a snapshot has a boat afloat for every one of `ids`
+pub fn boats_afloat(message: &ServerMessage, ids: &[i32]) -> bool {
+    match message {
+        ServerMessage::Snapshot(snapshot) => ids
+            .iter()
+            .all(|id| snapshot.boats.iter().any(|boat| boat.id == *id)),
+        _ => false,
+    }
+}